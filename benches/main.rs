@@ -1,7 +1,7 @@
 use std::hint::black_box;
 
 use haste::{Haste, Label};
-use sha3::sha3_256;
+use sha3::{KeccakF1600, sha3_256};
 
 fn main() {
     haste::main();
@@ -34,3 +34,94 @@ fn bench_libcrux_sha256(mut haste: Haste) {
             });
     }
 }
+
+/// Latency of hashing a short message, dominated by call overhead and a single
+/// permutation.
+#[haste::bench]
+fn bench_sha256_small(mut haste: Haste) {
+    let input = [0; 32];
+    haste
+        .with_sample_count(50)
+        .bench(Label::new("sha256 latency").with_part(input.len()), || {
+            sha3_256(black_box(&input))
+        });
+}
+
+#[haste::bench]
+fn bench_libcrux_sha256_small(mut haste: Haste) {
+    let input = [0; 32];
+    haste.with_sample_count(50).bench(
+        Label::new("libcrux sha256 latency").with_part(input.len()),
+        || libcrux_sha3::sha256(black_box(&input)),
+    );
+}
+
+#[haste::bench]
+fn bench_permutation(mut haste: Haste) {
+    let mut state = KeccakF1600::new();
+    haste
+        .with_sample_count(50)
+        .bench(Label::new("keccak-f[1600]"), || {
+            black_box(&mut state).permute()
+        });
+}
+
+/// Bindings to the XKCP compact C implementation, which serves as a baseline
+/// independent of other Rust implementations.
+#[cfg(all(feature = "xkcp-bench", unix))]
+mod xkcp {
+    use std::ffi::{c_uint, c_void};
+
+    unsafe extern "C" {
+        fn KeccakF1600_StatePermute(state: *mut c_void);
+        fn FIPS202_SHA3_256(input: *const u8, input_byte_len: c_uint, output: *mut u8);
+    }
+
+    pub fn permute(state: &mut [u8; 200]) {
+        // SAFETY: The permutation reads and writes exactly 200 bytes.
+        unsafe { KeccakF1600_StatePermute(state.as_mut_ptr().cast()) }
+    }
+
+    pub fn sha3_256(input: &[u8]) -> [u8; 32] {
+        let mut output = [0; 32];
+        let len = input.len().try_into().expect("input too long");
+        // SAFETY: input is valid for len bytes and output for 32 bytes.
+        unsafe { FIPS202_SHA3_256(input.as_ptr(), len, output.as_mut_ptr()) };
+        output
+    }
+}
+
+#[cfg(all(feature = "xkcp-bench", unix))]
+#[haste::bench]
+fn bench_xkcp_sha256(mut haste: Haste) {
+    let sizes = [32, 1024, 1024 * 1024];
+    for size in sizes {
+        let input = vec![0; size];
+        assert_eq!(
+            sha3_256(&input),
+            xkcp::sha3_256(&input),
+            "XKCP and sha3 differ"
+        );
+        haste
+            .with_throughput(haste::Throughput::Bytes(size))
+            .with_sample_count(50)
+            .bench(Label::new("xkcp sha256").with_part(size), || {
+                xkcp::sha3_256(black_box(&input))
+            });
+    }
+}
+
+#[cfg(all(feature = "xkcp-bench", unix))]
+#[haste::bench]
+fn bench_xkcp_permutation(mut haste: Haste) {
+    let mut state = [0; 200];
+    let mut expected = KeccakF1600::new();
+    expected.permute();
+    xkcp::permute(&mut state);
+    assert_eq!(expected.to_bytes(), state, "XKCP and sha3 differ");
+    haste
+        .with_sample_count(50)
+        .bench(Label::new("xkcp keccak-f[1600]"), || {
+            xkcp::permute(black_box(&mut state))
+        });
+}
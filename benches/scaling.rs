@@ -0,0 +1,71 @@
+//! Throughput of the batch API on 1, 2, 4, … N threads.
+//!
+//! Every thread hashes its own batch of messages with `sha3_256_batch_into`.
+//! The scaling efficiency is the aggregate throughput relative to the
+//! throughput of one thread times the number of threads. Efficiencies well
+//! below 100% indicate contention or false sharing, e.g., of the digests of
+//! neighboring threads.
+//!
+//! Run with `cargo bench --bench scaling`.
+
+use std::{
+    hint::black_box,
+    thread,
+    time::{Duration, Instant},
+};
+
+use sha3::sha3_256_batch_into;
+
+/// Length of each message in bytes
+const MESSAGE_LEN: usize = 1024;
+/// Number of messages in the batch of each thread
+const BATCH_LEN: usize = 256;
+/// Number of batches each thread hashes per sample
+const ROUNDS: usize = 20;
+/// Number of samples per thread count, of which the fastest is reported
+const SAMPLES: usize = 5;
+
+fn main() {
+    let max_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let messages: Vec<Vec<u8>> = (0..BATCH_LEN).map(|i| vec![i as u8; MESSAGE_LEN]).collect();
+    let messages: Vec<&[u8]> = messages.iter().map(Vec::as_slice).collect();
+
+    println!("threads  throughput  efficiency");
+    let mut single = None;
+    for threads in thread_counts(max_threads) {
+        let elapsed = (0..SAMPLES)
+            .map(|_| run(threads, &messages))
+            .min()
+            .expect("at least one sample");
+        let bytes = (threads * ROUNDS * BATCH_LEN * MESSAGE_LEN) as f64;
+        let throughput = bytes / elapsed.as_secs_f64() / (1024.0 * 1024.0);
+        let single = *single.get_or_insert(throughput);
+        let efficiency = throughput / (single * threads as f64) * 100.0;
+        println!("{threads:>7}  {throughput:>6.1} MiB/s  {efficiency:>9.1}%");
+    }
+}
+
+/// 1, 2, 4, … up to and including `max`.
+fn thread_counts(max: usize) -> Vec<usize> {
+    let mut counts: Vec<usize> = (0..).map(|i| 1 << i).take_while(|&n| n < max).collect();
+    counts.push(max);
+    counts
+}
+
+/// Time for `threads` threads to hash `ROUNDS` batches of `messages` each.
+fn run(threads: usize, messages: &[&[u8]]) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                // Each thread writes to its own digests
+                let mut digests = vec![[0; 32]; messages.len()];
+                for _ in 0..ROUNDS {
+                    sha3_256_batch_into(black_box(messages), &mut digests);
+                }
+                black_box(digests);
+            });
+        }
+    });
+    start.elapsed()
+}
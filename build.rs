@@ -0,0 +1,42 @@
+//! Builds the XKCP reference implementation for the comparison benchmarks if
+//! the `xkcp-bench` feature is enabled.
+
+use std::{env, path::PathBuf, process::Command};
+
+fn main() {
+    println!("cargo::rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_XKCP_BENCH").is_none() {
+        return;
+    }
+    println!("cargo::rerun-if-changed=benches/xkcp/Keccak-compact.c");
+    println!("cargo::rerun-if-env-changed=CC");
+    println!("cargo::rerun-if-env-changed=AR");
+
+    // The C implementation is compiled with the system C compiler. We only
+    // support unix-like targets for now, on all other targets the XKCP
+    // benchmarks are skipped.
+    if env::var("CARGO_CFG_TARGET_FAMILY").as_deref() != Ok("unix") {
+        println!("cargo::warning=xkcp-bench is only supported on unix targets");
+        return;
+    }
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let object = out_dir.join("Keccak-compact.o");
+    let archive = out_dir.join("libxkcp.a");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+    let ar = env::var("AR").unwrap_or_else(|_| "ar".to_owned());
+
+    run(Command::new(cc)
+        .args(["-O3", "-c", "benches/xkcp/Keccak-compact.c", "-o"])
+        .arg(&object));
+    run(Command::new(ar).arg("crs").arg(&archive).arg(&object));
+
+    println!("cargo::rustc-link-arg-benches={}", archive.display());
+}
+
+fn run(cmd: &mut Command) {
+    let status = cmd
+        .status()
+        .unwrap_or_else(|err| panic!("failed to run {cmd:?}: {err}"));
+    assert!(status.success(), "{cmd:?} failed with {status}");
+}
@@ -0,0 +1,61 @@
+/*
+ * C ABI of the sha3 crate, exported with the `ffi` feature.
+ *
+ * This header is maintained by hand. Update it together with src/ffi.rs
+ * whenever an exported function is added or its signature changes.
+ *
+ * Pointers to input and output buffers may be null if the corresponding
+ * length is 0. Hashers and SHAKE instances are allocated by the library and
+ * must be released with the matching _free function, unless they are consumed
+ * by sha3_hasher_finalize.
+ */
+
+#ifndef SHA3_H
+#define SHA3_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* One-shot SHA-3, writing the digest to out. */
+void sha3_224(const uint8_t *message, size_t message_len, uint8_t out[28]);
+void sha3_256(const uint8_t *message, size_t message_len, uint8_t out[32]);
+void sha3_384(const uint8_t *message, size_t message_len, uint8_t out[48]);
+void sha3_512(const uint8_t *message, size_t message_len, uint8_t out[64]);
+
+/* One-shot SHAKE, filling out_len bytes of out. */
+void sha3_shake128(const uint8_t *message, size_t message_len, uint8_t *out, size_t out_len);
+void sha3_shake256(const uint8_t *message, size_t message_len, uint8_t *out, size_t out_len);
+
+/* Incremental SHA-3 hasher. */
+typedef struct Sha3Hasher Sha3Hasher;
+
+/* Returns NULL unless output_bits is 224, 256, 384 or 512. */
+Sha3Hasher *sha3_hasher_new(size_t output_bits);
+void sha3_hasher_update(Sha3Hasher *hasher, const uint8_t *data, size_t data_len);
+/*
+ * Writes the digest and frees hasher. Returns 0 on success and -1 if out_len
+ * is not the output size in bytes.
+ */
+int sha3_hasher_finalize(Sha3Hasher *hasher, uint8_t *out, size_t out_len);
+void sha3_hasher_free(Sha3Hasher *hasher);
+
+/* SHAKE instance, which absorbs until the first squeeze. */
+typedef struct Sha3Shake Sha3Shake;
+
+/* Returns NULL unless security is 128 or 256. */
+Sha3Shake *sha3_shake_new(size_t security);
+/* Returns 0 on success and -1 if shake has already been squeezed. */
+int sha3_shake_update(Sha3Shake *shake, const uint8_t *data, size_t data_len);
+/* Consecutive calls continue the output stream. */
+void sha3_shake_squeeze(Sha3Shake *shake, uint8_t *out, size_t out_len);
+void sha3_shake_free(Sha3Shake *shake);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SHA3_H */
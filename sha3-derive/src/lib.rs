@@ -0,0 +1,83 @@
+//! Derive macro for the `Sha3Digestible` trait of the `sha3` crate
+//!
+//! Use it through the `derive` feature of `sha3`, which re-exports the macro
+//! as `sha3::digestible::Sha3Digest`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Index, parse_macro_input, parse_quote};
+
+/// Derive `Sha3Digestible` for a struct.
+///
+/// The encoding of a struct is the length-prefixed name of the type, the
+/// number of fields as a 64-bit little-endian integer, and the encodings of
+/// all fields in declaration order. All fields must implement
+/// `Sha3Digestible`. Enums and unions are not supported.
+#[proc_macro_derive(Sha3Digest)]
+pub fn derive_sha3_digest(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(_) | Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "Sha3Digestible can only be derived for structs",
+            ));
+        }
+    };
+    let members: Vec<TokenStream2> = match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| {
+                let ident = &field.ident;
+                quote!(#ident)
+            })
+            .collect(),
+        Fields::Unnamed(fields) => (0..fields.unnamed.len())
+            .map(|i| {
+                let index = Index::from(i);
+                quote!(#index)
+            })
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+    let field_count = members.len() as u64;
+    let field_types: Vec<_> = fields.iter().map(|field| field.ty.clone()).collect();
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let where_clause = input.generics.make_where_clause();
+    for ty in &field_types {
+        where_clause
+            .predicates
+            .push(parse_quote!(#ty: ::sha3::digestible::Sha3Digestible));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // The const generic of the method must not shadow a const generic of the
+    // type, so it has a name that is unlikely to clash
+    Ok(quote! {
+        impl #impl_generics ::sha3::digestible::Sha3Digestible for #ident #ty_generics
+        #where_clause
+        {
+            fn absorb_into<const __SHA3_N: usize>(
+                &self,
+                hasher: &mut ::sha3::Hasher<__SHA3_N>,
+            ) {
+                ::sha3::digestible::Sha3Digestible::absorb_into(#name, hasher);
+                ::sha3::digestible::Sha3Digestible::absorb_into(&#field_count, hasher);
+                #(
+                    ::sha3::digestible::Sha3Digestible::absorb_into(&self.#members, hasher);
+                )*
+            }
+        }
+    })
+}
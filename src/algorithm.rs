@@ -0,0 +1,521 @@
+//! Runtime selection of the hash function
+//!
+//! [`Sha3Algorithm`] names one of the SHA-3 hash functions or SHAKE
+//! extendable-output functions, so that the algorithm can be chosen at
+//! runtime, e.g., from a command line flag or during protocol negotiation.
+//! [`DynHasher`] is the incremental hasher for an algorithm chosen at
+//! runtime.
+//!
+//! The algorithms also provide their object identifiers and the DER encoded
+//! `DigestInfo` for RSA signatures and X.509 tooling.
+//!
+//! [`DynDigest`] is an object-safe trait implemented by all hashers, so that
+//! they can be stored as `Box<dyn DynDigest>`, e.g., in a registry of
+//! algorithms.
+//!
+//! The SHAKE functions produce a digest of fixed length when used through
+//! this module: 32 bytes for SHAKE128 and 64 bytes for SHAKE256, i.e., twice
+//! their security strength. This matches the output length of SHA3-256 and
+//! SHA3-512 with the same security level.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::algorithm::Sha3Algorithm;
+//! #
+//! let algorithm = Sha3Algorithm::Sha3_256;
+//! let mut hasher = algorithm.hasher();
+//! hasher.update(b"your input ");
+//! hasher.update(b"bytes");
+//! let digest = hasher.finalize();
+//! assert_eq!(algorithm.digest(b"your input bytes"), digest);
+//! assert_eq!(sha3::sha3_256(b"your input bytes")[..], digest);
+//! ```
+
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::{Hasher, Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256};
+
+/// SHA-3 hash function or SHAKE extendable-output function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sha3Algorithm {
+    /// SHA3-224
+    Sha3_224,
+    /// SHA3-256
+    Sha3_256,
+    /// SHA3-384
+    Sha3_384,
+    /// SHA3-512
+    Sha3_512,
+    /// SHAKE128 with 32 bytes output
+    Shake128,
+    /// SHAKE256 with 64 bytes output
+    Shake256,
+}
+
+impl Sha3Algorithm {
+    /// All algorithms.
+    pub const ALL: [Self; 6] = [
+        Self::Sha3_224,
+        Self::Sha3_256,
+        Self::Sha3_384,
+        Self::Sha3_512,
+        Self::Shake128,
+        Self::Shake256,
+    ];
+
+    /// Length of the digest in bytes.
+    pub fn output_size(self) -> usize {
+        match self {
+            Self::Sha3_224 => 28,
+            Self::Sha3_256 | Self::Shake128 => 32,
+            Self::Sha3_384 => 48,
+            Self::Sha3_512 | Self::Shake256 => 64,
+        }
+    }
+
+    /// Object identifier of the algorithm assigned by NIST in dotted
+    /// notation, e.g., `2.16.840.1.101.3.4.2.8` for SHA3-256.
+    pub fn oid(self) -> &'static str {
+        match self {
+            Self::Sha3_224 => "2.16.840.1.101.3.4.2.7",
+            Self::Sha3_256 => "2.16.840.1.101.3.4.2.8",
+            Self::Sha3_384 => "2.16.840.1.101.3.4.2.9",
+            Self::Sha3_512 => "2.16.840.1.101.3.4.2.10",
+            Self::Shake128 => "2.16.840.1.101.3.4.2.11",
+            Self::Shake256 => "2.16.840.1.101.3.4.2.12",
+        }
+    }
+
+    /// DER encoding of the contents of the object identifier, i.e., without
+    /// tag and length, as accepted by `ObjectIdentifier::from_bytes` of the
+    /// `const-oid` crate.
+    pub fn oid_bytes(self) -> &'static [u8] {
+        const fn nist_hash_algorithm(arc: u8) -> [u8; 9] {
+            [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, arc]
+        }
+        const SHA3_224: [u8; 9] = nist_hash_algorithm(7);
+        const SHA3_256: [u8; 9] = nist_hash_algorithm(8);
+        const SHA3_384: [u8; 9] = nist_hash_algorithm(9);
+        const SHA3_512: [u8; 9] = nist_hash_algorithm(10);
+        const SHAKE128: [u8; 9] = nist_hash_algorithm(11);
+        const SHAKE256: [u8; 9] = nist_hash_algorithm(12);
+        match self {
+            Self::Sha3_224 => &SHA3_224,
+            Self::Sha3_256 => &SHA3_256,
+            Self::Sha3_384 => &SHA3_384,
+            Self::Sha3_512 => &SHA3_512,
+            Self::Shake128 => &SHAKE128,
+            Self::Shake256 => &SHAKE256,
+        }
+    }
+
+    /// DER encoding of the `DigestInfo` structure of PKCS #1 ([RFC 8017])
+    /// for `digest`, as used in RSASSA-PKCS1-v1_5 signatures.
+    ///
+    /// The parameters of the algorithm identifier are NULL for the SHA-3
+    /// hash functions, as in common implementations, and absent for SHAKE as
+    /// required by [RFC 8702].
+    ///
+    /// # Panics
+    /// If `digest` does not have [`output_size`](Self::output_size) bytes.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::algorithm::Sha3Algorithm;
+    /// #
+    /// let algorithm = Sha3Algorithm::Sha3_256;
+    /// let digest_info = algorithm.digest_info(&algorithm.digest(b"message"));
+    /// assert_eq!(19 + 32, digest_info.len());
+    /// ```
+    ///
+    /// [RFC 8017]: https://www.rfc-editor.org/rfc/rfc8017
+    /// [RFC 8702]: https://www.rfc-editor.org/rfc/rfc8702
+    pub fn digest_info(self, digest: &[u8]) -> Vec<u8> {
+        const SEQUENCE: u8 = 0x30;
+        const OCTET_STRING: u8 = 0x04;
+        const NULL: u8 = 0x05;
+        const OBJECT_IDENTIFIER: u8 = 0x06;
+
+        assert_eq!(
+            self.output_size(),
+            digest.len(),
+            "digest has the wrong length"
+        );
+        let oid = self.oid_bytes();
+        let parameters: &[u8] = match self {
+            Self::Shake128 | Self::Shake256 => &[],
+            _ => &[NULL, 0],
+        };
+        // All lengths are below 128 and thus use the short form
+        let algorithm_len = 2 + oid.len() + parameters.len();
+        let total_len = 2 + algorithm_len + 2 + digest.len();
+        let mut der = Vec::with_capacity(2 + total_len);
+        der.extend([SEQUENCE, total_len as u8]);
+        der.extend([SEQUENCE, algorithm_len as u8]);
+        der.extend([OBJECT_IDENTIFIER, oid.len() as u8]);
+        der.extend_from_slice(oid);
+        der.extend_from_slice(parameters);
+        der.extend([OCTET_STRING, digest.len() as u8]);
+        der.extend_from_slice(digest);
+        der
+    }
+
+    /// Create a new incremental hasher for the algorithm.
+    pub fn hasher(self) -> DynHasher {
+        let inner = match self {
+            Self::Sha3_224 => Inner::Sha3_224(Sha3_224::new()),
+            Self::Sha3_256 => Inner::Sha3_256(Sha3_256::new()),
+            Self::Sha3_384 => Inner::Sha3_384(Sha3_384::new()),
+            Self::Sha3_512 => Inner::Sha3_512(Sha3_512::new()),
+            Self::Shake128 => Inner::Shake128(Shake128::new()),
+            Self::Shake256 => Inner::Shake256(Shake256::new()),
+        };
+        DynHasher { inner }
+    }
+
+    /// Hash `message` with the algorithm.
+    pub fn digest(self, message: &[u8]) -> Vec<u8> {
+        let mut hasher = self.hasher();
+        hasher.update(message);
+        hasher.finalize()
+    }
+}
+
+/// Formats the algorithm with its name from FIPS 202, e.g., `SHA3-256` or
+/// `SHAKE128`.
+impl fmt::Display for Sha3Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Sha3_224 => "SHA3-224",
+            Self::Sha3_256 => "SHA3-256",
+            Self::Sha3_384 => "SHA3-384",
+            Self::Sha3_512 => "SHA3-512",
+            Self::Shake128 => "SHAKE128",
+            Self::Shake256 => "SHAKE256",
+        })
+    }
+}
+
+/// Parses the name of an algorithm, e.g., `sha3-256` or `shake128`.
+///
+/// Parsing is case-insensitive and accepts `_` instead of `-`, e.g.,
+/// `SHA3_256`.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::algorithm::Sha3Algorithm;
+/// #
+/// let algorithm: Sha3Algorithm = "sha3-384".parse().unwrap();
+/// assert_eq!(Sha3Algorithm::Sha3_384, algorithm);
+/// assert_eq!(Ok(algorithm), "SHA3_384".parse());
+/// assert_eq!("SHA3-384", algorithm.to_string());
+/// assert!("sha2-256".parse::<Sha3Algorithm>().is_err());
+/// ```
+impl FromStr for Sha3Algorithm {
+    type Err = ParseAlgorithmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().replace('_', "-").as_str() {
+            "sha3-224" => Ok(Self::Sha3_224),
+            "sha3-256" => Ok(Self::Sha3_256),
+            "sha3-384" => Ok(Self::Sha3_384),
+            "sha3-512" => Ok(Self::Sha3_512),
+            "shake128" => Ok(Self::Shake128),
+            "shake256" => Ok(Self::Shake256),
+            _ => Err(ParseAlgorithmError { name: s.to_owned() }),
+        }
+    }
+}
+
+/// Error returned when parsing an unknown [`Sha3Algorithm`] name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseAlgorithmError {
+    name: String,
+}
+
+impl fmt::Display for ParseAlgorithmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown SHA-3 algorithm `{}`", self.name)
+    }
+}
+
+impl Error for ParseAlgorithmError {}
+
+/// Incremental hasher for a [`Sha3Algorithm`] chosen at runtime.
+#[derive(Debug, Clone)]
+pub struct DynHasher {
+    inner: Inner,
+}
+
+#[derive(Debug, Clone)]
+enum Inner {
+    Sha3_224(Sha3_224),
+    Sha3_256(Sha3_256),
+    Sha3_384(Sha3_384),
+    Sha3_512(Sha3_512),
+    Shake128(Shake128),
+    Shake256(Shake256),
+}
+
+impl DynHasher {
+    /// Algorithm of the hasher.
+    pub fn algorithm(&self) -> Sha3Algorithm {
+        match self.inner {
+            Inner::Sha3_224(_) => Sha3Algorithm::Sha3_224,
+            Inner::Sha3_256(_) => Sha3Algorithm::Sha3_256,
+            Inner::Sha3_384(_) => Sha3Algorithm::Sha3_384,
+            Inner::Sha3_512(_) => Sha3Algorithm::Sha3_512,
+            Inner::Shake128(_) => Sha3Algorithm::Shake128,
+            Inner::Shake256(_) => Sha3Algorithm::Shake256,
+        }
+    }
+
+    /// Absorb `data` into the hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.inner {
+            Inner::Sha3_224(hasher) => hasher.update(data),
+            Inner::Sha3_256(hasher) => hasher.update(data),
+            Inner::Sha3_384(hasher) => hasher.update(data),
+            Inner::Sha3_512(hasher) => hasher.update(data),
+            Inner::Shake128(shake) => shake.update(data),
+            Inner::Shake256(shake) => shake.update(data),
+        }
+    }
+
+    /// Finish the computation and return the digest of
+    /// [`output_size`](Sha3Algorithm::output_size) bytes.
+    pub fn finalize(self) -> Vec<u8> {
+        let mut digest = vec![0; self.algorithm().output_size()];
+        match self.inner {
+            Inner::Sha3_224(hasher) => digest.copy_from_slice(&hasher.finalize()),
+            Inner::Sha3_256(hasher) => digest.copy_from_slice(&hasher.finalize()),
+            Inner::Sha3_384(hasher) => digest.copy_from_slice(&hasher.finalize()),
+            Inner::Sha3_512(hasher) => digest.copy_from_slice(&hasher.finalize()),
+            Inner::Shake128(shake) => shake.finalize_xof().squeeze(&mut digest),
+            Inner::Shake256(shake) => shake.finalize_xof().squeeze(&mut digest),
+        }
+        digest
+    }
+}
+
+/// Object-safe interface of the hashers in this crate.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::{Sha3_512, Shake128, algorithm::DynDigest};
+/// #
+/// let mut hashers: Vec<Box<dyn DynDigest>> =
+///     vec![Box::new(Sha3_512::new()), Box::new(Shake128::new())];
+/// for hasher in &mut hashers {
+///     hasher.update(b"your input bytes");
+/// }
+/// let digests: Vec<Box<[u8]>> = hashers.into_iter().map(|h| h.finalize_boxed()).collect();
+/// assert_eq!(sha3::sha3_512(b"your input bytes")[..], *digests[0]);
+/// assert_eq!(32, digests[1].len());
+/// ```
+pub trait DynDigest {
+    /// Absorb `data` into the hasher.
+    fn update(&mut self, data: &[u8]);
+
+    /// Finish the computation and return the digest of
+    /// [`output_size`](Self::output_size) bytes.
+    fn finalize_boxed(self: Box<Self>) -> Box<[u8]>;
+
+    /// Length of the digest in bytes.
+    ///
+    /// For SHAKE, this is the fixed output length described in the
+    /// [module documentation](self).
+    fn output_size(&self) -> usize;
+
+    /// Reset the hasher to its initial state.
+    fn reset(&mut self);
+}
+
+impl<const N: usize> DynDigest for Hasher<N> {
+    fn update(&mut self, data: &[u8]) {
+        Hasher::update(self, data);
+    }
+
+    fn finalize_boxed(self: Box<Self>) -> Box<[u8]> {
+        Box::from(self.finalize())
+    }
+
+    fn output_size(&self) -> usize {
+        N
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+macro_rules! impl_dyn_digest_shake {
+    ($shake:ty, $algorithm:expr) => {
+        impl DynDigest for $shake {
+            fn update(&mut self, data: &[u8]) {
+                <$shake>::update(self, data);
+            }
+
+            fn finalize_boxed(self: Box<Self>) -> Box<[u8]> {
+                let mut digest = vec![0; $algorithm.output_size()];
+                self.finalize_xof().squeeze(&mut digest);
+                digest.into_boxed_slice()
+            }
+
+            fn output_size(&self) -> usize {
+                $algorithm.output_size()
+            }
+
+            fn reset(&mut self) {
+                *self = Self::new();
+            }
+        }
+    };
+}
+
+impl_dyn_digest_shake!(Shake128, Sha3Algorithm::Shake128);
+impl_dyn_digest_shake!(Shake256, Sha3Algorithm::Shake256);
+
+impl DynDigest for DynHasher {
+    fn update(&mut self, data: &[u8]) {
+        DynHasher::update(self, data);
+    }
+
+    fn finalize_boxed(self: Box<Self>) -> Box<[u8]> {
+        self.finalize().into_boxed_slice()
+    }
+
+    fn output_size(&self) -> usize {
+        self.algorithm().output_size()
+    }
+
+    fn reset(&mut self) {
+        *self = self.algorithm().hasher();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DynDigest, Sha3Algorithm};
+    use crate::{
+        Sha3_224, Sha3_256, Sha3_384, Sha3_512, Shake128, Shake256, sha3_224, sha3_256, sha3_384,
+        sha3_512, shake128, shake256,
+    };
+
+    #[test]
+    fn matches_static_functions() {
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        let mut shake128_out = [0; 32];
+        shake128(&input, &mut shake128_out);
+        let mut shake256_out = [0; 64];
+        shake256(&input, &mut shake256_out);
+        let expected: [&[u8]; 6] = [
+            &sha3_224(&input),
+            &sha3_256(&input),
+            &sha3_384(&input),
+            &sha3_512(&input),
+            &shake128_out,
+            &shake256_out,
+        ];
+        for (algorithm, expected) in Sha3Algorithm::ALL.into_iter().zip(expected) {
+            assert_eq!(expected, algorithm.digest(&input), "{algorithm:?}");
+            assert_eq!(algorithm.output_size(), expected.len());
+
+            let mut hasher = algorithm.hasher();
+            assert_eq!(algorithm, hasher.algorithm());
+            for chunk in input.chunks(17) {
+                hasher.update(chunk);
+            }
+            assert_eq!(expected, hasher.finalize(), "{algorithm:?}");
+        }
+    }
+
+    #[test]
+    fn names_roundtrip() {
+        for algorithm in Sha3Algorithm::ALL {
+            let name = algorithm.to_string();
+            assert_eq!(Ok(algorithm), name.parse());
+            assert_eq!(Ok(algorithm), name.to_lowercase().parse());
+            assert_eq!(Ok(algorithm), name.replace('-', "_").parse());
+        }
+        assert_eq!(Ok(Sha3Algorithm::Shake256), "Shake256".parse());
+        for name in ["", "sha3", "sha3-128", "shake-128", " sha3-256"] {
+            let err = name.parse::<Sha3Algorithm>().unwrap_err();
+            assert_eq!(format!("unknown SHA-3 algorithm `{name}`"), err.to_string());
+        }
+    }
+
+    #[test]
+    fn digest_info() {
+        // Prefixes used by OpenSSL for RSASSA-PKCS1-v1_5
+        let prefixes = [
+            "302d300d06096086480165030402070500041c",
+            "3031300d060960864801650304020805000420",
+            "3041300d060960864801650304020905000430",
+            "3051300d060960864801650304020a05000440",
+        ];
+        for (algorithm, prefix) in Sha3Algorithm::ALL.into_iter().zip(prefixes) {
+            let digest = algorithm.digest(b"message");
+            let digest_info = algorithm.digest_info(&digest);
+            assert_eq!(prefix, hex::encode(&digest_info[..19]), "{algorithm:?}");
+            assert_eq!(digest, digest_info[19..]);
+        }
+        // Absent parameters for SHAKE
+        let digest_info = Sha3Algorithm::Shake256.digest_info(&[0; 64]);
+        assert_eq!(
+            "304f300b060960864801650304020c0440",
+            hex::encode(&digest_info[..17])
+        );
+        assert_eq!(81, digest_info.len());
+    }
+
+    #[test]
+    fn oids() {
+        for algorithm in Sha3Algorithm::ALL {
+            // Only the last arc differs and is below 128, so it is encoded in
+            // the last byte
+            let last_arc = algorithm.oid().rsplit('.').next().unwrap();
+            assert_eq!(
+                last_arc.parse::<u8>().unwrap(),
+                *algorithm.oid_bytes().last().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic = "digest has the wrong length"]
+    fn digest_info_checks_length() {
+        Sha3Algorithm::Sha3_256.digest_info(&[0; 28]);
+    }
+
+    #[test]
+    fn dyn_digest() {
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        let hashers: Vec<Box<dyn DynDigest>> = vec![
+            Box::new(Sha3_224::new()),
+            Box::new(Sha3_256::new()),
+            Box::new(Sha3_384::new()),
+            Box::new(Sha3_512::new()),
+            Box::new(Shake128::new()),
+            Box::new(Shake256::new()),
+        ];
+        let dyn_hashers = Sha3Algorithm::ALL
+            .into_iter()
+            .map(|algorithm| Box::new(algorithm.hasher()) as Box<dyn DynDigest>);
+        for (algorithm, mut hasher) in Sha3Algorithm::ALL
+            .into_iter()
+            .cycle()
+            .zip(hashers.into_iter().chain(dyn_hashers))
+        {
+            assert_eq!(algorithm.output_size(), hasher.output_size());
+            hasher.update(b"discarded by reset");
+            hasher.reset();
+            hasher.update(&input);
+            assert_eq!(
+                algorithm.digest(&input),
+                *hasher.finalize_boxed(),
+                "{algorithm:?}"
+            );
+        }
+    }
+}
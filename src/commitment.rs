@@ -0,0 +1,125 @@
+//! Commit-reveal commitments
+//!
+//! A commitment binds to a message without revealing it. The committer
+//! publishes the [`Commitment`] and later reveals the message together with
+//! the [`Opening`], which anyone can check with [`verify`].
+//!
+//! The opening is a random 32 byte blinder. The commitment is the keyed
+//! SHA3-256 of the message with the blinder as key, see
+//! [`prefix_mac`](crate::prefix_mac). It is hiding as long as the blinder is
+//! secret and uniformly random, and binding due to the collision resistance
+//! of SHA3-256.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::commitment::{Opening, commit_with, verify};
+//! #
+//! // The opening must be sampled from a cryptographically secure RNG, e.g.,
+//! // with `commit` and the `rand_core` feature
+//! let opening = Opening::from_bytes([0x42; 32]);
+//! let commitment = commit_with(b"my vote", &opening);
+//!
+//! // Later, the message and the opening are revealed
+//! assert!(verify(&commitment, b"my vote", &opening));
+//! assert!(!verify(&commitment, b"another vote", &opening));
+//! ```
+
+use crate::{ct::ct_eq, prefix_mac::sha3_256_keyed};
+
+/// Commitment to a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Commitment([u8; 32]);
+
+impl Commitment {
+    /// Create a commitment from its bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Bytes of the commitment.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Opening of a commitment, i.e., the random blinder.
+///
+/// The opening must be kept secret until the message is revealed.
+#[derive(Clone)]
+pub struct Opening([u8; 32]);
+
+impl Opening {
+    /// Create an opening from its bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Bytes of the opening.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+/// Commit to `message` with a random opening sampled from `rng`.
+///
+/// The `rng` must be cryptographically secure for the commitment to be
+/// hiding.
+#[cfg(feature = "rand_core")]
+pub fn commit<R: rand_core::RngCore + ?Sized>(
+    message: &[u8],
+    rng: &mut R,
+) -> (Commitment, Opening) {
+    let mut blinder = [0; 32];
+    rng.fill_bytes(&mut blinder);
+    let opening = Opening(blinder);
+    (commit_with(message, &opening), opening)
+}
+
+/// Commit to `message` with the given `opening`.
+pub fn commit_with(message: &[u8], opening: &Opening) -> Commitment {
+    Commitment(sha3_256_keyed(&opening.0, message))
+}
+
+/// Check that `commitment` opens to `message` with `opening`.
+///
+/// The commitments are compared in constant time.
+pub fn verify(commitment: &Commitment, message: &[u8], opening: &Opening) -> bool {
+    ct_eq(&commit_with(message, opening).0, &commitment.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Opening, commit_with, verify};
+
+    #[test]
+    fn known_answer() {
+        let opening = Opening::from_bytes(std::array::from_fn(|i| i as u8));
+        let commitment = commit_with(b"my vote", &opening);
+        assert_eq!(
+            "07ce452eb2b8ea408310e66134568ca82697662b59b860ab72c7d4f722bb931f",
+            hex::encode(commitment.as_bytes())
+        );
+        assert!(verify(&commitment, b"my vote", &opening));
+        assert!(!verify(&commitment, b"my vote!", &opening));
+        assert!(!verify(
+            &commitment,
+            b"my vote",
+            &Opening::from_bytes([0; 32])
+        ));
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn random_openings() {
+        use rand_core::SeedableRng;
+
+        use super::commit;
+        use crate::rng::Shake128Rng;
+
+        let mut rng = Shake128Rng::from_seed([7; 32]);
+        let (commitment, opening) = commit(b"message", &mut rng);
+        assert!(verify(&commitment, b"message", &opening));
+        let (other, _) = commit(b"message", &mut rng);
+        assert_ne!(commitment, other);
+    }
+}
@@ -0,0 +1,25 @@
+//! Constant-time helpers
+//!
+//! The helpers are best-effort: without a dependency like `subtle`, they
+//! rely on [`black_box`] to keep the compiler from reintroducing branches on
+//! secret data. `black_box` is only a hint to the optimizer, so the generated
+//! code is not guaranteed to run in constant time.
+
+use std::hint::black_box;
+
+/// Compare `a` and `b` without exiting early at the first differing byte.
+///
+/// Only the contents are compared in constant time, the lengths are assumed
+/// to be public.
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    // Hiding every intermediate value keeps the optimizer from exiting once
+    // all bits of `diff` are set
+    let diff = a
+        .iter()
+        .zip(b)
+        .fold(0, |diff, (a, b)| black_box(diff | (a ^ b)));
+    diff == 0
+}
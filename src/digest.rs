@@ -0,0 +1,528 @@
+//! Digest newtype with hex formatting and parsing
+//!
+//! The hash functions of this crate return their digest as a plain byte
+//! array. Wrapping it in a [`Digest`] adds lower case hex formatting with
+//! [`Display`](fmt::Display), [`LowerHex`](fmt::LowerHex) and
+//! [`UpperHex`](fmt::UpperHex), and hex parsing with [`FromStr`]. A `Digest`
+//! dereferences to the underlying array.
+//!
+//! Digests can also be encoded in and parsed from base64, base58 and
+//! [multibase](Multibase).
+//!
+//! With the `serde` feature, digests are serialized as hex strings in
+//! human-readable formats like JSON and as bytes in binary formats.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::{digest::Digest, sha3_256};
+//! #
+//! let digest = Digest::from(sha3_256(b"your input bytes"));
+//! let expected = "414d4b6d11a92aaeeebe35f9374942f563848d345631bf5537407252dca6b378";
+//! assert_eq!(expected, digest.to_string());
+//! assert_eq!(Ok(digest), expected.parse());
+//! assert_eq!(0x41, digest[0]);
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use crate::{ct::ct_eq, encoding};
+
+/// Digest of `N` bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Digest<const N: usize>([u8; N]);
+
+impl<const N: usize> Digest<N> {
+    /// Create a digest from its bytes.
+    pub fn from_bytes(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+
+    /// Bytes of the digest.
+    pub fn into_bytes(self) -> [u8; N] {
+        self.0
+    }
+
+    /// Compare the digest to `other` in constant time.
+    ///
+    /// Use this instead of `==` when the digest is secret, e.g., a MAC tag
+    /// or commitment, as `==` exits early at the first differing byte. The
+    /// comparison is only best-effort constant time, as it relies on
+    /// [`std::hint::black_box`], which the optimizer is free to ignore.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+
+    /// Check in constant time that the digest equals `expected`.
+    ///
+    /// Returns false if `expected` does not have `N` bytes. The length of
+    /// `expected` is not treated as secret. Like [`Digest::ct_eq`], the
+    /// comparison is best-effort constant time.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::{digest::Digest, prefix_mac::sha3_256_keyed};
+    /// #
+    /// let tag = sha3_256_keyed(b"key", b"message");
+    /// let recomputed = Digest::from(sha3_256_keyed(b"key", b"message"));
+    /// assert!(recomputed.verify(&tag));
+    /// assert!(!recomputed.verify(&tag[..16]));
+    /// ```
+    pub fn verify(&self, expected: &[u8]) -> bool {
+        ct_eq(&self.0, expected)
+    }
+}
+
+/// Encodings of digests as text.
+impl<const N: usize> Digest<N> {
+    /// Standard base64 encoding with padding, see [RFC 4648] section 4.
+    ///
+    /// [RFC 4648]: https://www.rfc-editor.org/rfc/rfc4648
+    pub fn to_base64(&self) -> String {
+        encoding::base64_encode(&self.0, encoding::BASE64, true)
+    }
+
+    /// URL and filename safe base64 encoding without padding, see
+    /// [RFC 4648] section 5.
+    ///
+    /// [RFC 4648]: https://www.rfc-editor.org/rfc/rfc4648
+    pub fn to_base64_url(&self) -> String {
+        encoding::base64_encode(&self.0, encoding::BASE64_URL, false)
+    }
+
+    /// Base58 encoding with the alphabet of Bitcoin (base58btc).
+    pub fn to_base58(&self) -> String {
+        encoding::base58_encode(&self.0)
+    }
+
+    /// [Multibase] encoding, i.e., the encoding `base` prefixed with its
+    /// multibase code.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::digest::{Digest, Multibase};
+    /// #
+    /// let digest = Digest::from([0xde, 0xad, 0xbe, 0xef]);
+    /// assert_eq!("fdeadbeef", digest.to_multibase(Multibase::Base16));
+    /// assert_eq!("z6h8cQN", digest.to_multibase(Multibase::Base58Btc));
+    /// assert_eq!(Ok(digest), Digest::from_multibase("m3q2+7w"));
+    /// ```
+    ///
+    /// [Multibase]: https://github.com/multiformats/multibase
+    pub fn to_multibase(&self, base: Multibase) -> String {
+        let encoded = match base {
+            Multibase::Base16 => self.to_string(),
+            Multibase::Base58Btc => self.to_base58(),
+            Multibase::Base64 => encoding::base64_encode(&self.0, encoding::BASE64, false),
+            Multibase::Base64Url => self.to_base64_url(),
+        };
+        format!("{}{encoded}", base.code())
+    }
+
+    /// Parse the standard base64 encoding with padding.
+    ///
+    /// # Errors
+    /// If `s` is not a canonical encoding of `N` bytes.
+    pub fn from_base64(s: &str) -> Result<Self, ParseDigestError> {
+        Self::from_decoded(encoding::base64_decode(s, encoding::BASE64, true, 0)?)
+    }
+
+    /// Parse the URL and filename safe base64 encoding without padding.
+    ///
+    /// # Errors
+    /// If `s` is not a canonical encoding of `N` bytes.
+    pub fn from_base64_url(s: &str) -> Result<Self, ParseDigestError> {
+        Self::from_decoded(encoding::base64_decode(s, encoding::BASE64_URL, false, 0)?)
+    }
+
+    /// Parse the base58btc encoding.
+    ///
+    /// # Errors
+    /// If `s` is not an encoding of `N` bytes.
+    pub fn from_base58(s: &str) -> Result<Self, ParseDigestError> {
+        Self::from_decoded(encoding::base58_decode(s, 0)?)
+    }
+
+    /// Parse a multibase encoding in one of the [`Multibase`] encodings.
+    ///
+    /// The upper case hex (`F`) and padded base64 (`M` and `U`) variants are
+    /// accepted as well.
+    ///
+    /// # Errors
+    /// If the multibase code is unknown or the remainder of `s` is not an
+    /// encoding of `N` bytes.
+    pub fn from_multibase(s: &str) -> Result<Self, ParseDigestError> {
+        let mut chars = s.chars();
+        let code = chars.next().ok_or(ParseDigestError::InvalidLength(0))?;
+        let encoded = chars.as_str();
+        let decoded = match code {
+            'f' | 'F' => {
+                return encoded.parse().map_err(|err| match err {
+                    ParseDigestError::InvalidCharacter { c, index } => {
+                        ParseDigestError::InvalidCharacter {
+                            c,
+                            index: index + 1,
+                        }
+                    }
+                    err => err,
+                });
+            }
+            'z' => encoding::base58_decode(encoded, 1)?,
+            'm' => encoding::base64_decode(encoded, encoding::BASE64, false, 1)?,
+            'M' => encoding::base64_decode(encoded, encoding::BASE64, true, 1)?,
+            'u' => encoding::base64_decode(encoded, encoding::BASE64_URL, false, 1)?,
+            'U' => encoding::base64_decode(encoded, encoding::BASE64_URL, true, 1)?,
+            c => return Err(ParseDigestError::InvalidCharacter { c, index: 0 }),
+        };
+        Self::from_decoded(decoded)
+    }
+
+    fn from_decoded(decoded: Vec<u8>) -> Result<Self, ParseDigestError> {
+        decoded
+            .try_into()
+            .map(Self)
+            .map_err(|decoded: Vec<u8>| ParseDigestError::InvalidLength(decoded.len()))
+    }
+}
+
+/// Text encodings supported by [`Digest::to_multibase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Multibase {
+    /// Lower case hex with code `f`
+    Base16,
+    /// Base58 with the alphabet of Bitcoin with code `z`
+    Base58Btc,
+    /// Standard base64 without padding with code `m`
+    Base64,
+    /// URL and filename safe base64 without padding with code `u`
+    Base64Url,
+}
+
+impl Multibase {
+    /// Multibase code, i.e., the prefix of the encoding.
+    pub fn code(self) -> char {
+        match self {
+            Self::Base16 => 'f',
+            Self::Base58Btc => 'z',
+            Self::Base64 => 'm',
+            Self::Base64Url => 'u',
+        }
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Digest<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl<const N: usize> From<Digest<N>> for [u8; N] {
+    fn from(digest: Digest<N>) -> Self {
+        digest.0
+    }
+}
+
+impl<const N: usize> Deref for Digest<N> {
+    type Target = [u8; N];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> DerefMut for Digest<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Digest<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> PartialEq<[u8; N]> for Digest<N> {
+    fn eq(&self, other: &[u8; N]) -> bool {
+        self.0 == *other
+    }
+}
+
+impl<const N: usize> fmt::Debug for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Digest({self:x})")
+    }
+}
+
+/// Formats the digest as lower case hex.
+impl<const N: usize> fmt::Display for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+impl<const N: usize> fmt::UpperHex for Digest<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02X}"))
+    }
+}
+
+/// Parses the hex encoding of the digest.
+///
+/// Upper and lower case hex digits are accepted. The string must contain
+/// exactly `2 * N` hex digits and nothing else.
+impl<const N: usize> FromStr for Digest<N> {
+    type Err = ParseDigestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.len().is_multiple_of(2) {
+            return Err(ParseDigestError::OddLength);
+        }
+        if s.len() != 2 * N {
+            return Err(ParseDigestError::InvalidLength(s.len() / 2));
+        }
+        let mut digest = [0; N];
+        let mut digits = s.char_indices();
+        for byte in &mut digest {
+            let high = hex_value(digits.next())?;
+            let low = hex_value(digits.next())?;
+            *byte = high << 4 | low;
+        }
+        Ok(Self(digest))
+    }
+}
+
+fn hex_value(digit: Option<(usize, char)>) -> Result<u8, ParseDigestError> {
+    // The string has 2 * N bytes and every character before this one was an
+    // ASCII hex digit, so there are enough characters left
+    let (index, c) = digit.expect("string has 2 * N bytes");
+    c.to_digit(16)
+        .map(|digit| digit as u8)
+        .ok_or(ParseDigestError::InvalidCharacter { c, index })
+}
+
+/// Error returned when parsing a [`Digest`] from hex or another encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDigestError {
+    /// A character which is not a digit of the encoding.
+    InvalidCharacter { c: char, index: usize },
+    /// An odd number of hex digits.
+    OddLength,
+    /// The number of decoded bytes differs from the digest length.
+    InvalidLength(usize),
+}
+
+impl fmt::Display for ParseDigestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseDigestError::InvalidCharacter { c, index } => {
+                write!(f, "invalid character {c:?} at position {index}")
+            }
+            ParseDigestError::OddLength => f.write_str("odd number of hex digits"),
+            ParseDigestError::InvalidLength(len) => {
+                write!(f, "invalid digest length of {len} bytes")
+            }
+        }
+    }
+}
+
+impl Error for ParseDigestError {}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Digest<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Digest<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<const N: usize>;
+
+        impl<const N: usize> serde::de::Visitor<'_> for Visitor<N> {
+            type Value = Digest<N>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a digest of {N} bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                s.parse().map_err(E::custom)
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                bytes
+                    .try_into()
+                    .map(Digest)
+                    .map_err(|_| E::invalid_length(bytes.len(), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(Visitor)
+        } else {
+            deserializer.deserialize_bytes(Visitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Digest, Multibase, ParseDigestError};
+    use crate::{sha3_224, sha3_512};
+
+    #[test]
+    fn formatting() {
+        let digest = Digest::from(sha3_224(b""));
+        let hex = "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7";
+        assert_eq!(hex, digest.to_string());
+        assert_eq!(hex, format!("{digest:x}"));
+        assert_eq!(hex.to_uppercase(), format!("{digest:X}"));
+        assert_eq!(format!("0x{hex}"), format!("{digest:#x}"));
+        assert_eq!(format!("Digest({hex})"), format!("{digest:?}"));
+    }
+
+    #[test]
+    fn parsing() {
+        let digest = Digest::from(sha3_512(b"message"));
+        assert_eq!(Ok(digest), digest.to_string().parse());
+        assert_eq!(Ok(digest), format!("{digest:X}").parse());
+        assert_eq!(Ok(Digest::from([0xab, 0x01])), "aB01".parse());
+
+        assert_eq!(Err(ParseDigestError::OddLength), "abc".parse::<Digest<2>>());
+        assert_eq!(
+            Err(ParseDigestError::InvalidLength(3)),
+            "abcdef".parse::<Digest<2>>()
+        );
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: 'g', index: 3 }),
+            "abcg".parse::<Digest<2>>()
+        );
+        // Multi-byte characters must not cause a panic
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: 'é', index: 0 }),
+            "é".parse::<Digest<1>>()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        use serde::{
+            Deserialize,
+            de::value::{BytesDeserializer, Error},
+        };
+
+        let digest = Digest::from(sha3_224(b""));
+        let json = serde_json::to_string(&digest).unwrap();
+        assert_eq!(
+            "\"6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7\"",
+            json
+        );
+        assert_eq!(digest, serde_json::from_str::<Digest<28>>(&json).unwrap());
+        let err = serde_json::from_str::<Digest<32>>(&json).unwrap_err();
+        assert!(err.to_string().contains("invalid digest length"), "{err}");
+
+        let bytes = BytesDeserializer::<Error>::new(&digest[..]);
+        assert_eq!(digest, Digest::<28>::deserialize(bytes).unwrap());
+        let bytes = BytesDeserializer::<Error>::new(&digest[..27]);
+        let err = Digest::<28>::deserialize(bytes).unwrap_err();
+        assert_eq!(
+            "invalid length 27, expected a digest of 28 bytes",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn encodings() {
+        let digest = Digest::from(sha3_224(b""));
+        assert_eq!(
+            "a04DQjZn27c7bhVFTw6xq9RZf5obB44/W1prxw==",
+            digest.to_base64()
+        );
+        assert_eq!(
+            "a04DQjZn27c7bhVFTw6xq9RZf5obB44_W1prxw",
+            digest.to_base64_url()
+        );
+        assert_eq!(Ok(digest), Digest::from_base64(&digest.to_base64()));
+        assert_eq!(Ok(digest), Digest::from_base64_url(&digest.to_base64_url()));
+        assert_eq!(Ok(digest), Digest::from_base58(&digest.to_base58()));
+        for base in [
+            Multibase::Base16,
+            Multibase::Base58Btc,
+            Multibase::Base64,
+            Multibase::Base64Url,
+        ] {
+            let encoded = digest.to_multibase(base);
+            assert!(encoded.starts_with(base.code()));
+            assert_eq!(Ok(digest), Digest::from_multibase(&encoded), "{base:?}");
+        }
+        assert_eq!(
+            Ok(digest),
+            Digest::from_multibase(&format!("M{}", digest.to_base64()))
+        );
+        assert_eq!(Ok(digest), Digest::from_multibase(&format!("F{digest:X}")));
+
+        let short = Digest::from([0, 0, 1]);
+        assert_eq!("112", short.to_base58());
+        assert_eq!(
+            Err(ParseDigestError::InvalidLength(2)),
+            Digest::<3>::from_base58("12")
+        );
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: 'x', index: 0 }),
+            Digest::<3>::from_multibase("x112")
+        );
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: 'g', index: 3 }),
+            Digest::<3>::from_multibase("f00g001")
+        );
+        assert_eq!(
+            Err(ParseDigestError::InvalidLength(0)),
+            Digest::<3>::from_multibase("")
+        );
+    }
+
+    #[test]
+    fn constant_time_comparison() {
+        let digest = Digest::from(sha3_512(b"message"));
+        assert!(digest.ct_eq(&digest));
+        assert!(digest.verify(&sha3_512(b"message")));
+        let mut other = digest;
+        other[63] ^= 1;
+        assert!(!digest.ct_eq(&other));
+        assert!(!digest.verify(&other[..]));
+        assert!(!digest.verify(&digest[..63]));
+        assert!(!digest.verify(&[]));
+    }
+
+    #[test]
+    fn derefs_to_array() {
+        let mut digest = Digest::from([1, 2, 3]);
+        assert_eq!(3, digest.len());
+        digest[0] = 4;
+        assert_eq!(digest, [4, 2, 3]);
+        assert_eq!([4, 2, 3], digest.into_bytes());
+    }
+}
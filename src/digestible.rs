@@ -0,0 +1,223 @@
+//! Unambiguous hashing of structured data
+//!
+//! Types implementing [`Sha3Digestible`] feed a canonical encoding of their
+//! value into a [`Hasher`]. Hashing the fields of a struct one after another
+//! with [`Hasher::update`] is ambiguous, as `("ab", "c")` and `("a", "bc")`
+//! result in the same input. The encoding of this module avoids this:
+//!
+//! - Integers are encoded in little-endian byte order with their fixed size.
+//!   `usize` and `isize` are encoded as 64-bit integers, so that the encoding
+//!   does not depend on the platform. `bool` is encoded as one byte, 0 or 1.
+//! - Strings, slices and vectors are prefixed with their number of elements as
+//!   a 64-bit little-endian integer, followed by the encoding of the elements.
+//! - Arrays and tuples are the concatenation of the encodings of their
+//!   elements, as their length is fixed by the type.
+//! - `None` is encoded as the byte 0, `Some(value)` as the byte 1 followed by
+//!   the encoding of `value`.
+//!
+//! The encoding is only unambiguous among values of the same type.
+//!
+//! With the `derive` feature, `#[derive(Sha3Digest)]` implements the
+//! trait for structs. The encoding of a struct is its length-prefixed type
+//! name, its number of fields as a 64-bit little-endian integer and the
+//! encodings of its fields, which separates the domains of different structs.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::digestible::hash_value;
+//! #
+//! let first = hash_value::<32, _>(&("ab", "c"));
+//! let second = hash_value::<32, _>(&("a", "bc"));
+//! assert_ne!(first, second);
+//! ```
+
+#[cfg(feature = "derive")]
+pub use sha3_derive::Sha3Digest;
+
+use crate::hasher::Hasher;
+
+/// Value with a canonical encoding for hashing.
+pub trait Sha3Digestible {
+    /// Absorb the encoding of `self` into `hasher`.
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>);
+
+    /// Absorb the encodings of all `items` into `hasher`, without a length
+    /// prefix.
+    ///
+    /// Overridden for `u8` to absorb the slice at once.
+    fn absorb_slice_into<const N: usize>(items: &[Self], hasher: &mut Hasher<N>)
+    where
+        Self: Sized,
+    {
+        for item in items {
+            item.absorb_into(hasher);
+        }
+    }
+}
+
+/// Hash the canonical encoding of `value` with the SHA-3 hash function with
+/// `N` bytes output.
+pub fn hash_value<const N: usize, T: Sha3Digestible + ?Sized>(value: &T) -> [u8; N] {
+    let mut hasher = Hasher::<N>::new();
+    value.absorb_into(&mut hasher);
+    hasher.finalize()
+}
+
+macro_rules! impl_int {
+    ($($int:ty),*) => {
+        $(
+            impl Sha3Digestible for $int {
+                fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+                    hasher.update(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Sha3Digestible for u8 {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        hasher.update(&[*self]);
+    }
+
+    fn absorb_slice_into<const N: usize>(items: &[Self], hasher: &mut Hasher<N>) {
+        hasher.update(items);
+    }
+}
+
+impl Sha3Digestible for usize {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        (*self as u64).absorb_into(hasher);
+    }
+}
+
+impl Sha3Digestible for isize {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        (*self as i64).absorb_into(hasher);
+    }
+}
+
+impl Sha3Digestible for bool {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        u8::from(*self).absorb_into(hasher);
+    }
+}
+
+impl Sha3Digestible for str {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        self.as_bytes().absorb_into(hasher);
+    }
+}
+
+impl Sha3Digestible for String {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        self.as_str().absorb_into(hasher);
+    }
+}
+
+impl<T: Sha3Digestible> Sha3Digestible for [T] {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        self.len().absorb_into(hasher);
+        T::absorb_slice_into(self, hasher);
+    }
+}
+
+impl<T: Sha3Digestible> Sha3Digestible for Vec<T> {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        self.as_slice().absorb_into(hasher);
+    }
+}
+
+impl<T: Sha3Digestible, const M: usize> Sha3Digestible for [T; M] {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        T::absorb_slice_into(self, hasher);
+    }
+}
+
+impl<T: Sha3Digestible> Sha3Digestible for Option<T> {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        match self {
+            None => false.absorb_into(hasher),
+            Some(value) => {
+                true.absorb_into(hasher);
+                value.absorb_into(hasher);
+            }
+        }
+    }
+}
+
+impl<T: Sha3Digestible + ?Sized> Sha3Digestible for &T {
+    fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+        (**self).absorb_into(hasher);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($(($($name:ident),+)),*) => {
+        $(
+            impl<$($name: Sha3Digestible),+> Sha3Digestible for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn absorb_into<const N: usize>(&self, hasher: &mut Hasher<N>) {
+                    let ($($name,)+) = self;
+                    $($name.absorb_into(hasher);)+
+                }
+            }
+        )*
+    };
+}
+
+impl_tuple!(
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F)
+);
+
+#[cfg(test)]
+mod tests {
+    use super::hash_value;
+    use crate::sha3_256;
+
+    #[test]
+    fn canonical_encoding() {
+        assert_eq!(sha3_256(&[0x34, 0x12]), hash_value::<32, _>(&0x1234_u16));
+        assert_eq!(sha3_256(&[0xff; 8]), hash_value::<32, _>(&-1_isize));
+        assert_eq!(
+            sha3_256(&[3, 0, 0, 0, 0, 0, 0, 0, b'a', b'b', b'c']),
+            hash_value::<32, _>("abc")
+        );
+        assert_eq!(
+            sha3_256(&[2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0]),
+            hash_value::<32, _>(&vec![1_u16, 2])
+        );
+        assert_eq!(sha3_256(&[1, 0, 2, 0]), hash_value::<32, _>(&[1_u16, 2]));
+        assert_eq!(
+            sha3_256(&[1, 7, 0]),
+            hash_value::<32, _>(&(Some(7_u8), None::<u32>))
+        );
+        assert_eq!(
+            sha3_256(&[1, 0, 0, 0, 0, 0, 0, 0, 42, 1]),
+            hash_value::<32, _>(&(&[42_u8][..], true))
+        );
+    }
+
+    #[test]
+    fn unambiguous() {
+        assert_ne!(
+            hash_value::<32, _>(&("ab", "c")),
+            hash_value::<32, _>(&("a", "bc"))
+        );
+        assert_ne!(
+            hash_value::<32, _>(&vec![vec![1_u8], vec![]]),
+            hash_value::<32, _>(&vec![vec![], vec![1_u8]])
+        );
+        assert_ne!(
+            hash_value::<32, _>(&Some(0_u8)),
+            hash_value::<32, _>(&None::<u8>)
+        );
+    }
+}
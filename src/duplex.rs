@@ -0,0 +1,206 @@
+//! Duplex construction
+//!
+//! The duplex construction of [Bertoni et al.] keeps the Keccak state alive
+//! between calls. Every call to [`Duplex::duplex`] absorbs an input block,
+//! applies KECCAK-p\[1600, 24\] and squeezes an output block from the new
+//! state. Inputs and outputs can thus be interleaved, which is the basis for
+//! authenticated encryption, reseedable PRNGs and session transcripts.
+//!
+//! Every input block is padded with `pad10*1`, so the output of a call
+//! depends on all previous inputs and their boundaries. The output of the
+//! first call equals the output of the sponge with the original Keccak padding
+//! for the same input.
+//!
+//! In the default *rate-limited* mode, inputs are absorbed into the outer
+//! `rate` bytes of the state. In the *full-state* mode of the full-state keyed
+//! duplex, inputs are absorbed into the whole state, including the capacity.
+//! Full-state absorption is only secure if the state is initialized with a
+//! secret key, as in keyed constructions. Outputs are always at most `rate`
+//! bytes long.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::duplex::Duplex;
+//! #
+//! let mut duplex = Duplex::new(512);
+//! let mut first = [0; 32];
+//! duplex.duplex(b"first input", &mut first);
+//! assert_eq!(sha3::keccak256(b"first input"), first);
+//!
+//! let mut second = [0; 32];
+//! duplex.duplex(b"second input", &mut second);
+//! ```
+//!
+//! [Bertoni et al.]: https://keccak.team/files/SpongeDuplex.pdf
+
+use crate::keccak::{State, keccakf_1600_state_permute};
+
+/// Duplex object on a live Keccak-f\[1600\] state.
+#[derive(Debug)]
+pub struct Duplex {
+    state: State,
+    /// Rate in bytes
+    rate: usize,
+    /// Number of bytes that are available for the input and its padding
+    input_block: usize,
+}
+
+impl Duplex {
+    /// Create a rate-limited duplex object with a capacity of `capacity`
+    /// bits.
+    ///
+    /// # Panics
+    /// If the capacity is not a multiple of 8 or leaves a rate of less than 2
+    /// bytes.
+    pub fn new(capacity: usize) -> Self {
+        let rate = Self::rate_bytes(capacity);
+        Self {
+            state: State::default(),
+            rate,
+            input_block: rate,
+        }
+    }
+
+    /// Create a full-state duplex object with a capacity of `capacity` bits,
+    /// which absorbs inputs into the whole state.
+    ///
+    /// # Panics
+    /// If the capacity is not a multiple of 8 or leaves a rate of less than 2
+    /// bytes.
+    pub fn new_full_state(capacity: usize) -> Self {
+        Self {
+            state: State::default(),
+            rate: Self::rate_bytes(capacity),
+            input_block: 200,
+        }
+    }
+
+    fn rate_bytes(capacity: usize) -> usize {
+        assert!(
+            capacity.is_multiple_of(8) && capacity <= 1600 - 16,
+            "capacity must be a multiple of 8 and at most 1584 bits"
+        );
+        (1600 - capacity) / 8
+    }
+
+    /// Rate in bytes, which is the maximum output length of
+    /// [`Duplex::duplex`].
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Maximum input length of [`Duplex::duplex`] in bytes.
+    ///
+    /// One byte of the input block is reserved for the padding.
+    pub fn max_input_len(&self) -> usize {
+        self.input_block - 1
+    }
+
+    /// Absorb `input`, permute the state and fill `output` with the first
+    /// bytes of the new state.
+    ///
+    /// # Panics
+    /// If `input` is longer than [`Duplex::max_input_len`] or `output` is
+    /// longer than [`Duplex::rate`].
+    pub fn duplex(&mut self, input: &[u8], output: &mut [u8]) {
+        assert!(
+            input.len() <= self.max_input_len(),
+            "input must be at most max_input_len() bytes"
+        );
+        assert!(
+            output.len() <= self.rate,
+            "output must be at most rate() bytes"
+        );
+        let input_block = self.input_block;
+        self.state.with_bytes(|bytes| {
+            bytes
+                .iter_mut()
+                .zip(input)
+                .for_each(|(state, input)| *state ^= input);
+            // pad10*1 within the input block
+            bytes[input.len()] ^= 0b1;
+            bytes[input_block - 1] ^= 0b10000000;
+        });
+        keccakf_1600_state_permute(&mut self.state);
+        self.state
+            .with_bytes(|bytes| output.copy_from_slice(&bytes[..output.len()]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Duplex;
+    use crate::{
+        keccak256,
+        sponge::{AbsorbState, KECCAK_DELIMETED_SUFFIX},
+    };
+
+    #[test]
+    fn first_output_is_keccak() {
+        for len in [0, 1, 100, 134] {
+            let input = vec![0xab; len];
+            let mut duplex = Duplex::new(512);
+            let mut output = [0; 32];
+            duplex.duplex(&input, &mut output);
+            assert_eq!(keccak256(&input), output, "len {len}");
+        }
+    }
+
+    #[test]
+    fn outputs_are_sponge_of_padded_inputs() {
+        // The i-th output equals the sponge output of the padded previous
+        // inputs followed by the i-th input
+        const RATE: usize = 136;
+        let inputs: [&[u8]; 4] = [b"", b"abc", &[7; RATE - 1], b"last"];
+        let mut duplex = Duplex::new(512);
+        let mut padded_inputs = vec![];
+        for input in inputs {
+            let mut output = [0; 32];
+            duplex.duplex(input, &mut output);
+
+            let mut sponge = AbsorbState::new(RATE * 8, 512, KECCAK_DELIMETED_SUFFIX);
+            sponge.absorb(&padded_inputs);
+            sponge.absorb(input);
+            assert_eq!(sponge.finalize::<32>(), output);
+
+            let mut block = [0; RATE];
+            block[..input.len()].copy_from_slice(input);
+            block[input.len()] ^= 0b1;
+            block[RATE - 1] ^= 0b10000000;
+            padded_inputs.extend_from_slice(&block);
+        }
+    }
+
+    #[test]
+    fn full_state_absorbs_into_capacity() {
+        let mut rate_limited = Duplex::new(256);
+        let mut full_state = Duplex::new_full_state(256);
+        assert_eq!(167, rate_limited.max_input_len());
+        assert_eq!(199, full_state.max_input_len());
+
+        let mut a = [0; 168];
+        let mut b = [0; 168];
+        full_state.duplex(&[1; 199], &mut a);
+        let mut other = Duplex::new_full_state(256);
+        let mut input = [1; 199];
+        // Differs only in the capacity part of the state
+        input[198] = 2;
+        other.duplex(&input, &mut b);
+        assert_ne!(a, b);
+
+        rate_limited.duplex(&[1; 167], &mut b);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    #[should_panic = "input must be at most max_input_len() bytes"]
+    fn input_too_long() {
+        Duplex::new(256).duplex(&[0; 168], &mut []);
+    }
+
+    #[test]
+    #[should_panic = "output must be at most rate() bytes"]
+    fn output_too_long() {
+        Duplex::new_full_state(256).duplex(&[], &mut [0; 169]);
+    }
+}
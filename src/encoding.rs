@@ -0,0 +1,226 @@
+//! Hex, base64 and base58 encodings for digests
+
+use crate::digest::ParseDigestError;
+
+/// Alphabet of standard base64, see RFC 4648 section 4
+pub(crate) const BASE64: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+/// Alphabet of URL and filename safe base64, see RFC 4648 section 5
+pub(crate) const BASE64_URL: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+/// Alphabet of base58btc, which omits 0, O, I and l
+const BASE58: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Encode `bytes` as lower case hex.
+pub(crate) fn hex_encode(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    bytes
+        .iter()
+        .flat_map(|byte| [byte >> 4, byte & 0xf])
+        .map(|nibble| DIGITS[nibble as usize] as char)
+        .collect()
+}
+
+/// Encode `bytes` as base64 with `alphabet`, with `=` padding if `pad` is
+/// set.
+pub(crate) fn base64_encode(bytes: &[u8], alphabet: &[u8; 64], pad: bool) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut block = [0; 3];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let block = u32::from_be_bytes([0, block[0], block[1], block[2]]);
+        // n bytes are encoded by n + 1 characters
+        for i in 0..=chunk.len() {
+            let sextet = (block >> (18 - 6 * i)) & 0x3f;
+            encoded.push(alphabet[sextet as usize] as char);
+        }
+        if pad {
+            for _ in chunk.len()..3 {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Decode base64 with `alphabet`, which must be padded with `=` if and only
+/// if `pad` is set.
+///
+/// Non-canonical encodings, whose unused trailing bits are not zero, are
+/// rejected. The index of an invalid character is offset by `offset`.
+pub(crate) fn base64_decode(
+    s: &str,
+    alphabet: &[u8; 64],
+    pad: bool,
+    offset: usize,
+) -> Result<Vec<u8>, ParseDigestError> {
+    let data = if pad {
+        if !s.len().is_multiple_of(4) {
+            return Err(ParseDigestError::InvalidLength(s.len() / 4 * 3));
+        }
+        s.strip_suffix("==")
+            .or_else(|| s.strip_suffix('='))
+            .unwrap_or(s)
+    } else {
+        s
+    };
+    if data.len() % 4 == 1 {
+        return Err(ParseDigestError::InvalidLength(data.len() / 4 * 3));
+    }
+    let mut decoded = Vec::with_capacity(data.len() / 4 * 3 + 2);
+    let mut bits = 0_u32;
+    let mut bit_len = 0;
+    for (index, c) in data.char_indices() {
+        let invalid = ParseDigestError::InvalidCharacter {
+            c,
+            index: index + offset,
+        };
+        let sextet = alphabet
+            .iter()
+            .position(|&digit| digit as char == c)
+            .ok_or(invalid.clone())?;
+        bits = bits << 6 | sextet as u32;
+        bit_len += 6;
+        if bit_len >= 8 {
+            bit_len -= 8;
+            decoded.push((bits >> bit_len) as u8);
+            bits &= (1 << bit_len) - 1;
+        }
+        if index + c.len_utf8() == data.len() && bits != 0 {
+            return Err(invalid);
+        }
+    }
+    Ok(decoded)
+}
+
+/// Encode `bytes` as base58btc.
+///
+/// Every leading zero byte is encoded as `1`.
+pub(crate) fn base58_encode(bytes: &[u8]) -> String {
+    let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+    // Base 58 digits in little-endian order
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in &bytes[zeros..] {
+        let mut carry = u32::from(byte);
+        for digit in &mut digits {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let leading = std::iter::repeat_n('1', zeros);
+    leading
+        .chain(
+            digits
+                .iter()
+                .rev()
+                .map(|&digit| BASE58[digit as usize] as char),
+        )
+        .collect()
+}
+
+/// Decode base58btc. The index of an invalid character is offset by
+/// `offset`.
+pub(crate) fn base58_decode(s: &str, offset: usize) -> Result<Vec<u8>, ParseDigestError> {
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    // Bytes in little-endian order
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len());
+    for (index, c) in s.char_indices().skip(zeros) {
+        let value = BASE58.iter().position(|&digit| digit as char == c).ok_or(
+            ParseDigestError::InvalidCharacter {
+                c,
+                index: index + offset,
+            },
+        )?;
+        let mut carry = value as u32;
+        for byte in &mut bytes {
+            carry += u32::from(*byte) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    bytes.extend(std::iter::repeat_n(0, zeros));
+    bytes.reverse();
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BASE64, BASE64_URL, base58_decode, base58_encode, base64_decode, base64_encode};
+    use crate::digest::ParseDigestError;
+
+    #[test]
+    fn base64_rfc4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encoded, base64_encode(plain.as_bytes(), BASE64, true));
+            assert_eq!(
+                Ok(plain.as_bytes().to_vec()),
+                base64_decode(encoded, BASE64, true, 0)
+            );
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(unpadded, base64_encode(plain.as_bytes(), BASE64, false));
+            assert_eq!(
+                Ok(plain.as_bytes().to_vec()),
+                base64_decode(unpadded, BASE64, false, 0)
+            );
+        }
+        assert_eq!("-_8", base64_encode(&[0xfb, 0xff], BASE64_URL, false));
+    }
+
+    #[test]
+    fn base64_rejects_invalid() {
+        assert!(base64_decode("Zg=", BASE64, true, 0).is_err());
+        assert!(base64_decode("Zg", BASE64, true, 0).is_err());
+        assert!(base64_decode("Zg==", BASE64, false, 0).is_err());
+        assert!(base64_decode("Z", BASE64, false, 0).is_err());
+        assert!(base64_decode("Z===", BASE64, true, 0).is_err());
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: '+', index: 3 }),
+            base64_decode("Zm+v", BASE64_URL, true, 1)
+        );
+        // Non-zero trailing bits
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: 'h', index: 1 }),
+            base64_decode("Zh==", BASE64, true, 0)
+        );
+    }
+
+    #[test]
+    fn base58_vectors() {
+        // Test vectors of the base58 IETF draft
+        let vectors: [(&[u8], &str); 4] = [
+            (b"Hello World!", "2NEpo7TZRRrLZSi2U"),
+            (
+                b"The quick brown fox jumps over the lazy dog.",
+                "USm3fpXnKG5EUBx2ndxBDMPVciP5hGey2Jh4NDv6gmeo1LkMeiKrLJUUBk6Z",
+            ),
+            (&[0, 0, 0x28, 0x7f, 0xb4, 0xcd], "11233QC4"),
+            (&[], ""),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(encoded, base58_encode(plain));
+            assert_eq!(Ok(plain.to_vec()), base58_decode(encoded, 0));
+        }
+        assert_eq!(
+            Err(ParseDigestError::InvalidCharacter { c: '0', index: 2 }),
+            base58_decode("10", 1)
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! `expand_message` functions for hashing to finite fields
+//!
+//! Implements `expand_message_xmd` with the SHA-3 hash functions and
+//! `expand_message_xof` with the SHAKE XOFs as specified in Section 5.3 of
+//! [RFC 9380]. They expand a message and a domain separation tag (DST) into a
+//! uniformly random byte string, which `hash_to_field` reduces to field
+//! elements.
+//!
+//! Domain separation tags longer than 255 bytes are shortened by hashing them
+//! as specified in Section 5.3.3.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::expand_message::expand_message_xof_shake128;
+//! #
+//! let dst = b"QUUX-V01-CS02-with-expander-SHAKE128";
+//! let mut uniform_bytes = [0; 32];
+//! expand_message_xof_shake128(b"", dst, &mut uniform_bytes).unwrap();
+//! assert_eq!(
+//!     "86518c9cd86581486e9485aa74ab35ba150d1c75c88e26b7043e44e2acd735a2",
+//!     hex::encode(uniform_bytes)
+//! );
+//! ```
+//!
+//! [RFC 9380]: https://www.rfc-editor.org/rfc/rfc9380.html
+
+use std::{error::Error, fmt};
+
+use crate::{
+    hasher::Hasher,
+    sponge::{AbsorbState, SHAKE_DELIMETED_SUFFIX},
+};
+
+/// Prefix for hashing domain separation tags longer than 255 bytes
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+/// Maximum output length of the `expand_message` functions
+const MAX_OUTPUT_LEN: usize = u16::MAX as usize;
+
+/// `expand_message_xmd` with the SHA-3 hash function with `N` bytes output,
+/// filling all of `output`.
+///
+/// # Errors
+/// If `dst` is empty, or `output` is longer than 255 digests or than 65535
+/// bytes.
+pub fn expand_message_xmd<const N: usize>(
+    message: &[u8],
+    dst: &[u8],
+    output: &mut [u8],
+) -> Result<(), ExpandMessageError> {
+    /// Zero block of the largest block size of the SHA-3 hash functions
+    const Z_PAD: [u8; 144] = [0; 144];
+
+    let digest_len = N;
+    let ell = output.len().div_ceil(digest_len);
+    if ell > 255 || output.len() > MAX_OUTPUT_LEN {
+        return Err(ExpandMessageError::OutputTooLong(output.len()));
+    }
+    if dst.is_empty() {
+        return Err(ExpandMessageError::EmptyDst);
+    }
+    let hashed_dst;
+    let dst = if dst.len() > 255 {
+        let mut hasher = Hasher::<N>::new();
+        hasher.update(OVERSIZE_DST_PREFIX);
+        hasher.update(dst);
+        hashed_dst = hasher.finalize();
+        hashed_dst.as_ref()
+    } else {
+        dst
+    };
+    let dst_len = [dst.len() as u8];
+
+    let mut hasher = Hasher::<N>::new();
+    hasher.update(&Z_PAD[..Hasher::<N>::BLOCK_SIZE]);
+    hasher.update(message);
+    hasher.update(&(output.len() as u16).to_be_bytes());
+    hasher.update(&[0]);
+    hasher.update(dst);
+    hasher.update(&dst_len);
+    let b_0 = hasher.finalize();
+
+    let mut b_i = b_0;
+    for (i, chunk) in output.chunks_mut(digest_len).enumerate() {
+        let mut hasher = Hasher::<N>::new();
+        if i > 0 {
+            for (b, b_0) in b_i.iter_mut().zip(b_0) {
+                *b ^= b_0;
+            }
+        }
+        hasher.update(&b_i);
+        hasher.update(&[i as u8 + 1]);
+        hasher.update(dst);
+        hasher.update(&dst_len);
+        b_i = hasher.finalize();
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
+    }
+    Ok(())
+}
+
+/// `expand_message_xof` with SHAKE128, filling all of `output`.
+///
+/// # Errors
+/// If `dst` is empty or `output` is longer than 65535 bytes.
+pub fn expand_message_xof_shake128(
+    message: &[u8],
+    dst: &[u8],
+    output: &mut [u8],
+) -> Result<(), ExpandMessageError> {
+    expand_message_xof::<128>(message, dst, output)
+}
+
+/// `expand_message_xof` with SHAKE256, filling all of `output`.
+///
+/// # Errors
+/// If `dst` is empty or `output` is longer than 65535 bytes.
+pub fn expand_message_xof_shake256(
+    message: &[u8],
+    dst: &[u8],
+    output: &mut [u8],
+) -> Result<(), ExpandMessageError> {
+    expand_message_xof::<256>(message, dst, output)
+}
+
+/// `expand_message_xof` with the SHAKE XOF of security level `K` bits.
+fn expand_message_xof<const K: usize>(
+    message: &[u8],
+    dst: &[u8],
+    output: &mut [u8],
+) -> Result<(), ExpandMessageError> {
+    if output.len() > MAX_OUTPUT_LEN {
+        return Err(ExpandMessageError::OutputTooLong(output.len()));
+    }
+    if dst.is_empty() {
+        return Err(ExpandMessageError::EmptyDst);
+    }
+    let shake = || AbsorbState::new(1600 - 2 * K, 2 * K, SHAKE_DELIMETED_SUFFIX);
+    // The hashed DST has ceil(2 * k / 8) bytes for the security level k
+    let mut hashed_dst = [0; 64];
+    let dst = if dst.len() > 255 {
+        let hashed_dst = &mut hashed_dst[..2 * K / 8];
+        let mut sponge = shake();
+        sponge.absorb(OVERSIZE_DST_PREFIX);
+        sponge.absorb(dst);
+        sponge.into_squeeze().squeeze(hashed_dst);
+        &*hashed_dst
+    } else {
+        dst
+    };
+    let mut sponge = shake();
+    sponge.absorb(message);
+    sponge.absorb(&(output.len() as u16).to_be_bytes());
+    sponge.absorb(dst);
+    sponge.absorb(&[dst.len() as u8]);
+    sponge.into_squeeze().squeeze(output);
+    Ok(())
+}
+
+/// Error returned by the `expand_message` functions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpandMessageError {
+    /// The requested output length exceeds the limit of the function.
+    OutputTooLong(usize),
+    /// The domain separation tag is empty, which Section 3.1 of RFC 9380
+    /// forbids.
+    EmptyDst,
+}
+
+impl fmt::Display for ExpandMessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandMessageError::OutputTooLong(len) => {
+                write!(f, "output length {len} exceeds the limit of expand_message")
+            }
+            ExpandMessageError::EmptyDst => f.write_str("domain separation tag is empty"),
+        }
+    }
+}
+
+impl Error for ExpandMessageError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ExpandMessageError, expand_message_xmd, expand_message_xof_shake128,
+        expand_message_xof_shake256,
+    };
+
+    const DST_SHAKE128: &[u8] = b"QUUX-V01-CS02-with-expander-SHAKE128";
+    const DST_SHAKE256: &[u8] = b"QUUX-V01-CS02-with-expander-SHAKE256";
+
+    // Test vectors from Appendix K.4 and K.6 of RFC 9380
+
+    #[test]
+    fn xof_shake128() {
+        let mut output = [0; 32];
+        expand_message_xof_shake128(b"", DST_SHAKE128, &mut output).unwrap();
+        assert_eq!(
+            "86518c9cd86581486e9485aa74ab35ba150d1c75c88e26b7043e44e2acd735a2",
+            hex::encode(output)
+        );
+        expand_message_xof_shake128(b"abc", DST_SHAKE128, &mut output).unwrap();
+        assert_eq!(
+            "8696af52a4d862417c0763556073f47bc9b9ba43c99b505305cb1ec04a9ab468",
+            hex::encode(output)
+        );
+        let mut output = [0; 128];
+        expand_message_xof_shake128(b"", DST_SHAKE128, &mut output).unwrap();
+        assert_eq!(
+            "7314ff1a155a2fb99a0171dc71b89ab6e3b2b7d59e38e64419b8b6294d03ffee\
+             42491f11370261f436220ef787f8f76f5b26bdcd850071920ce023f3ac468477\
+             44f4612b8714db8f5db83205b2e625d95afd7d7b4d3094d3bdde815f52850bb4\
+             1ead9822e08f22cf41d615a303b0d9dde73263c049a7b9898208003a739a2e57",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn xof_shake256() {
+        let mut output = [0; 32];
+        expand_message_xof_shake256(b"", DST_SHAKE256, &mut output).unwrap();
+        assert_eq!(
+            "2ffc05c48ed32b95d72e807f6eab9f7530dd1c2f013914c8fed38c5ccc15ad76",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn xof_oversize_dst() {
+        // Test vectors from Appendix K.5 of RFC 9380
+        let dst = [
+            &b"QUUX-V01-CS02-with-expander-SHAKE128-long-DST-"[..],
+            &[b'1'; 210],
+        ]
+        .concat();
+        let mut output = [0; 32];
+        expand_message_xof_shake128(b"", &dst, &mut output).unwrap();
+        assert_eq!(
+            "827c6216330a122352312bccc0c8d6e7a146c5257a776dbd9ad9d75cd880fc53",
+            hex::encode(output)
+        );
+        expand_message_xof_shake128(b"abc", &dst, &mut output).unwrap();
+        assert_eq!(
+            "690c8d82c7213b4282c6cb41c00e31ea1d3e2005f93ad19bbf6da40f15790c5c",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn xmd() {
+        let dst = b"QUUX-V01-CS02-with-expander-SHA3-256";
+        let mut output = [0; 32];
+        expand_message_xmd::<32>(b"", dst, &mut output).unwrap();
+        assert_eq!(
+            "0633e7abc9098228c749e7cc1c08f7c28067a005df8b21ce2f877e157543593c",
+            hex::encode(output)
+        );
+        let mut output = [0; 128];
+        expand_message_xmd::<32>(b"abc", dst, &mut output).unwrap();
+        assert_eq!(
+            "d3235ad97df2cf0402dc75c1373351f230a23fad135bb552fc22e572e1ebbc9e\
+             26f4692d8cb2bf1b8ba16a22371ea490ac8e83a7d580f80b3c65598b910c47e4\
+             a6bc4a904cbe21dd3ed60883e7635f4f3045fce99df48fd0195c8457405fe496\
+             97de589bca6cdd9af91063643d9c64caab9e63ec0658267cda21be35ace6f20f",
+            hex::encode(output)
+        );
+        expand_message_xmd::<64>(b"abc", dst, &mut output).unwrap();
+        assert_eq!(
+            "f5c0b37f085c56cb62ae734774ddf86dc46995ccca872c0d745720eecba9e65b\
+             bcd884bdae3ad43acdaf307468ccaa92a449d40e24b8020b6df2fd0f744e9855\
+             7f25724d84b0e7c67fa565bd6a90f54c9a3bd48b5002e7fa0909edcbc2df11d1\
+             fc6ba89db27cb169ab15d9ab549947cde47f36b4657581b057a3a97dc6fa6271",
+            hex::encode(output)
+        );
+        let mut output = [0; 64];
+        expand_message_xmd::<48>(b"abc", &[b'x'; 300], &mut output).unwrap();
+        assert_eq!(
+            "1f40e6b9c2b3ab0fa0bc3beb504dd49d92d07bdfed786326d1cb10e8bbfbff0a\
+             b045cec557e5024a0e51d182f26de6771e133e5635bc52b920e755475731a2a8",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn output_length_limits() {
+        // 255 * 32 bytes is the maximum for SHA3-256
+        let mut output = vec![0; 255 * 32 + 1];
+        assert_eq!(
+            Err(ExpandMessageError::OutputTooLong(255 * 32 + 1)),
+            expand_message_xmd::<32>(b"", b"dst", &mut output)
+        );
+        assert_eq!(
+            Ok(()),
+            expand_message_xmd::<32>(b"", b"dst", &mut output[..255 * 32])
+        );
+        let mut output = vec![0; 65536];
+        assert_eq!(
+            Err(ExpandMessageError::OutputTooLong(65536)),
+            expand_message_xof_shake256(b"", b"dst", &mut output)
+        );
+        assert_eq!(
+            Ok(()),
+            expand_message_xof_shake128(b"", b"dst", &mut output[..65535])
+        );
+    }
+
+    #[test]
+    fn rejects_empty_dst() {
+        let mut output = [0; 32];
+        assert_eq!(
+            Err(ExpandMessageError::EmptyDst),
+            expand_message_xmd::<32>(b"", b"", &mut output)
+        );
+        assert_eq!(
+            Err(ExpandMessageError::EmptyDst),
+            expand_message_xof_shake128(b"", b"", &mut output)
+        );
+        assert_eq!(
+            Err(ExpandMessageError::EmptyDst),
+            expand_message_xof_shake256(b"", b"", &mut output)
+        );
+    }
+}
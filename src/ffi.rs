@@ -0,0 +1,349 @@
+//! C ABI for the SHA-3 hash functions and SHAKE
+//!
+//! The functions of this module are exported with unmangled names and
+//! declared in the header `include/sha3.h`. To use them from C, build the
+//! crate as a static or dynamic library with the `ffi` feature, e.g.,
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! and link against the resulting `libsha3.a`, or pass `--crate-type cdylib`
+//! for a shared library.
+//!
+//! The header is written by hand and not generated from this module, so it
+//! must be updated whenever an exported function is added or its signature
+//! changes.
+//!
+//! Pointers to input and output buffers may be null if the corresponding
+//! length is 0. Incremental hashers and XOFs are allocated on the heap and
+//! must be released with the matching `_free` function, unless they are
+//! consumed by [`sha3_hasher_finalize`].
+
+use std::{ffi::c_int, mem, slice};
+
+use crate::{
+    Shake128, Shake256, XofReader,
+    algorithm::{DynHasher, Sha3Algorithm},
+};
+
+/// Return value of the fallible functions on success
+const SUCCESS: c_int = 0;
+/// Return value of the fallible functions on invalid arguments
+const INVALID_ARGUMENT: c_int = -1;
+
+/// View `len` bytes at `ptr` as a slice, which may be null if `len` is 0.
+///
+/// # Safety
+/// If `len` is positive, `ptr` must be valid for reads of `len` bytes.
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        return &[];
+    }
+    // SAFETY: Guaranteed by the caller.
+    unsafe { slice::from_raw_parts(ptr, len) }
+}
+
+/// View `len` bytes at `ptr` as a mutable slice, which may be null if `len`
+/// is 0.
+///
+/// # Safety
+/// If `len` is positive, `ptr` must be valid for writes of `len` bytes.
+unsafe fn output<'a>(ptr: *mut u8, len: usize) -> &'a mut [u8] {
+    if len == 0 {
+        return &mut [];
+    }
+    // SAFETY: Guaranteed by the caller.
+    unsafe { slice::from_raw_parts_mut(ptr, len) }
+}
+
+macro_rules! ffi_sha3 {
+    ($($name:ident, $hash:path, $len:literal);*) => {
+        $(
+            #[doc = concat!("Write the ", stringify!($len), " byte digest of `message_len` bytes at `message` to `out`.")]
+            ///
+            /// # Safety
+            /// `message` must be valid for reads of `message_len` bytes and
+            #[doc = concat!("`out` valid for writes of ", stringify!($len), " bytes.")]
+            #[unsafe(no_mangle)]
+            pub unsafe extern "C" fn $name(message: *const u8, message_len: usize, out: *mut u8) {
+                // SAFETY: Guaranteed by the caller.
+                let message = unsafe { input(message, message_len) };
+                let digest = $hash(message);
+                // SAFETY: Guaranteed by the caller.
+                unsafe { output(out, $len) }.copy_from_slice(&digest);
+            }
+        )*
+    };
+}
+
+ffi_sha3!(
+    sha3_224, crate::sha3_224, 28;
+    sha3_256, crate::sha3_256, 32;
+    sha3_384, crate::sha3_384, 48;
+    sha3_512, crate::sha3_512, 64
+);
+
+/// Fill `out_len` bytes at `out` with the SHAKE128 output of `message_len`
+/// bytes at `message`.
+///
+/// # Safety
+/// `message` must be valid for reads of `message_len` bytes and `out` valid
+/// for writes of `out_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_shake128(
+    message: *const u8,
+    message_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) {
+    // SAFETY: Guaranteed by the caller.
+    let (message, out) = unsafe { (input(message, message_len), output(out, out_len)) };
+    crate::shake128(message, out);
+}
+
+/// Fill `out_len` bytes at `out` with the SHAKE256 output of `message_len`
+/// bytes at `message`.
+///
+/// # Safety
+/// `message` must be valid for reads of `message_len` bytes and `out` valid
+/// for writes of `out_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_shake256(
+    message: *const u8,
+    message_len: usize,
+    out: *mut u8,
+    out_len: usize,
+) {
+    // SAFETY: Guaranteed by the caller.
+    let (message, out) = unsafe { (input(message, message_len), output(out, out_len)) };
+    crate::shake256(message, out);
+}
+
+/// Incremental SHA-3 hasher, opaque to C.
+pub struct Sha3Hasher(DynHasher);
+
+/// Create a hasher with an output size of `output_bits`, which must be 224,
+/// 256, 384 or 512. Returns null for other sizes.
+#[unsafe(no_mangle)]
+pub extern "C" fn sha3_hasher_new(output_bits: usize) -> *mut Sha3Hasher {
+    let algorithm = match output_bits {
+        224 => Sha3Algorithm::Sha3_224,
+        256 => Sha3Algorithm::Sha3_256,
+        384 => Sha3Algorithm::Sha3_384,
+        512 => Sha3Algorithm::Sha3_512,
+        _ => return std::ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(Sha3Hasher(algorithm.hasher())))
+}
+
+/// Absorb `data_len` bytes at `data` into `hasher`.
+///
+/// # Safety
+/// `hasher` must be returned by [`sha3_hasher_new`] and not be finalized or
+/// freed. `data` must be valid for reads of `data_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_hasher_update(
+    hasher: *mut Sha3Hasher,
+    data: *const u8,
+    data_len: usize,
+) {
+    // SAFETY: Guaranteed by the caller.
+    let (hasher, data) = unsafe { (&mut *hasher, input(data, data_len)) };
+    hasher.0.update(data);
+}
+
+/// Write the digest of `hasher` to `out` and free `hasher`.
+///
+/// Returns 0 on success and -1 if `out_len` is not the output size of
+/// `hasher` in bytes. `hasher` is freed in both cases.
+///
+/// # Safety
+/// `hasher` must be returned by [`sha3_hasher_new`] and not be finalized or
+/// freed. `out` must be valid for writes of `out_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_hasher_finalize(
+    hasher: *mut Sha3Hasher,
+    out: *mut u8,
+    out_len: usize,
+) -> c_int {
+    // SAFETY: Guaranteed by the caller.
+    let hasher = unsafe { Box::from_raw(hasher) };
+    if hasher.0.algorithm().output_size() != out_len {
+        return INVALID_ARGUMENT;
+    }
+    // SAFETY: Guaranteed by the caller.
+    unsafe { output(out, out_len) }.copy_from_slice(&hasher.0.finalize());
+    SUCCESS
+}
+
+/// Free `hasher` without computing its digest. Does nothing if `hasher` is
+/// null.
+///
+/// # Safety
+/// `hasher` must be null or returned by [`sha3_hasher_new`] and not be
+/// finalized or freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_hasher_free(hasher: *mut Sha3Hasher) {
+    if !hasher.is_null() {
+        // SAFETY: Guaranteed by the caller.
+        drop(unsafe { Box::from_raw(hasher) });
+    }
+}
+
+/// SHAKE instance, opaque to C, which absorbs until the first squeeze.
+pub struct Sha3Shake(ShakeState);
+
+enum ShakeState {
+    Absorbing128(Shake128),
+    Absorbing256(Shake256),
+    Squeezing(Box<XofReader>),
+}
+
+impl Sha3Shake {
+    /// Finish absorbing if not done yet and return the output stream.
+    fn reader(&mut self) -> &mut XofReader {
+        // The placeholder is replaced before it can be observed
+        let state = mem::replace(&mut self.0, ShakeState::Absorbing128(Shake128::new()));
+        self.0 = match state {
+            ShakeState::Absorbing128(shake) => {
+                ShakeState::Squeezing(Box::new(shake.finalize_xof()))
+            }
+            ShakeState::Absorbing256(shake) => {
+                ShakeState::Squeezing(Box::new(shake.finalize_xof()))
+            }
+            squeezing @ ShakeState::Squeezing(_) => squeezing,
+        };
+        match &mut self.0 {
+            ShakeState::Squeezing(reader) => reader,
+            _ => unreachable!("absorbing was finished above"),
+        }
+    }
+}
+
+/// Create a SHAKE instance with a `security` level of 128 or 256 bits.
+/// Returns null for other levels.
+#[unsafe(no_mangle)]
+pub extern "C" fn sha3_shake_new(security: usize) -> *mut Sha3Shake {
+    let shake = match security {
+        128 => ShakeState::Absorbing128(Shake128::new()),
+        256 => ShakeState::Absorbing256(Shake256::new()),
+        _ => return std::ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(Sha3Shake(shake)))
+}
+
+/// Absorb `data_len` bytes at `data` into `shake`.
+///
+/// Returns 0 on success and -1 if `shake` has already been squeezed.
+///
+/// # Safety
+/// `shake` must be returned by [`sha3_shake_new`] and not be freed. `data`
+/// must be valid for reads of `data_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_shake_update(
+    shake: *mut Sha3Shake,
+    data: *const u8,
+    data_len: usize,
+) -> c_int {
+    // SAFETY: Guaranteed by the caller.
+    let (shake, data) = unsafe { (&mut *shake, input(data, data_len)) };
+    match &mut shake.0 {
+        ShakeState::Absorbing128(shake) => shake.update(data),
+        ShakeState::Absorbing256(shake) => shake.update(data),
+        ShakeState::Squeezing(_) => return INVALID_ARGUMENT,
+    }
+    SUCCESS
+}
+
+/// Fill `out_len` bytes at `out` with the next output of `shake`.
+///
+/// The first call finishes absorbing, afterwards `shake` can only be
+/// squeezed. Consecutive calls continue the output stream.
+///
+/// # Safety
+/// `shake` must be returned by [`sha3_shake_new`] and not be freed. `out`
+/// must be valid for writes of `out_len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_shake_squeeze(shake: *mut Sha3Shake, out: *mut u8, out_len: usize) {
+    // SAFETY: Guaranteed by the caller.
+    let (shake, out) = unsafe { (&mut *shake, output(out, out_len)) };
+    shake.reader().squeeze(out);
+}
+
+/// Free `shake`. Does nothing if `shake` is null.
+///
+/// # Safety
+/// `shake` must be null or returned by [`sha3_shake_new`] and not be freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sha3_shake_free(shake: *mut Sha3Shake) {
+    if !shake.is_null() {
+        // SAFETY: Guaranteed by the caller.
+        drop(unsafe { Box::from_raw(shake) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::{
+        sha3_256, sha3_hasher_finalize, sha3_hasher_free, sha3_hasher_new, sha3_hasher_update,
+        sha3_shake_free, sha3_shake_new, sha3_shake_squeeze, sha3_shake_update, sha3_shake256,
+    };
+
+    #[test]
+    fn one_shot() {
+        let message = b"your input bytes";
+        let mut digest = [0; 32];
+        unsafe { sha3_256(message.as_ptr(), message.len(), digest.as_mut_ptr()) };
+        assert_eq!(crate::sha3_256(message), digest);
+
+        unsafe { sha3_256(ptr::null(), 0, digest.as_mut_ptr()) };
+        assert_eq!(crate::sha3_256(b""), digest);
+
+        let mut output = [0; 100];
+        unsafe { sha3_shake256(message.as_ptr(), message.len(), output.as_mut_ptr(), 100) };
+        let mut expected = [0; 100];
+        crate::shake256(message, &mut expected);
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn incremental_hasher() {
+        assert!(sha3_hasher_new(128).is_null());
+
+        let hasher = sha3_hasher_new(384);
+        let mut digest = [0; 48];
+        unsafe {
+            sha3_hasher_update(hasher, b"your input ".as_ptr(), 11);
+            sha3_hasher_update(hasher, b"bytes".as_ptr(), 5);
+            assert_eq!(0, sha3_hasher_finalize(hasher, digest.as_mut_ptr(), 48));
+        }
+        assert_eq!(crate::sha3_384(b"your input bytes"), digest);
+
+        let hasher = sha3_hasher_new(256);
+        assert_eq!(-1, unsafe {
+            sha3_hasher_finalize(hasher, digest.as_mut_ptr(), 48)
+        });
+        unsafe { sha3_hasher_free(sha3_hasher_new(224)) };
+    }
+
+    #[test]
+    fn shake_squeeze() {
+        assert!(sha3_shake_new(512).is_null());
+
+        let shake = sha3_shake_new(256);
+        let mut output = [0; 300];
+        unsafe {
+            assert_eq!(0, sha3_shake_update(shake, b"abc".as_ptr(), 3));
+            sha3_shake_squeeze(shake, output.as_mut_ptr(), 100);
+            sha3_shake_squeeze(shake, output[100..].as_mut_ptr(), 200);
+            assert_eq!(-1, sha3_shake_update(shake, b"d".as_ptr(), 1));
+            sha3_shake_free(shake);
+        }
+        let mut expected = [0; 300];
+        crate::shake256(b"abc", &mut expected);
+        assert_eq!(expected, output);
+    }
+}
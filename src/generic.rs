@@ -0,0 +1,68 @@
+//! One-shot hash functions accepting any byte container
+//!
+//! The functions of this module accept `impl AsRef<[u8]>`, so that `&str`,
+//! `String`, `Vec<u8>` and arrays can be hashed without converting them to a
+//! slice first. They forward to the functions of the same name at the crate
+//! root, which take `&[u8]` and are only instantiated once, for code that is
+//! sensitive to code size.
+//!
+//! # Example Usage
+//! ```
+//! use sha3::generic::sha3_256;
+//!
+//! let message = String::from("your input bytes");
+//! assert_eq!(sha3::sha3_256(b"your input bytes"), sha3_256(&message));
+//! assert_eq!(sha3_256(&message), sha3_256(message.into_bytes()));
+//! ```
+
+/// SHA-3 Hash with 224 bits (28 bytes) output, see [`crate::sha3_224`].
+#[inline]
+pub fn sha3_224(message: impl AsRef<[u8]>) -> [u8; 28] {
+    crate::sha3_224(message.as_ref())
+}
+
+/// SHA-3 Hash with 256 bits (32 bytes) output, see [`crate::sha3_256`].
+#[inline]
+pub fn sha3_256(message: impl AsRef<[u8]>) -> [u8; 32] {
+    crate::sha3_256(message.as_ref())
+}
+
+/// SHA-3 Hash with 384 bits (48 bytes) output, see [`crate::sha3_384`].
+#[inline]
+pub fn sha3_384(message: impl AsRef<[u8]>) -> [u8; 48] {
+    crate::sha3_384(message.as_ref())
+}
+
+/// SHA-3 Hash with 512 bits (64 bytes) output, see [`crate::sha3_512`].
+#[inline]
+pub fn sha3_512(message: impl AsRef<[u8]>) -> [u8; 64] {
+    crate::sha3_512(message.as_ref())
+}
+
+/// Legacy Keccak-256 hash with 256 bits (32 bytes) output, see
+/// [`crate::keccak256`].
+#[inline]
+pub fn keccak256(message: impl AsRef<[u8]>) -> [u8; 32] {
+    crate::keccak256(message.as_ref())
+}
+
+/// Legacy Keccak-512 hash with 512 bits (64 bytes) output, see
+/// [`crate::keccak512`].
+#[inline]
+pub fn keccak512(message: impl AsRef<[u8]>) -> [u8; 64] {
+    crate::keccak512(message.as_ref())
+}
+
+/// SHAKE128 extendable-output function, filling all of `output`, see
+/// [`crate::shake128`].
+#[inline]
+pub fn shake128(message: impl AsRef<[u8]>, output: &mut [u8]) {
+    crate::shake128(message.as_ref(), output);
+}
+
+/// SHAKE256 extendable-output function, filling all of `output`, see
+/// [`crate::shake256`].
+#[inline]
+pub fn shake256(message: impl AsRef<[u8]>, output: &mut [u8]) {
+    crate::shake256(message.as_ref(), output);
+}
@@ -0,0 +1,804 @@
+//! Incremental SHA-3 hashing
+//!
+//! A [`Hasher`] absorbs its input in multiple calls to [`Hasher::update`] and
+//! computes the same digest as the one-shot functions in the crate root. The
+//! output size in bytes is a const generic parameter, for which the type
+//! aliases [`Sha3_224`], [`Sha3_256`], [`Sha3_384`] and [`Sha3_512`] exist.
+//! Generic code can be written over the output size:
+//!
+//! ```
+//! use sha3::Hasher;
+//!
+//! fn hash_twice<const N: usize>(message: &[u8]) -> [u8; N] {
+//!     let mut hasher = Hasher::<N>::new();
+//!     hasher.update(message);
+//!     hasher.update(message);
+//!     hasher.finalize()
+//! }
+//!
+//! let digest: [u8; 32] = hash_twice(b"abc");
+//! assert_eq!(sha3::sha3_256(b"abcabc"), digest);
+//! ```
+
+use std::{error::Error, fmt, io::IoSlice, mem};
+
+use crate::{
+    encoding,
+    sponge::{AbsorbState, SHA3_DELIMETED_SUFFIX},
+};
+
+/// Incremental SHA3-224.
+pub type Sha3_224 = Hasher<28>;
+/// Incremental SHA3-256.
+pub type Sha3_256 = Hasher<32>;
+/// Incremental SHA3-384.
+pub type Sha3_384 = Hasher<48>;
+/// Incremental SHA3-512.
+pub type Sha3_512 = Hasher<64>;
+
+/// Incremental SHA-3 hasher with an output size of `N` bytes.
+///
+/// `N` must be 28, 32, 48 or 64, which is checked at compile time. The
+/// output size is given in bytes instead of bits, as the type of the digest
+/// cannot be computed from the number of bits on stable Rust.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::Sha3_256;
+/// #
+/// let mut hasher = Sha3_256::new();
+/// hasher.update(b"your input ");
+/// hasher.update(b"bytes");
+/// assert_eq!(sha3::sha3_256(b"your input bytes"), hasher.finalize());
+/// ```
+///
+/// Unsupported output sizes fail to compile.
+/// ```compile_fail
+/// let hasher = sha3::Hasher::<16>::new();
+/// ```
+#[derive(Clone)]
+pub struct Hasher<const N: usize> {
+    sponge: AbsorbState,
+    /// Whether an input with an incomplete last byte has been absorbed
+    trailing_bits: bool,
+}
+
+/// Generate methods absorbing integers in little-endian and big-endian byte
+/// order.
+macro_rules! update_int {
+    ($($int:ty, $le:ident, $be:ident),*) => {
+        $(
+            #[doc = concat!("Absorb `value` as ", stringify!($int), " in little-endian byte order.")]
+            pub fn $le(&mut self, value: $int) {
+                self.update(&value.to_le_bytes());
+            }
+
+            #[doc = concat!("Absorb `value` as ", stringify!($int), " in big-endian byte order.")]
+            pub fn $be(&mut self, value: $int) {
+                self.update(&value.to_be_bytes());
+            }
+        )*
+    };
+}
+
+impl<const N: usize> Hasher<N> {
+    /// Output size in bits.
+    pub const BITS: usize = {
+        assert!(
+            matches!(N, 28 | 32 | 48 | 64),
+            "output size must be 28, 32, 48 or 64 bytes"
+        );
+        N * 8
+    };
+    /// Output size in bytes.
+    pub const OUTPUT_SIZE: usize = N;
+    /// Capacity of the sponge in bytes, twice the output size.
+    pub const CAPACITY: usize = 2 * N;
+    /// Rate of the sponge in bytes.
+    pub const RATE: usize = 200 - Self::CAPACITY;
+    /// Block size in bytes, i.e., the rate of the sponge.
+    pub const BLOCK_SIZE: usize = Self::RATE;
+
+    /// Create a new hasher.
+    pub fn new() -> Self {
+        let capacity = Self::BITS * 2;
+        Self {
+            sponge: AbsorbState::new(1600 - capacity, capacity, SHA3_DELIMETED_SUFFIX),
+            trailing_bits: false,
+        }
+    }
+
+    /// Absorb `data` into the state.
+    ///
+    /// # Panics
+    /// If called after [`Hasher::update_bits`] with an incomplete last byte.
+    pub fn update(&mut self, data: &[u8]) {
+        assert!(
+            !self.trailing_bits,
+            "cannot update after an incomplete byte"
+        );
+        self.sponge.absorb(data);
+    }
+
+    /// Absorb all buffers of `bufs` into the state, as if they were
+    /// concatenated.
+    ///
+    /// The buffers are absorbed directly without copying them into a
+    /// contiguous buffer.
+    ///
+    /// # Panics
+    /// If called after [`Hasher::update_bits`] with an incomplete last byte.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use std::io::IoSlice;
+    /// # use sha3::Sha3_256;
+    /// #
+    /// let mut hasher = Sha3_256::new();
+    /// hasher.update_vectored(&[IoSlice::new(b"header"), IoSlice::new(b"body")]);
+    /// assert_eq!(sha3::sha3_256(b"headerbody"), hasher.finalize());
+    /// ```
+    pub fn update_vectored(&mut self, bufs: &[IoSlice<'_>]) {
+        for buf in bufs {
+            self.update(buf);
+        }
+    }
+
+    /// Absorb all chunks produced by `chunks` into the state, as if they were
+    /// concatenated.
+    ///
+    /// Lazily produced data can be absorbed without collecting it first.
+    ///
+    /// # Panics
+    /// If called after [`Hasher::update_bits`] with an incomplete last byte.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_256;
+    /// #
+    /// let mut hasher = Sha3_256::new();
+    /// hasher.update_iter((0..3).map(|i| format!("line {i}\n")));
+    /// assert_eq!(sha3::sha3_256(b"line 0\nline 1\nline 2\n"), hasher.finalize());
+    /// ```
+    pub fn update_iter<I>(&mut self, chunks: I)
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        for chunk in chunks {
+            self.update(chunk.as_ref());
+        }
+    }
+
+    update_int!(
+        u16,
+        update_u16_le,
+        update_u16_be,
+        u32,
+        update_u32_le,
+        update_u32_be,
+        u64,
+        update_u64_le,
+        update_u64_be,
+        u128,
+        update_u128_le,
+        update_u128_be
+    );
+
+    /// Absorb `data` into the state and return the hasher, so that a digest
+    /// can be computed in a single expression.
+    ///
+    /// # Panics
+    /// If called after [`Hasher::update_bits`] with an incomplete last byte.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_256;
+    /// #
+    /// let digest = Sha3_256::new()
+    ///     .chain_update(b"your input ")
+    ///     .chain_update(b"bytes")
+    ///     .finalize();
+    /// assert_eq!(sha3::sha3_256(b"your input bytes"), digest);
+    /// ```
+    #[must_use]
+    pub fn chain_update(mut self, data: &[u8]) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Absorb the first `bit_len` bits of `data` into the state.
+    ///
+    /// FIPS 202 defines SHA-3 on bit strings. As in Appendix B.1 of FIPS 202,
+    /// bits are numbered from the least significant bit of each byte, so the
+    /// 5 bit message `11001` is the byte `0b10011`. If `bit_len` is not a
+    /// multiple of 8, this must be the last update before finalizing.
+    ///
+    /// # Panics
+    /// If `data` is shorter than `bit_len` bits, or if called after an update
+    /// with an incomplete last byte.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_256;
+    /// #
+    /// // SHA3-256 example with a 5 bit message from the NIST examples
+    /// let mut hasher = Sha3_256::new();
+    /// hasher.update_bits(&[0b10011], 5);
+    /// assert_eq!(
+    ///     "7b0047cf5a456882363cbf0fb05322cf65f4b7059a46365e830132e3b5d957af",
+    ///     hex::encode(hasher.finalize())
+    /// );
+    /// ```
+    pub fn update_bits(&mut self, data: &[u8], bit_len: usize) {
+        assert!(
+            !self.trailing_bits,
+            "cannot update after an incomplete byte"
+        );
+        assert!(bit_len <= data.len() * 8, "data must contain bit_len bits");
+        self.sponge.absorb_bits(data, bit_len);
+        self.trailing_bits = !bit_len.is_multiple_of(8);
+    }
+
+    /// Finish absorbing and return the digest.
+    pub fn finalize(self) -> [u8; N] {
+        self.sponge.finalize()
+    }
+
+    /// Finish absorbing and return the lower case hex encoding of the digest.
+    pub fn finalize_hex(self) -> String {
+        encoding::hex_encode(&self.finalize())
+    }
+
+    /// Return the digest of the input absorbed so far without consuming the
+    /// hasher, which can continue absorbing afterwards.
+    ///
+    /// This allows emitting rolling checkpoints of a long stream.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_256;
+    /// #
+    /// let mut hasher = Sha3_256::new();
+    /// hasher.update(b"first chunk");
+    /// assert_eq!(sha3::sha3_256(b"first chunk"), hasher.finalize_clone());
+    /// hasher.update(b", second chunk");
+    /// assert_eq!(
+    ///     sha3::sha3_256(b"first chunk, second chunk"),
+    ///     hasher.finalize()
+    /// );
+    /// ```
+    pub fn finalize_clone(&self) -> [u8; N] {
+        self.sponge.clone().finalize()
+    }
+
+    /// Finish absorbing and write the digest into `output`.
+    ///
+    /// # Errors
+    /// If `output` is not exactly `N` bytes long.
+    pub fn finalize_into(self, output: &mut [u8]) -> Result<(), OutputLengthError> {
+        if output.len() != N {
+            return Err(OutputLengthError {
+                expected: N,
+                actual: output.len(),
+            });
+        }
+        output.copy_from_slice(&self.finalize());
+        Ok(())
+    }
+
+    /// Return the digest and reset the hasher to its initial state, so that
+    /// it can be reused for the next message.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_256;
+    /// #
+    /// let mut hasher = Sha3_256::new();
+    /// for record in [&b"first"[..], b"second"] {
+    ///     hasher.update(record);
+    ///     assert_eq!(sha3::sha3_256(record), hasher.finalize_reset());
+    /// }
+    /// ```
+    pub fn finalize_reset(&mut self) -> [u8; N] {
+        mem::take(self).finalize()
+    }
+
+    /// Finish absorbing and return the digest truncated to the first `M`
+    /// bytes.
+    ///
+    /// Truncating to more bytes than the digest has fails to compile.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_512;
+    /// #
+    /// let mut hasher = Sha3_512::new();
+    /// hasher.update(b"your input bytes");
+    /// let digest: [u8; 32] = hasher.finalize_truncated();
+    /// assert_eq!(sha3::sha3_512(b"your input bytes")[..32], digest);
+    /// ```
+    pub fn finalize_truncated<const M: usize>(self) -> [u8; M] {
+        const { assert!(M <= N, "cannot truncate to more than the digest") };
+        self.sponge.finalize()
+    }
+
+    /// Export the state of the in-progress computation in a portable binary
+    /// format, which [`Hasher::import_state`] restores to continue hashing,
+    /// possibly on a different machine.
+    ///
+    /// The format has a length of [`EXPORTED_STATE_LEN`] bytes and does not
+    /// depend on the endianness of the platform. It starts with a version
+    /// byte, followed by the output size in bits as a 16-bit little-endian
+    /// integer, a byte that is 1 if an incomplete byte was absorbed and 0
+    /// otherwise, the number of bytes absorbed into the current block and the
+    /// delimited suffix as 16-bit little-endian integers, and the 200 bytes of
+    /// the Keccak state in the byte order of FIPS 202.
+    ///
+    /// The exported state allows recovering parts of the absorbed input, so it
+    /// must be protected like the input itself.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Sha3_256;
+    /// #
+    /// let mut hasher = Sha3_256::new();
+    /// hasher.update(b"your input ");
+    /// let exported = hasher.export_state();
+    ///
+    /// let mut hasher = Sha3_256::import_state(&exported).unwrap();
+    /// hasher.update(b"bytes");
+    /// assert_eq!(sha3::sha3_256(b"your input bytes"), hasher.finalize());
+    /// ```
+    pub fn export_state(&self) -> [u8; EXPORTED_STATE_LEN] {
+        let (state, pos, suffix) = self.sponge.to_parts();
+        let mut exported = [0; EXPORTED_STATE_LEN];
+        exported[0] = STATE_FORMAT_VERSION;
+        exported[1..3].copy_from_slice(&(Self::BITS as u16).to_le_bytes());
+        exported[3] = u8::from(self.trailing_bits);
+        exported[4..6].copy_from_slice(&(pos as u16).to_le_bytes());
+        exported[6..8].copy_from_slice(&suffix.to_le_bytes());
+        exported[8..].copy_from_slice(&state);
+        exported
+    }
+
+    /// Restore a hasher from a state returned by [`Hasher::export_state`].
+    ///
+    /// # Errors
+    /// Returns an error if `exported` is not a state in a supported version
+    /// of the format, or if it was exported by a hasher with a different
+    /// output size.
+    pub fn import_state(exported: &[u8]) -> Result<Self, ImportStateError> {
+        if exported.len() != EXPORTED_STATE_LEN {
+            return Err(ImportStateError::InvalidLength(exported.len()));
+        }
+        if exported[0] != STATE_FORMAT_VERSION {
+            return Err(ImportStateError::UnsupportedVersion(exported[0]));
+        }
+        let trailing_bits = match exported[3] {
+            0 => false,
+            1 => true,
+            _ => return Err(ImportStateError::InvalidState),
+        };
+        Self::from_parts(
+            u16::from_le_bytes([exported[1], exported[2]]).into(),
+            &exported[8..],
+            u16::from_le_bytes([exported[4], exported[5]]).into(),
+            u16::from_le_bytes([exported[6], exported[7]]),
+            trailing_bits,
+        )
+    }
+
+    /// Recreate a hasher from the parts of an exported or serialized state.
+    fn from_parts(
+        bits: usize,
+        state: &[u8],
+        pos: usize,
+        suffix: u16,
+        trailing_bits: bool,
+    ) -> Result<Self, ImportStateError> {
+        if bits != Self::BITS {
+            return Err(ImportStateError::ParameterMismatch {
+                expected: Self::BITS,
+                actual: bits,
+            });
+        }
+        let state: &[u8; 200] = state
+            .try_into()
+            .map_err(|_| ImportStateError::InvalidState)?;
+        if pos >= Self::BLOCK_SIZE
+            || suffix == 0
+            || (!trailing_bits && suffix != u16::from(SHA3_DELIMETED_SUFFIX))
+        {
+            return Err(ImportStateError::InvalidState);
+        }
+        Ok(Self {
+            sponge: AbsorbState::from_parts(Self::BLOCK_SIZE, state, pos, suffix),
+            trailing_bits,
+        })
+    }
+}
+
+// Implemented manually to include the output size
+impl<const N: usize> fmt::Debug for Hasher<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hasher")
+            .field("bits", &Self::BITS)
+            .field("sponge", &self.sponge)
+            .field("trailing_bits", &self.trailing_bits)
+            .finish()
+    }
+}
+
+impl<const N: usize> Default for Hasher<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Serialized form of an in-progress [`Hasher`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename = "Hasher")]
+struct Checkpoint {
+    /// Output size in bits, to reject restoring into a different hasher
+    bits: usize,
+    /// Bytes of the Keccak state in the byte order of FIPS 202
+    state: Vec<u8>,
+    pos: usize,
+    suffix: u16,
+    trailing_bits: bool,
+}
+
+/// Serializes the state of an in-progress computation, which can be
+/// deserialized to continue hashing later, e.g., after a restart.
+///
+/// The serialized state contains the absorbed input in a form that allows
+/// recovering parts of it, so it must be protected like the input itself.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Hasher<N> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let (state, pos, suffix) = self.sponge.to_parts();
+        let checkpoint = Checkpoint {
+            bits: Self::BITS,
+            state: state.to_vec(),
+            pos,
+            suffix,
+            trailing_bits: self.trailing_bits,
+        };
+        checkpoint.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Hasher<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        let checkpoint = Checkpoint::deserialize(deserializer)?;
+        Self::from_parts(
+            checkpoint.bits,
+            &checkpoint.state,
+            checkpoint.pos,
+            checkpoint.suffix,
+            checkpoint.trailing_bits,
+        )
+        .map_err(D::Error::custom)
+    }
+}
+
+/// Length of a state exported by [`Hasher::export_state`] in bytes.
+pub const EXPORTED_STATE_LEN: usize = 208;
+
+/// Version of the format of [`Hasher::export_state`].
+const STATE_FORMAT_VERSION: u8 = 1;
+
+/// Error returned by [`Hasher::import_state`] for an invalid exported state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportStateError {
+    /// The exported state has the contained length instead of
+    /// [`EXPORTED_STATE_LEN`] bytes.
+    InvalidLength(usize),
+    /// The exported state has the contained version, which is not supported.
+    UnsupportedVersion(u8),
+    /// The state was exported by a hasher with a different output size.
+    ParameterMismatch {
+        /// Output size of the importing hasher in bits
+        expected: usize,
+        /// Output size of the exporting hasher in bits
+        actual: usize,
+    },
+    /// The exported state is corrupted.
+    InvalidState,
+}
+
+impl fmt::Display for ImportStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLength(len) => write!(
+                f,
+                "exported state has length {len}, expected {EXPORTED_STATE_LEN} bytes"
+            ),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported state format version {version}")
+            }
+            Self::ParameterMismatch { expected, actual } => write!(
+                f,
+                "state of SHA3-{actual} cannot be imported as SHA3-{expected}"
+            ),
+            Self::InvalidState => f.write_str("invalid exported state"),
+        }
+    }
+}
+
+impl Error for ImportStateError {}
+
+/// Error returned by [`Hasher::finalize_into`] for an output buffer of the
+/// wrong length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLengthError {
+    /// Length of the digest in bytes
+    pub expected: usize,
+    /// Length of the output buffer
+    pub actual: usize,
+}
+
+impl fmt::Display for OutputLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "output has length {}, expected {} bytes",
+            self.actual, self.expected
+        )
+    }
+}
+
+impl Error for OutputLengthError {}
+
+#[cfg(test)]
+mod tests {
+    use std::io::IoSlice;
+
+    use super::{ImportStateError, OutputLengthError, Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut hashers = (
+            Sha3_224::new(),
+            Sha3_256::new(),
+            Sha3_384::new(),
+            Sha3_512::new(),
+        );
+        for piece in input.chunks(77) {
+            hashers.0.update(piece);
+            hashers.1.update(piece);
+            hashers.2.update(piece);
+            hashers.3.update(piece);
+        }
+        assert_eq!(crate::sha3_224(&input), hashers.0.finalize());
+        assert_eq!(crate::sha3_256(&input), hashers.1.finalize());
+        assert_eq!(crate::sha3_384(&input), hashers.2.finalize());
+        assert_eq!(crate::sha3_512(&input), hashers.3.finalize());
+    }
+
+    #[test]
+    fn finalize_clone_checkpoints() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut hasher = Sha3_384::new();
+        for (i, piece) in input.chunks(77).enumerate() {
+            hasher.update(piece);
+            let end = (77 * (i + 1)).min(input.len());
+            assert_eq!(crate::sha3_384(&input[..end]), hasher.finalize_clone());
+        }
+        assert_eq!(crate::sha3_384(&input), hasher.finalize());
+    }
+
+    #[test]
+    fn update_vectored() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let (a, rest) = input.split_at(100);
+        let (b, c) = rest.split_at(500);
+        let mut hasher = Sha3_224::new();
+        hasher.update_vectored(&[IoSlice::new(a), IoSlice::new(&[]), IoSlice::new(b)]);
+        hasher.update_vectored(&[IoSlice::new(c)]);
+        assert_eq!(crate::sha3_224(&input), hasher.finalize());
+    }
+
+    #[test]
+    fn update_iter() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut hasher = Sha3_512::new();
+        hasher.update_iter(input.chunks(33));
+        hasher.update_iter(Vec::<Vec<u8>>::new());
+        assert_eq!(crate::sha3_512(&input), hasher.finalize());
+    }
+
+    #[test]
+    fn typed_updates() {
+        let mut hasher = Sha3_256::new();
+        hasher.update_u16_le(0x0102);
+        hasher.update_u16_be(0x0102);
+        hasher.update_u32_le(0x03040506);
+        hasher.update_u32_be(0x03040506);
+        hasher.update_u64_le(0x0708);
+        hasher.update_u64_be(0x0708);
+        hasher.update_u128_le(9);
+        hasher.update_u128_be(9);
+        let expected = [
+            &[2, 1, 1, 2, 6, 5, 4, 3, 3, 4, 5, 6][..],
+            &[8, 7, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 7, 8],
+            &[9],
+            &[0; 30],
+            &[9],
+        ]
+        .concat();
+        assert_eq!(crate::sha3_256(&expected), hasher.finalize());
+    }
+
+    #[test]
+    fn clone_reuses_prefix() {
+        let mut prefix = Sha3_256::new();
+        prefix.update(b"common prefix ");
+        for suffix in [&b"a"[..], b"b", b""] {
+            let mut hasher = prefix.clone();
+            hasher.update(suffix);
+            let expected = crate::sha3_256(&[&b"common prefix "[..], suffix].concat());
+            assert_eq!(expected, hasher.finalize());
+        }
+    }
+
+    #[cfg(not(feature = "debug-state"))]
+    #[test]
+    fn debug_redacts_state() {
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"secret");
+        assert_eq!(
+            "Hasher { bits: 256, sponge: AbsorbState { state: <redacted>, rate: 136, pos: 6, \
+             suffix: 6, overwrite: false }, trailing_bits: false }",
+            format!("{hasher:?}")
+        );
+    }
+
+    #[cfg(feature = "debug-state")]
+    #[test]
+    fn debug_prints_lanes() {
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"secret");
+        // "secret" in little-endian byte order
+        assert!(format!("{hasher:?}").contains("[0000746572636573, 0000000000000000,"));
+    }
+
+    #[test]
+    fn finalize_reset() {
+        let mut hasher = Sha3_256::new();
+        hasher.update_bits(&[0b1], 1);
+        hasher.finalize_reset();
+        hasher.update(b"abc");
+        assert_eq!(crate::sha3_256(b"abc"), hasher.finalize_reset());
+        assert_eq!(crate::sha3_256(b""), hasher.finalize_reset());
+    }
+
+    #[test]
+    fn finalize_into() {
+        let mut output = [0; 64];
+        let mut hasher = Sha3_512::new();
+        hasher.update(b"abc");
+        assert_eq!(Ok(()), hasher.finalize_into(&mut output));
+        assert_eq!(crate::sha3_512(b"abc"), output);
+
+        assert_eq!(
+            Err(OutputLengthError {
+                expected: 32,
+                actual: 64
+            }),
+            Sha3_256::new().finalize_into(&mut output)
+        );
+    }
+
+    fn sha3_256_bits(data: &[u8], bit_len: usize) -> String {
+        let mut hasher = Sha3_256::new();
+        hasher.update_bits(data, bit_len);
+        hex::encode(hasher.finalize())
+    }
+
+    #[test]
+    fn bit_oriented_messages() {
+        // Examples with 5 and 30 bit messages from the NIST example values
+        assert_eq!(
+            "7b0047cf5a456882363cbf0fb05322cf65f4b7059a46365e830132e3b5d957af",
+            sha3_256_bits(&[0x13], 5)
+        );
+        assert_eq!(
+            "c8242fef409e5ae9d1f1c857ae4dc624b92b19809f62aa8c07411c54a078b1d0",
+            sha3_256_bits(&[0x53, 0x58, 0x7b, 0x19], 30)
+        );
+        assert_eq!(crate::sha3_256(b"abc"), {
+            let mut hasher = Sha3_256::new();
+            hasher.update(b"a");
+            hasher.update_bits(b"bc", 16);
+            hasher.finalize()
+        });
+    }
+
+    #[test]
+    fn bit_oriented_padding_at_end_of_block() {
+        // The trailing bits are in the last byte of the 136 byte block, so the
+        // first 1 bit of the padding is the last bit of the block or in the
+        // next block.
+        let input: Vec<u8> = (0..136).map(|i: u8| i.wrapping_mul(7)).collect();
+        assert_eq!(
+            "df3ae530c4950ca67018b1fcb2b0b5015096bca3d0509321ba33dec129aebf51",
+            sha3_256_bits(&input, 136 * 8 - 3)
+        );
+        assert_eq!(
+            "81e7ecb492d033f1692e770a6eb874e70aac45ec10da93483fc8d3537805c097",
+            sha3_256_bits(&input, 136 * 8 - 2)
+        );
+        assert_eq!(
+            "ea2998805aa2b93a778f6ea1c6c28ea975c8a875dbe42ea8332f254c887fe194",
+            sha3_256_bits(&input, 136 * 8 - 1)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn update_after_incomplete_byte() {
+        let mut hasher = Sha3_256::new();
+        hasher.update_bits(&[0], 3);
+        hasher.update(b"");
+    }
+
+    #[test]
+    fn export_import_state() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut hasher = Sha3_384::new();
+        hasher.update(&input[..500]);
+        let exported = hasher.export_state();
+        assert_eq!(1, exported[0]);
+        assert_eq!(384_u16.to_le_bytes(), exported[1..3]);
+
+        let mut imported = Sha3_384::import_state(&exported).unwrap();
+        imported.update(&input[500..]);
+        assert_eq!(crate::sha3_384(&input), imported.finalize());
+
+        assert_eq!(
+            Err(ImportStateError::ParameterMismatch {
+                expected: 256,
+                actual: 384
+            }),
+            Sha3_256::import_state(&exported).map(|_| ())
+        );
+        assert_eq!(
+            Err(ImportStateError::InvalidLength(207)),
+            Sha3_384::import_state(&exported[1..]).map(|_| ())
+        );
+        let mut unsupported = exported;
+        unsupported[0] = 2;
+        assert_eq!(
+            Err(ImportStateError::UnsupportedVersion(2)),
+            Sha3_384::import_state(&unsupported).map(|_| ())
+        );
+        let mut corrupted = exported;
+        corrupted[5] = 1;
+        assert_eq!(
+            Err(ImportStateError::InvalidState),
+            Sha3_384::import_state(&corrupted).map(|_| ())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint_round_trip() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut hasher = Sha3_256::new();
+        hasher.update(&input[..300]);
+        let checkpoint = serde_json::to_string(&hasher).unwrap();
+
+        let mut restored: Sha3_256 = serde_json::from_str(&checkpoint).unwrap();
+        restored.update(&input[300..]);
+        assert_eq!(crate::sha3_256(&input), restored.finalize());
+
+        assert!(serde_json::from_str::<Sha3_512>(&checkpoint).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! HMAC with the SHA-3 hash functions
+//!
+//! HMAC is specified in [FIPS 198-1]. For SHA-3, the block size used to pad
+//! the key with `ipad` and `opad` is the rate of the sponge, e.g., 136 bytes
+//! for SHA3-256 (see [`Hasher::BLOCK_SIZE`]). Keys longer than the block size
+//! are hashed first.
+//!
+//! Note that SHA-3 does not need the nested construction of HMAC to be a
+//! secure MAC. Prefer [KMAC](crate::kmac) unless a protocol mandates HMAC.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::{Sha3_256, hmac::Hmac};
+//! #
+//! let mut mac = Hmac::<Sha3_256>::new(b"key");
+//! mac.update(b"your input bytes");
+//! let tag: [u8; 32] = mac.finalize();
+//! ```
+//!
+//! [FIPS 198-1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.198-1.pdf
+
+use crate::hasher::Hasher;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+/// Largest block size of the SHA-3 hash functions, that of SHA3-224
+const MAX_BLOCK_SIZE: usize = 144;
+
+/// HMAC over the SHA-3 hasher `H`, e.g., `Hmac<Sha3_256>`.
+pub struct Hmac<H> {
+    inner: H,
+    /// Outer hasher with the key already absorbed
+    outer: H,
+}
+
+impl<const N: usize> Hmac<Hasher<N>> {
+    /// Create a new HMAC instance with the `key`.
+    pub fn new(key: &[u8]) -> Self {
+        let mut block = [0; MAX_BLOCK_SIZE];
+        let padded_key = &mut block[..Hasher::<N>::BLOCK_SIZE];
+        if key.len() > Hasher::<N>::BLOCK_SIZE {
+            let mut hasher = Hasher::<N>::new();
+            hasher.update(key);
+            let digest = hasher.finalize();
+            padded_key[..N].copy_from_slice(&digest);
+        } else {
+            padded_key[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = Hasher::new();
+        let mut outer = Hasher::new();
+        padded_key.iter_mut().for_each(|k| *k ^= IPAD);
+        inner.update(padded_key);
+        // Swap the inner pad for the outer pad
+        padded_key.iter_mut().for_each(|k| *k ^= IPAD ^ OPAD);
+        outer.update(padded_key);
+        Self { inner, outer }
+    }
+
+    /// Absorb `data` into the inner hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Finish the computation and return the MAC.
+    pub fn finalize(self) -> [u8; N] {
+        let Self { inner, mut outer } = self;
+        outer.update(&inner.finalize());
+        outer.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hmac;
+    use crate::{Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+
+    #[test]
+    fn nist_examples() {
+        // HMAC-SHA3 examples of the NIST Cryptographic Standards and
+        // Guidelines, keylen<blocklen
+        let key: Vec<u8> = (0..64).collect();
+        let data = b"Sample message for keylen<blocklen";
+        let mut mac = Hmac::<Sha3_224>::new(&key[..28]);
+        mac.update(data);
+        assert_eq!(
+            "332cfd59347fdb8e576e77260be4aba2d6dc53117b3bfb52c6d18c04",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_256>::new(&key[..32]);
+        mac.update(data);
+        assert_eq!(
+            "4fe8e202c4f058e8dddc23d8c34e467343e23555e24fc2f025d598f558f67205",
+            hex::encode(mac.finalize())
+        );
+
+        // keylen=blocklen
+        let key: Vec<u8> = (0..136).collect();
+        let mut mac = Hmac::<Sha3_256>::new(&key);
+        mac.update(b"Sample message for keylen=blocklen");
+        assert_eq!(
+            "68b94e2e538a9be4103bebb5aa016d47961d4d1aa906061313b557f8af2c3faa",
+            hex::encode(mac.finalize())
+        );
+    }
+
+    #[test]
+    fn short_key() {
+        let key = b"Jefe";
+        let data = b"what do ya want for nothing?";
+        let mut mac = Hmac::<Sha3_224>::new(key);
+        mac.update(data);
+        assert_eq!(
+            "7fdb8dd88bd2f60d1b798634ad386811c2cfc85bfaf5d52bbace5e66",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_256>::new(key);
+        mac.update(data);
+        assert_eq!(
+            "c7d4072e788877ae3596bbb0da73b887c9171f93095b294ae857fbe2645e1ba5",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_384>::new(key);
+        mac.update(data);
+        assert_eq!(
+            "f1101f8cbf9766fd6764d2ed61903f21ca9b18f57cf3e1a23ca13508a93243ce\
+             48c045dc007f26a21b3f5e0e9df4c20a",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_512>::new(key);
+        mac.update(data);
+        assert_eq!(
+            "5a4bfeab6166427c7a3647b747292b8384537cdb89afb3bf5665e4c5e709350b\
+             287baec921fd7ca0ee7a0c31d022a95e1fc92ba9d77df883960275beb4e62024",
+            hex::encode(mac.finalize())
+        );
+    }
+
+    #[test]
+    fn key_longer_than_block_size() {
+        let key = [0xaa; 200];
+        let data = b"Test Using Larger Than Block-Size Key - Hash Key First";
+        let mut mac = Hmac::<Sha3_224>::new(&key);
+        mac.update(data);
+        assert_eq!(
+            "5e73d57bd011f0f92fef3c3b92ea4bcb4821c6d83c37db34f29e0760",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_256>::new(&key);
+        mac.update(data);
+        assert_eq!(
+            "49ad92b02124fdac9627ae45e008a696182ab6bfb8470457777c744aeb9df06f",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_384>::new(&key);
+        mac.update(data);
+        assert_eq!(
+            "3e7b62d091d75f484892bc2ed26d7b0ed37c9529f0227197cc8522971eb6f721\
+             5dd4e0cc6ea306987e0cbfe914f3a916",
+            hex::encode(mac.finalize())
+        );
+        let mut mac = Hmac::<Sha3_512>::new(&key);
+        mac.update(data);
+        assert_eq!(
+            "fafc7b7fe3332ce153966b27f6586fa5b49ec5d8dff3d7fd26a011451ca4c9de\
+             437913879159d9c5181a9a6f377ef18b48399756decea695b04fe90a9d3b93d1",
+            hex::encode(mac.finalize())
+        );
+    }
+}
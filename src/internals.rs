@@ -0,0 +1,19 @@
+//! Access to the internals of the Keccak-f\[1600\] permutation
+//!
+//! This module is only available with the `internals` feature. It is intended
+//! for research and debugging, e.g. to explore cryptanalysis exercises or
+//! constructions built directly on the permutation. The APIs in this module
+//! are lower level than the hash functions and may change more frequently.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::internals::State;
+//! #
+//! let mut state = State::default();
+//! state.permute();
+//! assert!(state != State::default());
+//! state.inverse_permute();
+//! assert!(state == State::default());
+//! ```
+
+pub use crate::keccak::{FromHexError, State};
@@ -0,0 +1,746 @@
+//! Hashing of `std::io` sources
+//!
+//! The functions in this module stream the data of a [`Read`] source through
+//! a fixed buffer into an incremental [`Hasher`], so that files and sockets
+//! can be hashed without loading them into memory. [`HashingReader`] and
+//! [`HashingWriter`] hash the data passed through them to another reader or
+//! writer.
+//!
+//! With the `mmap` feature, `hash_file_mmap` maps a file into memory and
+//! absorbs it directly, which avoids the read system calls and copying the
+//! data into the buffer. As the file must not be modified while it is mapped,
+//! it is an `unsafe` function. On targets other than unix, it falls back to
+//! reading the file like [`hash_file`].
+//!
+//! The `_with_progress` variants and [`ProgressHasher`] report the number of
+//! absorbed bytes at a fixed interval, e.g., to display a progress bar.
+//! The `_cancellable` variants stop hashing once a [`CancellationToken`] is
+//! cancelled, e.g., on a timeout.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::io::sha3_256_reader;
+//! #
+//! let file: &[u8] = b"your input bytes";
+//! let digest = sha3_256_reader(file).unwrap();
+//! assert_eq!(sha3::sha3_256(b"your input bytes"), digest);
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, ErrorKind, Read, Write},
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use crate::hasher::Hasher;
+
+/// Size of the buffer for reading from the source
+const BUFFER_LEN: usize = 8 * 1024;
+
+/// Hash all data of `reader` until the end of the stream with the SHA-3 hash
+/// function with `N` bytes output.
+///
+/// # Errors
+/// If reading fails with an error other than [`ErrorKind::Interrupted`].
+pub fn hash_reader<const N: usize, R: Read>(reader: R) -> io::Result<[u8; N]> {
+    let mut hasher = Hasher::<N>::new();
+    update_from_reader(&mut hasher, reader)?;
+    Ok(hasher.finalize())
+}
+
+/// Hash all data of `reader` like [`hash_reader`] and call `progress` every
+/// `interval` bytes, see [`ProgressHasher`].
+///
+/// # Errors
+/// If reading fails with an error other than [`ErrorKind::Interrupted`].
+///
+/// # Panics
+/// If `interval` is 0.
+pub fn hash_reader_with_progress<const N: usize, R: Read, F: FnMut(u64)>(
+    reader: R,
+    interval: u64,
+    progress: F,
+) -> io::Result<[u8; N]> {
+    let mut hasher = ProgressHasher::<N, F>::new(interval, progress);
+    read_chunks(reader, |chunk| {
+        hasher.update(chunk);
+        Ok(())
+    })?;
+    Ok(hasher.finalize())
+}
+
+/// Absorb all data of `reader` until the end of the stream into `hasher`.
+///
+/// Returns the number of bytes absorbed.
+///
+/// # Errors
+/// If reading fails with an error other than [`ErrorKind::Interrupted`].
+pub fn update_from_reader<const N: usize, R: Read>(
+    hasher: &mut Hasher<N>,
+    reader: R,
+) -> io::Result<u64> {
+    read_chunks(reader, |chunk| {
+        hasher.update(chunk);
+        Ok(())
+    })
+}
+
+/// Hash the file at `path` with the SHA-3 hash function with `N` bytes output.
+///
+/// The file is read with [`hash_reader`].
+///
+/// # Errors
+/// If opening or reading the file fails.
+pub fn hash_file<const N: usize>(path: impl AsRef<Path>) -> io::Result<[u8; N]> {
+    hash_reader(File::open(path)?)
+}
+
+/// Hash the file at `path` like [`hash_file`], but memory-map it instead of
+/// reading it into a buffer.
+///
+/// Empty files are not mapped, as mappings cannot be empty.
+///
+/// # Safety
+/// The file must not be modified or truncated, by this or any other process,
+/// until the function returns. The mapped contents are passed to the hasher
+/// as a `&[u8]`, so a concurrent write is undefined behavior and truncating
+/// the file raises `SIGBUS`.
+///
+/// # Errors
+/// If opening, mapping or reading the file fails.
+#[cfg(all(feature = "mmap", unix))]
+pub unsafe fn hash_file_mmap<const N: usize>(path: impl AsRef<Path>) -> io::Result<[u8; N]> {
+    let file = File::open(path)?;
+    let Ok(len) = usize::try_from(file.metadata()?.len()) else {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "file is too large to be mapped",
+        ));
+    };
+    if len == 0 {
+        return hash_reader(file);
+    }
+    let map = mmap::Mmap::map(&file, len)?;
+    let mut hasher = Hasher::<N>::new();
+    // SAFETY: The caller guarantees that the file is not modified while it
+    // is mapped.
+    hasher.update(unsafe { map.as_slice() });
+    Ok(hasher.finalize())
+}
+
+/// Hash the file at `path` like [`hash_file`], as memory-mapping is only
+/// supported on unix targets.
+///
+/// # Safety
+/// This variant reads the file and is always safe to call. It has the
+/// signature of the memory-mapping variant on unix targets, whose safety
+/// requirements portable callers must uphold.
+///
+/// # Errors
+/// If opening or reading the file fails.
+#[cfg(all(feature = "mmap", not(unix)))]
+pub unsafe fn hash_file_mmap<const N: usize>(path: impl AsRef<Path>) -> io::Result<[u8; N]> {
+    hash_file(path)
+}
+
+/// Hash the file at `path` like [`hash_file`] and call `progress` every
+/// `interval` bytes, see [`ProgressHasher`].
+///
+/// # Errors
+/// If opening or reading the file fails.
+///
+/// # Panics
+/// If `interval` is 0.
+pub fn hash_file_with_progress<const N: usize, F: FnMut(u64)>(
+    path: impl AsRef<Path>,
+    interval: u64,
+    progress: F,
+) -> io::Result<[u8; N]> {
+    hash_reader_with_progress(File::open(path)?, interval, progress)
+}
+
+/// Hash all data of `reader` like [`hash_reader`] and abort with a
+/// [`Cancelled`] error once `token` is cancelled.
+///
+/// The token is checked before every read from `reader`.
+///
+/// # Errors
+/// If reading fails with an error other than [`ErrorKind::Interrupted`], or
+/// an error of kind [`ErrorKind::Other`] wrapping [`Cancelled`] if the
+/// computation was cancelled.
+pub fn hash_reader_cancellable<const N: usize, R: Read>(
+    reader: R,
+    token: &CancellationToken,
+) -> io::Result<[u8; N]> {
+    let mut hasher = Hasher::<N>::new();
+    token.check()?;
+    read_chunks(reader, |chunk| {
+        hasher.update(chunk);
+        token.check()
+    })?;
+    Ok(hasher.finalize())
+}
+
+/// Hash the file at `path` like [`hash_file`] and abort with a [`Cancelled`]
+/// error once `token` is cancelled.
+///
+/// The token is checked before every read from the file.
+///
+/// # Errors
+/// If opening or reading the file fails, or an error of kind
+/// [`ErrorKind::Other`] wrapping [`Cancelled`] if the computation was
+/// cancelled.
+pub fn hash_file_cancellable<const N: usize>(
+    path: impl AsRef<Path>,
+    token: &CancellationToken,
+) -> io::Result<[u8; N]> {
+    hash_reader_cancellable(File::open(path)?, token)
+}
+
+/// Pass all data of `reader` in chunks to `absorb` and return its length.
+///
+/// Stops at the first error returned by `absorb`.
+fn read_chunks<R: Read>(
+    mut reader: R,
+    mut absorb: impl FnMut(&[u8]) -> io::Result<()>,
+) -> io::Result<u64> {
+    let mut buffer = [0; BUFFER_LEN];
+    let mut total = 0;
+    loop {
+        let len = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(total),
+            Ok(len) => len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        absorb(&buffer[..len])?;
+        total += len as u64;
+    }
+}
+
+/// Token to cancel a running [`hash_reader_cancellable`] or
+/// [`hash_file_cancellable`] from another thread.
+///
+/// Clones of a token share its state, so that cancelling one clone cancels
+/// all of them.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::io::{CancellationToken, Cancelled, hash_reader_cancellable};
+/// #
+/// let token = CancellationToken::new();
+/// token.clone().cancel();
+/// let err = hash_reader_cancellable::<32, _>(&b"upload"[..], &token).unwrap_err();
+/// assert!(err.get_ref().unwrap().is::<Cancelled>());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the token and all its clones.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn check(&self) -> io::Result<()> {
+        if self.is_cancelled() {
+            Err(io::Error::other(Cancelled))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Error wrapped in the [`io::Error`] returned by a cancelled computation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("hashing was cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// SHA3-224 of all data of `reader`.
+///
+/// # Errors
+/// See [`hash_reader`].
+pub fn sha3_224_reader<R: Read>(reader: R) -> io::Result<[u8; 28]> {
+    hash_reader::<28, R>(reader)
+}
+
+/// SHA3-256 of all data of `reader`.
+///
+/// # Errors
+/// See [`hash_reader`].
+pub fn sha3_256_reader<R: Read>(reader: R) -> io::Result<[u8; 32]> {
+    hash_reader::<32, R>(reader)
+}
+
+/// SHA3-384 of all data of `reader`.
+///
+/// # Errors
+/// See [`hash_reader`].
+pub fn sha3_384_reader<R: Read>(reader: R) -> io::Result<[u8; 48]> {
+    hash_reader::<48, R>(reader)
+}
+
+/// SHA3-512 of all data of `reader`.
+///
+/// # Errors
+/// See [`hash_reader`].
+pub fn sha3_512_reader<R: Read>(reader: R) -> io::Result<[u8; 64]> {
+    hash_reader::<64, R>(reader)
+}
+
+/// Hasher that reports its progress to a callback.
+///
+/// The callback is called with the total number of bytes absorbed so far
+/// whenever it reaches a multiple of the interval, i.e., with `interval`,
+/// `2 * interval`, and so on. This allows displaying a progress bar when
+/// hashing large inputs.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::io::ProgressHasher;
+/// #
+/// let mut reported = Vec::new();
+/// let mut hasher = ProgressHasher::<32, _>::new(1024, |absorbed| reported.push(absorbed));
+/// hasher.update(&[0; 1500]);
+/// hasher.update(&[0; 1500]);
+/// let digest = hasher.finalize();
+/// assert_eq!(sha3::sha3_256(&[0; 3000]), digest);
+/// assert_eq!(vec![1024, 2048], reported);
+/// ```
+pub struct ProgressHasher<const N: usize, F> {
+    hasher: Hasher<N>,
+    interval: u64,
+    absorbed: u64,
+    /// Number of absorbed bytes at which the callback is called next
+    next_report: u64,
+    progress: F,
+}
+
+impl<const N: usize, F: FnMut(u64)> ProgressHasher<N, F> {
+    /// Create a new hasher calling `progress` every `interval` bytes.
+    ///
+    /// # Panics
+    /// If `interval` is 0.
+    pub fn new(interval: u64, progress: F) -> Self {
+        assert_ne!(interval, 0, "progress interval must be positive");
+        Self {
+            hasher: Hasher::new(),
+            interval,
+            absorbed: 0,
+            next_report: interval,
+            progress,
+        }
+    }
+
+    /// Absorb `data` into the hasher.
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let until_report = self.next_report - self.absorbed;
+            let len = usize::try_from(until_report).map_or(data.len(), |len| len.min(data.len()));
+            let (chunk, rest) = data.split_at(len);
+            self.hasher.update(chunk);
+            self.absorbed += len as u64;
+            if self.absorbed == self.next_report {
+                (self.progress)(self.absorbed);
+                self.next_report += self.interval;
+            }
+            data = rest;
+        }
+    }
+
+    /// Number of bytes absorbed so far.
+    pub fn absorbed(&self) -> u64 {
+        self.absorbed
+    }
+
+    /// Finish the computation and return the digest.
+    pub fn finalize(self) -> [u8; N] {
+        self.hasher.finalize()
+    }
+}
+
+/// Reader that hashes all data read through it with the SHA-3 hash function
+/// with `N` bytes output.
+///
+/// The data is passed through unchanged, so a download can be verified while
+/// it is streamed to disk.
+///
+/// # Example Usage
+/// ```
+/// # use std::io::Read;
+/// # use sha3::io::HashingReader;
+/// #
+/// let download: &[u8] = b"your input bytes";
+/// let mut reader = HashingReader::<_, 32>::new(download);
+/// let mut contents = Vec::new();
+/// reader.read_to_end(&mut contents).unwrap();
+/// let (_, digest) = reader.into_inner();
+/// assert_eq!(sha3::sha3_256(&contents), digest);
+/// ```
+pub struct HashingReader<R, const N: usize> {
+    reader: R,
+    hasher: Hasher<N>,
+}
+
+impl<R: Read, const N: usize> HashingReader<R, N> {
+    /// Wrap `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            hasher: Hasher::new(),
+        }
+    }
+
+    /// Reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Return the wrapped reader and the digest of all data read so far.
+    pub fn into_inner(self) -> (R, [u8; N]) {
+        (self.reader, self.hasher.finalize())
+    }
+}
+
+impl<R: Read, const N: usize> Read for HashingReader<R, N> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Writer that hashes all data written through it with the SHA-3 hash function
+/// with `N` bytes output.
+///
+/// The data is passed through unchanged, so a digest can be computed while
+/// serializing directly to a file or socket.
+///
+/// # Example Usage
+/// ```
+/// # use std::io::Write;
+/// # use sha3::io::HashingWriter;
+/// #
+/// let mut writer = HashingWriter::<_, 32>::new(Vec::new());
+/// writer.write_all(b"your input bytes").unwrap();
+/// let (written, digest) = writer.finalize();
+/// assert_eq!(sha3::sha3_256(&written), digest);
+/// ```
+pub struct HashingWriter<W, const N: usize> {
+    writer: W,
+    hasher: Hasher<N>,
+}
+
+impl<W: Write, const N: usize> HashingWriter<W, N> {
+    /// Wrap `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            hasher: Hasher::new(),
+        }
+    }
+
+    /// Reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Return the wrapped writer and the digest of all data written so far.
+    ///
+    /// The writer is not flushed.
+    pub fn finalize(self) -> (W, [u8; N]) {
+        (self.writer, self.hasher.finalize())
+    }
+}
+
+impl<W: Write, const N: usize> Write for HashingWriter<W, N> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Only the bytes accepted by the writer are hashed
+        let len = self.writer.write(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(all(feature = "mmap", unix))]
+mod mmap {
+    use std::{fs::File, io, os::fd::AsRawFd, ptr, slice};
+
+    /// Read-only private mapping of a whole file.
+    pub(super) struct Mmap {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    impl Mmap {
+        /// Map the first `len` bytes of `file`. `len` must be positive.
+        pub(super) fn map(file: &File, len: usize) -> io::Result<Self> {
+            // SAFETY: We request a new read-only mapping at an address chosen
+            // by the kernel, so no existing memory is affected.
+            let ptr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { ptr, len })
+        }
+
+        /// Contents of the mapping.
+        ///
+        /// # Safety
+        /// The file must not be modified or truncated while the returned
+        /// slice is alive.
+        pub(super) unsafe fn as_slice(&self) -> &[u8] {
+            // SAFETY: The mapping is readable, has `len` bytes and lives as
+            // long as self. The caller guarantees that the contents do not
+            // change.
+            unsafe { slice::from_raw_parts(self.ptr.cast(), self.len) }
+        }
+    }
+
+    impl Drop for Mmap {
+        fn drop(&mut self) {
+            // SAFETY: The mapping was created in `map` and is not used after
+            // this.
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{self, ErrorKind, Read, Write},
+    };
+
+    use super::{
+        CancellationToken, Cancelled, HashingReader, HashingWriter, hash_file,
+        hash_file_cancellable, hash_file_with_progress, hash_reader_cancellable,
+        hash_reader_with_progress, sha3_224_reader, sha3_512_reader,
+    };
+    use crate::sha3_256;
+
+    /// Reader returning at most 7 bytes per call, interrupted every other
+    /// call.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        interrupt: bool,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.interrupt = !self.interrupt;
+            if self.interrupt {
+                return Err(ErrorKind::Interrupted.into());
+            }
+            let len = buf.len().min(7).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    #[test]
+    fn matches_one_shot() {
+        let input: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        assert_eq!(
+            crate::sha3_512(&input),
+            sha3_512_reader(&input[..]).unwrap()
+        );
+        let reader = Trickle {
+            data: &input,
+            interrupt: false,
+        };
+        assert_eq!(crate::sha3_224(&input), sha3_224_reader(reader).unwrap());
+    }
+
+    #[cfg(not(miri))]
+    #[test]
+    fn hash_files() {
+        let dir = std::env::temp_dir().join(format!("sha3-hash-file-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for len in [0, 1000, 3 * 1024 * 1024 + 5] {
+            let input: Vec<u8> = (0..=255).cycle().take(len).collect();
+            let path = dir.join(format!("{len}.bin"));
+            fs::write(&path, &input).unwrap();
+            assert_eq!(sha3_256(&input), hash_file::<32>(&path).unwrap());
+            #[cfg(feature = "mmap")]
+            // SAFETY: The file is not modified while it is hashed.
+            assert_eq!(sha3_256(&input), unsafe {
+                super::hash_file_mmap::<32>(&path).unwrap()
+            });
+        }
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(hash_file::<32>(dir.join("missing")).is_err());
+    }
+
+    #[test]
+    fn reports_progress() {
+        let input: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        let reader = Trickle {
+            data: &input,
+            interrupt: false,
+        };
+        let mut reported = Vec::new();
+        let digest =
+            hash_reader_with_progress::<32, _, _>(reader, 3000, |n| reported.push(n)).unwrap();
+        assert_eq!(sha3_256(&input), digest);
+        assert_eq!(vec![3000, 6000, 9000, 12000, 15000, 18000], reported);
+
+        // Every byte
+        let mut count = 0;
+        hash_reader_with_progress::<32, _, _>(&input[..100], 1, |n| {
+            count += 1;
+            assert_eq!(count, n);
+        })
+        .unwrap();
+        assert_eq!(100, count);
+    }
+
+    #[cfg(not(miri))]
+    #[test]
+    fn reports_file_progress() {
+        let dir = std::env::temp_dir().join(format!("sha3-progress-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Within one buffer and across many buffers
+        for len in [1000, 2 * 1024 * 1024 + 5] {
+            let input: Vec<u8> = (0..=255).cycle().take(len).collect();
+            let path = dir.join(format!("{len}.bin"));
+            fs::write(&path, &input).unwrap();
+            let mut last = 0;
+            let digest = hash_file_with_progress::<32, _>(&path, 100, |n| last = n).unwrap();
+            assert_eq!(sha3_256(&input), digest);
+            assert_eq!(len as u64 / 100 * 100, last);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cancellation() {
+        /// Reader cancelling the token after the given number of reads
+        struct Cancelling<'a> {
+            token: &'a CancellationToken,
+            reads: usize,
+        }
+
+        impl Read for Cancelling<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.reads == 0 {
+                    self.token.cancel();
+                } else {
+                    self.reads -= 1;
+                }
+                buf.fill(0);
+                Ok(buf.len())
+            }
+        }
+
+        let token = CancellationToken::new();
+        assert!(hash_reader_cancellable::<32, _>(&b"upload"[..], &token).is_ok());
+        // The reader never ends, so only cancellation can stop hashing
+        let reader = Cancelling {
+            token: &token,
+            reads: 3,
+        };
+        let err = hash_reader_cancellable::<32, _>(reader, &token).unwrap_err();
+        assert_eq!(ErrorKind::Other, err.kind());
+        assert_eq!(Cancelled, err.downcast::<Cancelled>().unwrap());
+        assert!(token.is_cancelled());
+        assert!(hash_file_cancellable::<32>("Cargo.toml", &token).is_err());
+    }
+
+    #[test]
+    fn hashing_reader_passes_data_through() {
+        let input: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        let trickle = Trickle {
+            data: &input,
+            interrupt: false,
+        };
+        let mut reader = HashingReader::<_, 48>::new(trickle);
+        let mut output = Vec::new();
+        // read_to_end retries interrupted reads
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(input, output);
+        let (trickle, digest) = reader.into_inner();
+        assert!(trickle.data.is_empty());
+        assert_eq!(crate::sha3_384(&input), digest);
+    }
+
+    #[test]
+    fn hashing_writer_passes_data_through() {
+        /// Writer accepting at most 5 bytes per call
+        struct Short(Vec<u8>);
+
+        impl Write for Short {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                let len = buf.len().min(5);
+                self.0.extend_from_slice(&buf[..len]);
+                Ok(len)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let input: Vec<u8> = (0..=255).cycle().take(20_000).collect();
+        let mut writer = HashingWriter::<_, 32>::new(Short(Vec::new()));
+        writer.write_all(&input).unwrap();
+        writer.flush().unwrap();
+        let (short, digest) = writer.finalize();
+        assert_eq!(input, short.0);
+        assert_eq!(sha3_256(&input), digest);
+    }
+
+    #[test]
+    fn propagates_errors() {
+        struct Failing;
+
+        impl Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(ErrorKind::BrokenPipe.into())
+            }
+        }
+
+        let err = sha3_224_reader(Failing).unwrap_err();
+        assert_eq!(ErrorKind::BrokenPipe, err.kind());
+    }
+}
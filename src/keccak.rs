@@ -10,20 +10,14 @@
 //! [XKCP]: https://github.com/XKCP/XKCP
 #![allow(non_snake_case)]
 use std::{
-    mem,
+    fmt,
     ops::{Index, IndexMut},
-    slice,
 };
 
 // NOTE: References to Sections, Algorithms, Tables, etc. refer to the
 // FIPS 202 standard (https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf)
 // if not otherwise specified.
 
-/// Bits that are appended to the end of the input for domain separation and
-/// padding. For SHA-3, this is the bit pattern 0b10 + the first 1 bit of the
-/// pad10*1 padding.
-const DELIMETED_SUFFIX: u8 = 0b110;
-
 /// Number of rounds performed in `KECCAK-C`.
 const ROUNDS: usize = 24;
 
@@ -31,8 +25,8 @@ const ROUNDS: usize = 24;
 type Lane = u64;
 
 /// State array A. Contains 1600 bits.
-#[derive(Clone, Copy)]
-struct State([Lane; 25]);
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct State([Lane; 25]);
 
 /// Compute a [`Lane`] index if viewing the [`State`] as `[u64; 25]`.
 ///
@@ -63,12 +57,119 @@ impl IndexMut<(usize, usize)> for State {
     }
 }
 
+/// Prints the lanes as a matrix of rows `y` with the `debug-state` feature and
+/// redacts them otherwise, as the state may contain secrets.
+impl fmt::Debug for State {
+    #[cfg(feature = "debug-state")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Row([Lane; 5]);
+
+        impl fmt::Debug for Row {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("[")?;
+                for (x, lane) in self.0.iter().enumerate() {
+                    if x > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{lane:016x}")?;
+                }
+                f.write_str("]")
+            }
+        }
+
+        f.debug_list()
+            .entries((0..5).map(|y| Row(std::array::from_fn(|x| Lane::from_le(self[(x, y)])))))
+            .finish()
+    }
+
+    #[cfg(not(feature = "debug-state"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
 impl State {
-    fn bytes_mut(&mut self) -> &mut [u8] {
-        let len = self.0.len() * mem::size_of::<u64>();
-        let ptr = self.0.as_mut_ptr().cast();
-        // TODO safety
-        unsafe { slice::from_raw_parts_mut(ptr, len) }
+    /// Call `f` with the 200 bytes of the state in the byte order of FIPS 202
+    /// and return its result.
+    ///
+    /// Outside of the permutation, the lanes are stored in little-endian byte
+    /// order, so the bytes are the in-memory representation of the lanes.
+    /// With the `zerocopy` feature, `f` gets a view of the lanes as bytes.
+    /// Otherwise, the lanes are copied into a byte array and back.
+    #[inline(always)]
+    pub(crate) fn with_bytes<R>(&mut self, f: impl FnOnce(&mut [u8; 200]) -> R) -> R {
+        #[cfg(feature = "zerocopy")]
+        {
+            f(zerocopy::transmute_mut!(&mut self.0))
+        }
+        #[cfg(not(feature = "zerocopy"))]
+        {
+            let mut bytes = self.bytes();
+            let result = f(&mut bytes);
+            for (lane, chunk) in self.0.iter_mut().zip(bytes.chunks_exact(8)) {
+                *lane = Lane::from_ne_bytes(chunk.try_into().expect("chunk has 8 bytes"));
+            }
+            result
+        }
+    }
+
+    /// XOR `block` onto the first bytes of the state.
+    ///
+    /// Whole lanes of the input are read as `u64` words and XORed onto the
+    /// lanes directly, only the bytes of a trailing partial lane go through
+    /// [`State::with_bytes`].
+    #[inline(always)]
+    pub(crate) fn xor_lanes(&mut self, block: &[u8]) {
+        let chunks = block.chunks_exact(8);
+        let remainder = chunks.remainder();
+        let lanes = block.len() / 8;
+        for (lane, chunk) in self.0.iter_mut().zip(chunks) {
+            // Lanes are stored in little-endian byte order, so the native
+            // byte order of the word matches the bytes of the lane
+            *lane ^= Lane::from_ne_bytes(chunk.try_into().expect("chunk has 8 bytes"));
+        }
+        if !remainder.is_empty() {
+            self.with_bytes(|bytes| {
+                bytes[lanes * 8..]
+                    .iter_mut()
+                    .zip(remainder)
+                    .for_each(|(byte, input)| *byte ^= input);
+            });
+        }
+    }
+
+    /// Copy the bytes of the state starting at byte `offset` into `output`.
+    ///
+    /// Unlike [`State::bytes`], only the lanes covering the output are read.
+    #[inline(always)]
+    pub(crate) fn read_bytes(&self, offset: usize, mut output: &mut [u8]) {
+        let mut pos = offset;
+        while !output.is_empty() {
+            let lane = self.0[pos / 8].to_ne_bytes();
+            let start = pos % 8;
+            let len = output.len().min(8 - start);
+            let (head, tail) = output.split_at_mut(len);
+            head.copy_from_slice(&lane[start..start + len]);
+            output = tail;
+            pos += len;
+        }
+    }
+
+    /// XOR `byte` onto byte `pos` of the state.
+    #[inline(always)]
+    pub(crate) fn xor_byte(&mut self, pos: usize, byte: u8) {
+        let mut bytes = [0; 8];
+        bytes[pos % 8] = byte;
+        self.0[pos / 8] ^= Lane::from_ne_bytes(bytes);
+    }
+
+    /// The 200 bytes of the state in the byte order of FIPS 202.
+    pub(crate) fn bytes(&self) -> [u8; 200] {
+        let mut bytes = [0; 200];
+        for (chunk, lane) in bytes.chunks_exact_mut(8).zip(&self.0) {
+            chunk.copy_from_slice(&lane.to_ne_bytes());
+        }
+        bytes
     }
 
     fn lanes_to_le(&mut self) {
@@ -77,7 +178,124 @@ impl State {
     }
 }
 
+#[cfg(feature = "internals")]
+impl State {
+    /// Apply the Keccak-f\[1600\] permutation to the state.
+    pub fn permute(&mut self) {
+        keccakf_1600_state_permute(self);
+    }
+
+    /// Apply the KECCAK-p\[1600, nr\] permutation with the last `rounds`
+    /// rounds of Keccak-f\[1600\] to the state.
+    ///
+    /// # Panics
+    /// If `rounds` is greater than 24.
+    pub fn permute_rounds(&mut self, rounds: usize) {
+        assert!(rounds <= ROUNDS, "KECCAK-p[1600] has at most 24 rounds");
+        self.lanes_to_le();
+        keccak_p1600_rounds(self, rounds);
+        self.lanes_to_le();
+    }
+
+    /// Apply the inverse of the Keccak-f\[1600\] permutation to the state.
+    pub fn inverse_permute(&mut self) {
+        keccakf_1600_state_permute_inverse(self);
+    }
+
+    /// Create a state from its 25 lanes, where lane `(x, y)` is at index
+    /// `x + 5 * y`.
+    pub fn from_lanes(lanes: [Lane; 25]) -> Self {
+        // Outside of the permutation, lanes are stored in little-endian byte
+        // order
+        Self(lanes.map(Lane::to_le))
+    }
+
+    /// Lanes of the state, where lane `(x, y)` is at index `x + 5 * y`.
+    pub fn to_lanes(&self) -> [Lane; 25] {
+        self.0.map(Lane::from_le)
+    }
+
+    /// Create a state from the hex encoding of its 200 bytes.
+    ///
+    /// The bytes are in the order used by FIPS 202 and the intermediate values
+    /// published by the Keccak Team, i.e. lane `(0, 0)` in little-endian byte
+    /// order comes first. Upper and lower case hex digits are accepted and
+    /// ASCII whitespace between digits is ignored, so that states can be
+    /// copied from papers.
+    pub fn from_hex(hex: &str) -> Result<Self, FromHexError> {
+        let mut state = Self::default();
+        state.with_bytes(|bytes| {
+            let mut digits = hex.char_indices().filter(|(_, c)| !c.is_ascii_whitespace());
+            let mut len = 0;
+            while let Some((index, c)) = digits.next() {
+                let high = hex_value(index, c)?;
+                let (index, c) = digits.next().ok_or(FromHexError::OddLength)?;
+                let low = hex_value(index, c)?;
+                if len < bytes.len() {
+                    bytes[len] = high << 4 | low;
+                }
+                len += 1;
+            }
+            if len != bytes.len() {
+                return Err(FromHexError::InvalidLength(len));
+            }
+            Ok(())
+        })?;
+        Ok(state)
+    }
+
+    /// Lower case hex encoding of the 200 bytes of the state.
+    ///
+    /// See [`State::from_hex`] for the byte order.
+    pub fn to_hex(&self) -> String {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        self.bytes()
+            .iter()
+            .flat_map(|byte| [byte >> 4, byte & 0xf])
+            .map(|nibble| DIGITS[nibble as usize] as char)
+            .collect()
+    }
+}
+
+#[cfg(feature = "internals")]
+fn hex_value(index: usize, c: char) -> Result<u8, FromHexError> {
+    c.to_digit(16)
+        .map(|digit| digit as u8)
+        .ok_or(FromHexError::InvalidCharacter { c, index })
+}
+
+/// Error returned by [`State::from_hex`].
+#[cfg(feature = "internals")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FromHexError {
+    /// A character which is neither a hex digit nor whitespace.
+    InvalidCharacter { c: char, index: usize },
+    /// An odd number of hex digits.
+    OddLength,
+    /// The number of decoded bytes is not 200.
+    InvalidLength(usize),
+}
+
+#[cfg(feature = "internals")]
+impl std::fmt::Display for FromHexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FromHexError::InvalidCharacter { c, index } => {
+                write!(f, "invalid character {c:?} at position {index}")
+            }
+            FromHexError::OddLength => f.write_str("odd number of hex digits"),
+            FromHexError::InvalidLength(len) => {
+                write!(f, "invalid state length {len}, expected 200 bytes")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "internals")]
+impl std::error::Error for FromHexError {}
+
 /// 3.2.1 Algorithm 1: θ(A)
+#[cfg(test)]
 fn theta(A: &mut State) {
     // We have 5 * 64 columns, whose parity bits we can store in 5 lanes
     let mut C: [Lane; 5] = Default::default();
@@ -109,7 +327,7 @@ fn theta(A: &mut State) {
 /// Table 2: Values are modulo the width w = 64
 /// In row-major order starting with x = 0, y = 0
 // TODO: Compute this table with a const function to be closer to spec?
-const KECCAK_RHO_OFFSETS: [u32; 25] = [
+pub(crate) const KECCAK_RHO_OFFSETS: [u32; 25] = [
     0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
 ];
 
@@ -120,6 +338,7 @@ const KECCAK_RHO_OFFSETS: [u32; 25] = [
 /// > offset, which depends on the fixed x and y coordinates of the
 /// > lane. Equivalently, for each bit in the lane, the z coordinate is
 /// > modified by adding the offset, modulo the lane size.
+#[cfg(test)]
 fn rho(A: &mut State) {
     for x in 0..5 {
         for y in 0..5 {
@@ -133,6 +352,7 @@ fn rho(A: &mut State) {
 /// Quote from 3.2.3 (description of π):
 /// > The effect of π is to rearrange the positions of the lanes, as illustrated
 /// > for any slice in Figure 5 below.
+#[cfg(test)]
 fn pi(A: &mut State) {
     let temp_A = *A;
     for x in 0..5 {
@@ -144,11 +364,34 @@ fn pi(A: &mut State) {
     }
 }
 
+/// π(ρ(A)) in place, using a single temporary lane.
+///
+/// π moves lane (x, y) to (y, 2x + 3y). Starting at lane (1, 0), following
+/// these moves visits all lanes except (0, 0), which neither step changes. As
+/// in the [readable and compact] implementation, each lane is rotated and
+/// moved to its destination while the lane it replaces is kept for the next
+/// step.
+///
+/// [readable and compact]: https://github.com/XKCP/XKCP/blob/716f007dd73ef28d357b8162173646be574ad1b7/Standalone/CompactFIPS202/C/Keccak-readable-and-compact.c
+#[cfg(any(test, feature = "shuffling"))]
+fn rho_pi(A: &mut State) {
+    let (mut x, mut y) = (1, 0);
+    let mut current = A[(x, y)];
+    for _ in 0..24 {
+        let offset = KECCAK_RHO_OFFSETS[x + 5 * y];
+        (x, y) = (y, (2 * x + 3 * y) % 5);
+        let next = A[(x, y)];
+        A[(x, y)] = current.rotate_left(offset);
+        current = next;
+    }
+}
+
 /// 3.2.3 Algorithm 4: χ(A)
 ///
 /// Quote from 3.2.4:
 /// > The effect of χ is to XOR each bit with a non-linear function of two other
 /// > bits in its row
+#[cfg(test)]
 fn chi(A: &mut State) {
     let mut C: [Lane; 5] = Default::default();
 
@@ -166,7 +409,7 @@ fn chi(A: &mut State) {
 /// Table taken from:
 /// https://github.com/XKCP/XKCP/blob/716f007dd73ef28d357b8162173646be574ad1b7/lib/low/KeccakP-1600/ref-64bits/KeccakP-1600-reference.c#L109-L135
 // TODO: Compute this table with a const function to be closer to spec?
-const KECCAK_ROUND_CONSTANTS: [Lane; ROUNDS] = [
+pub(crate) const KECCAK_ROUND_CONSTANTS: [Lane; ROUNDS] = [
     0x0000000000000001,
     0x0000000000008082,
     0x800000000000808a,
@@ -199,73 +442,734 @@ const KECCAK_ROUND_CONSTANTS: [Lane; ROUNDS] = [
 /// > The effect of ι is to modify some of the bits of Lane (0, 0) in a manner
 /// > that depends on the round
 /// > index ir. The other 24 lanes are not affected by ι.
+#[cfg(any(test, feature = "internals", feature = "shuffling"))]
 fn iota(A: &mut State, round: usize) {
     A[(0, 0)] ^= KECCAK_ROUND_CONSTANTS[round];
 }
 
+/// Index of the input lane that π moves to each lane of the output.
+///
+/// π moves lane (x', y') to (y', 2x' + 3y'), so lane (x, y) of the output
+/// comes from lane (x + 3y, x) of the input.
+const PI_SOURCE: [usize; 25] = {
+    let mut source = [0; 25];
+    let mut i = 0;
+    while i < 25 {
+        let (x, y) = (i % 5, i / 5);
+        source[i] = (x + 3 * y) % 5 + 5 * x;
+        i += 1;
+    }
+    source
+};
+
+/// Expand `$body` five times, with `$i` bound to the constants 0 to 4.
+///
+/// As the indices are constants, the table lookups of [`round`], e.g. the ρ
+/// offsets and the π rearrangement, are evaluated at compile time instead of
+/// relying on the optimizer to unroll the loops.
+macro_rules! unroll5 {
+    ($i:ident => $body:expr) => {
+        unroll5!(@ $i => $body; 0 1 2 3 4)
+    };
+    (@ $i:ident => $body:expr; $($value:literal)*) => {
+        $({
+            #[allow(non_upper_case_globals)]
+            const $i: usize = $value;
+            $body;
+        })*
+    };
+}
+
+/// Lane (x, y) of the output of χ, given the row `B` of lanes after π.
+#[cfg(not(feature = "lane-complementing"))]
+#[inline(always)]
+fn chi_lane(B: &[Lane; 5], x: usize, _y: usize) -> Lane {
+    B[x] ^ (!B[(x + 1) % 5] & B[(x + 2) % 5])
+}
+
+/// Lanes which are stored complemented during the permutation with the
+/// `lane-complementing` feature.
+///
+/// This is the pattern of Section 2.2 of the [Keccak implementation
+/// overview], which reduces the number of NOT operations in χ from 25 to 8
+/// per round.
+///
+/// [Keccak implementation overview]: https://keccak.team/files/Keccak-implementation-3.2.pdf
+#[cfg(feature = "lane-complementing")]
+const COMPLEMENTED: [bool; 25] = {
+    let mut complemented = [false; 25];
+    // Lanes (1, 0), (2, 0), (3, 1), (2, 2), (2, 3) and (0, 4)
+    let lanes = [1, 2, 8, 12, 17, 20];
+    let mut i = 0;
+    while i < lanes.len() {
+        complemented[lanes[i]] = true;
+        i += 1;
+    }
+    complemented
+};
+
+/// Whether lane (x, y) of the rows `B` after π is complemented, given that
+/// the lanes in [`COMPLEMENTED`] are complemented before θ.
+#[cfg(feature = "lane-complementing")]
+const B_COMPLEMENTED: [bool; 25] = {
+    // A column parity of θ is complemented if an odd number of the lanes of
+    // the column are
+    let mut parity = [false; 5];
+    let mut i = 0;
+    while i < 25 {
+        parity[i % 5] ^= COMPLEMENTED[i];
+        i += 1;
+    }
+    let mut complemented = [false; 25];
+    let mut i = 0;
+    while i < 25 {
+        let source = PI_SOURCE[i];
+        let x = source % 5;
+        complemented[i] = COMPLEMENTED[source] ^ parity[(x + 4) % 5] ^ parity[(x + 1) % 5];
+        i += 1;
+    }
+    complemented
+};
+
+/// Lane (x, y) of the output of χ, given the row `B` of lanes after π.
+///
+/// The lanes of `B` are complemented according to [`B_COMPLEMENTED`] and
+/// the output lane is complemented according to [`COMPLEMENTED`]. The
+/// flags are constants, so the branches are resolved at compile time. `!b1
+/// & b2` is computed from the stored lanes with AND or OR, and only needs a
+/// NOT if both or none of the lanes are complemented.
+#[cfg(feature = "lane-complementing")]
+#[inline(always)]
+fn chi_lane(B: &[Lane; 5], x: usize, y: usize) -> Lane {
+    let complemented = |x: usize| B_COMPLEMENTED[x % 5 + 5 * y];
+    let (b0, b1, b2) = (B[x], B[(x + 1) % 5], B[(x + 2) % 5]);
+    let output_complemented = COMPLEMENTED[x + 5 * y];
+    // Prefer the form that leaves the output complemented as required
+    let prefer_inverted = complemented(x) != output_complemented;
+    // `term` is the AND of the uncomplemented lanes, or its complement if
+    // `inverted`
+    let (term, inverted) = match (complemented(x + 1), complemented(x + 2)) {
+        (true, false) => (b1 & b2, false),
+        (false, true) => (b1 | b2, true),
+        (true, true) if prefer_inverted => (!b1 | b2, true),
+        (true, true) => (b1 & !b2, false),
+        (false, false) if prefer_inverted => (b1 | !b2, true),
+        (false, false) => (!b1 & b2, false),
+    };
+    let lane = b0 ^ term;
+    if complemented(x) ^ inverted != output_complemented {
+        !lane
+    } else {
+        lane
+    }
+}
+
+/// Complement the lanes in [`COMPLEMENTED`].
+#[cfg(feature = "lane-complementing")]
+fn complement_lanes(lanes: &mut [Lane; 25]) {
+    for (lane, &complemented) in lanes.iter_mut().zip(&COMPLEMENTED) {
+        if complemented {
+            *lane = !*lane;
+        }
+    }
+}
+
+/// 3.3 Rnd(A, ir) = ι(χ(π(ρ(θ(A)))), ir) in a single pass over the state.
+///
+/// Instead of sweeping over the whole state once per step, the column
+/// parities of θ are computed first. Then, each row of the output is computed
+/// at once: its five lanes are gathered from the input with the θ effect, the
+/// ρ rotation and the π rearrangement applied, and χ combines them before
+/// they are written. ι is applied to lane (0, 0) at the end.
+///
+/// The round reads the lanes `a` and writes the result to `e`, so that
+/// consecutive rounds can alternate between two buffers without copying.
+#[inline(always)]
+fn round(a: &[Lane; 25], e: &mut [Lane; 25], round: usize) {
+    // θ: column parities and the effect added to each column
+    let C: [Lane; 5] = std::array::from_fn(|x| a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20]);
+    let D: [Lane; 5] = std::array::from_fn(|x| C[(x + 4) % 5] ^ C[(x + 1) % 5].rotate_left(1));
+
+    unroll5!(y => {
+        let mut B: [Lane; 5] = [0; 5];
+        unroll5!(x => {
+            const SOURCE: usize = PI_SOURCE[x + 5 * y];
+            B[x] = (a[SOURCE] ^ D[SOURCE % 5]).rotate_left(KECCAK_RHO_OFFSETS[SOURCE]);
+        });
+        // χ on the row
+        unroll5!(x => e[x + 5 * y] = chi_lane(&B, x, y));
+    });
+    e[0] ^= KECCAK_ROUND_CONSTANTS[round];
+}
+
 /// 3.3 Algorithm 7: KECCAK-p[b, nr](S)
 ///
 /// Not the generic algorithm, but specialized to `b = 1600` and `nr = 24`.
 /// See Section 3.4 of FIPS 202.
-fn keccakf_1600_state_permute(state: &mut State) {
+pub(crate) fn keccakf_1600_state_permute(state: &mut State) {
+    state.lanes_to_le();
+    keccak_p1600_rounds(state, ROUNDS);
+    state.lanes_to_le();
+}
+
+/// [`keccakf_1600_state_permute`] reduced to the last 12 rounds.
+#[cfg(test)]
+pub(crate) fn keccakp_1600_12_state_permute(state: &mut State) {
+    state.lanes_to_le();
+    keccak_p1600_rounds(state, 12);
+    state.lanes_to_le();
+}
+
+/// KECCAK-p\[1600, 12\] permutation with 12 rounds, as used by
+/// KangarooTwelve and TurboSHAKE.
+///
+/// The lanes are native integers, where lane `(x, y)` is at index `x + 5 * y`.
+/// When converting between bytes and lanes, lanes are in little-endian byte
+/// order.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::keccak_p1600_12;
+/// #
+/// // TurboSHAKE128 of the empty message with domain separation byte 0x1F
+/// let mut lanes = [0; 25];
+/// lanes[0] ^= 0x1f;
+/// // Last bit of the padding in the last byte of the 168 byte rate
+/// lanes[20] ^= 0x80 << 56;
+/// keccak_p1600_12(&mut lanes);
+/// assert_eq!(0xf2af83591c5f411e, lanes[0]);
+/// ```
+pub fn keccak_p1600_12(lanes: &mut [u64; 25]) {
+    keccak_p1600(lanes, 12);
+}
+
+/// KECCAK-p\[1600, nr\] permutation with `rounds` rounds.
+///
+/// As specified in Section 3.3 of FIPS 202, the permutation consists of the
+/// last `rounds` rounds of KECCAK-f\[1600\] and thus uses the last `rounds`
+/// round constants. With 24 rounds it is KECCAK-f\[1600\], with 12 rounds
+/// [`keccak_p1600_12`]. The lanes are native integers as for
+/// [`keccak_p1600_12`].
+///
+/// # Panics
+/// If `rounds` is greater than 24.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::keccak_p1600;
+/// #
+/// // Reduced-round experiment with 4 rounds
+/// let mut lanes = [0; 25];
+/// keccak_p1600(&mut lanes, 4);
+/// assert_eq!(0xd7cd974cfd1febe9, lanes[0]);
+/// ```
+pub fn keccak_p1600(lanes: &mut [u64; 25], rounds: usize) {
+    assert!(rounds <= ROUNDS, "KECCAK-p[1600] has at most 24 rounds");
+    let mut state = State(*lanes);
+    keccak_p1600_rounds(&mut state, rounds);
+    *lanes = state.0;
+}
+
+/// State of the KECCAK-f\[1600\] permutation.
+///
+/// The raw permutation for constructions that are not covered by the hash
+/// functions, like duplex objects or Farfalle. The state consists of 25 lanes
+/// of 64 bits, where lane `(x, y)` is at index `x + 5 * y`. The 200 bytes of
+/// the state are the lanes in little-endian byte order.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::KeccakF1600;
+/// #
+/// // Keccak-256 of the empty message
+/// let mut state = KeccakF1600::new();
+/// state.xor_bytes(0, &[0x01]);
+/// state.xor_bytes(135, &[0x80]);
+/// state.permute();
+/// let mut digest = [0; 32];
+/// state.extract_bytes(0, &mut digest);
+/// assert_eq!(sha3::keccak256(b""), digest);
+/// ```
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct KeccakF1600(State);
+
+impl KeccakF1600 {
+    /// Create a state with all bits set to zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a state from its 25 lanes.
+    pub fn from_lanes(lanes: [u64; 25]) -> Self {
+        Self(State(lanes.map(Lane::to_le)))
+    }
+
+    /// Lanes of the state.
+    pub fn to_lanes(&self) -> [u64; 25] {
+        self.0.0.map(Lane::from_le)
+    }
+
+    /// Lane `(x, y)` of the state.
+    ///
+    /// # Panics
+    /// If `x` or `y` is greater than 4.
+    pub fn lane(&self, x: usize, y: usize) -> u64 {
+        assert!(x < 5 && y < 5, "lane coordinates must be less than 5");
+        Lane::from_le(self.0[(x, y)])
+    }
+
+    /// Set lane `(x, y)` of the state to `value`.
+    ///
+    /// # Panics
+    /// If `x` or `y` is greater than 4.
+    pub fn set_lane(&mut self, x: usize, y: usize, value: u64) {
+        assert!(x < 5 && y < 5, "lane coordinates must be less than 5");
+        self.0[(x, y)] = value.to_le();
+    }
+
+    /// Create a state from its 200 bytes.
+    pub fn from_bytes(bytes: &[u8; 200]) -> Self {
+        let mut state = Self::new();
+        state.xor_bytes(0, bytes);
+        state
+    }
+
+    /// The 200 bytes of the state.
+    pub fn to_bytes(&self) -> [u8; 200] {
+        let mut bytes = [0; 200];
+        self.extract_bytes(0, &mut bytes);
+        bytes
+    }
+
+    /// XOR `bytes` onto the state, starting at byte `offset`.
+    ///
+    /// # Panics
+    /// If the bytes do not fit into the state.
+    pub fn xor_bytes(&mut self, offset: usize, bytes: &[u8]) {
+        self.0.with_bytes(|state| {
+            let state = &mut state[offset..offset + bytes.len()];
+            state.iter_mut().zip(bytes).for_each(|(s, b)| *s ^= b);
+        });
+    }
+
+    /// Copy the bytes of the state starting at byte `offset` into `output`.
+    ///
+    /// # Panics
+    /// If the output extends beyond the end of the state.
+    pub fn extract_bytes(&self, offset: usize, output: &mut [u8]) {
+        output.copy_from_slice(&self.0.bytes()[offset..offset + output.len()]);
+    }
+
+    /// Apply KECCAK-f\[1600\] to the state.
+    pub fn permute(&mut self) {
+        keccakf_1600_state_permute(&mut self.0);
+    }
+
+    /// Apply KECCAK-p\[1600, nr\] with the last `rounds` rounds to the state.
+    ///
+    /// # Panics
+    /// If `rounds` is greater than 24.
+    pub fn permute_rounds(&mut self, rounds: usize) {
+        assert!(rounds <= ROUNDS, "KECCAK-p[1600] has at most 24 rounds");
+        self.0.lanes_to_le();
+        keccak_p1600_rounds(&mut self.0, rounds);
+        self.0.lanes_to_le();
+    }
+}
+
+/// 3.3 Algorithm 7 specialized to `b = 1600`, applying the last `rounds`
+/// rounds of KECCAK-f\[1600\]. The lanes must be native integers.
+fn keccak_p1600_rounds(state: &mut State, rounds: usize) {
+    #[cfg(feature = "lane-complementing")]
+    complement_lanes(&mut state.0);
+    let mut first = ROUNDS - rounds;
+    let mut temp = [0; 25];
+    if !rounds.is_multiple_of(2) {
+        round(&state.0, &mut temp, first);
+        state.0 = temp;
+        first += 1;
+    }
+    // Two rounds per iteration, alternating between the state and `temp`
+    for round_index in (first..ROUNDS).step_by(2) {
+        round(&state.0, &mut temp, round_index);
+        round(&temp, &mut state.0, round_index + 1);
+    }
+    #[cfg(feature = "lane-complementing")]
+    complement_lanes(&mut state.0);
+}
+
+/// Inverse of [`theta`].
+///
+/// The θ step adds the effect `D` of the column parities `C` to every lane of
+/// the state. As `D` is added to all five lanes of a column, the column
+/// parities after θ are `C + D`. To invert θ, we first recover the column
+/// parities `C` of the input from those of the output and then subtract `D`
+/// again.
+///
+/// The map `C -> C + D` is invariant under translation along the x and z
+/// axis. Its inverse is therefore determined by the preimage of a single bit
+/// at `x = 0, z = 0`, which is stored in [`KECCAK_THETA_INVERSE`].
+#[cfg(any(test, feature = "internals"))]
+fn theta_inverse(A: &mut State) {
+    // Column parities after θ
+    let mut C: [Lane; 5] = Default::default();
+    for (x, Cx) in C.iter_mut().enumerate() {
+        *Cx = A[(x, 0)] ^ A[(x, 1)] ^ A[(x, 2)] ^ A[(x, 3)] ^ A[(x, 4)];
+    }
+
+    // Column parities before θ: For every bit set in C, add the preimage of
+    // that bit, which is KECCAK_THETA_INVERSE translated to its position.
+    let mut P: [Lane; 5] = Default::default();
+    for (x0, Cx0) in C.into_iter().enumerate() {
+        for z in 0..Lane::BITS {
+            let mask = 0_u64.wrapping_sub((Cx0 >> z) & 1);
+            for (dx, Q) in KECCAK_THETA_INVERSE.iter().enumerate() {
+                P[(x0 + dx) % 5] ^= Q.rotate_left(z) & mask;
+            }
+        }
+    }
+
+    for x in 0..5 {
+        let D = P[(x + 4) % 5] ^ P[(x + 1) % 5].rotate_left(1);
+        for y in 0..5 {
+            A[(x, y)] ^= D;
+        }
+    }
+}
+
+/// Column parities which are mapped by θ to a single parity bit at
+/// `x = 0, z = 0`. Indexed by `x`.
+// Computed by Gaussian elimination over GF(2).
+#[cfg(any(test, feature = "internals"))]
+const KECCAK_THETA_INVERSE: [Lane; 5] = [
+    0xde26bc4d789af135,
+    0x09af135e26bc4d78,
+    0xebc4d789af135e26,
+    0x7135e26bc4d789af,
+    0xcd789af135e26bc4,
+];
+
+/// Inverse of [`rho`].
+#[cfg(any(test, feature = "internals"))]
+fn rho_inverse(A: &mut State) {
+    for x in 0..5 {
+        for y in 0..5 {
+            A[(x, y)] = A[(x, y)].rotate_right(KECCAK_RHO_OFFSETS[x + 5 * y]);
+        }
+    }
+}
+
+/// Inverse of [`pi`].
+#[cfg(any(test, feature = "internals"))]
+fn pi_inverse(A: &mut State) {
+    let temp_A = *A;
+    for x in 0..5 {
+        for y in 0..5 {
+            A[(x, y)] = temp_A[(y, 2 * x + 3 * y)];
+        }
+    }
+}
+
+/// Inverse of [`chi`].
+///
+/// Based on the inverse of χ in the [KeccakTools]. Starting from the output
+/// row, the input bits are recovered one at a time, each time using bits which
+/// have already been recovered.
+///
+/// [KeccakTools]: https://github.com/KeccakTeam/KeccakTools/blob/master/Sources/Keccak-f.h
+#[cfg(any(test, feature = "internals"))]
+fn chi_inverse(A: &mut State) {
+    let mut C: [Lane; 5] = Default::default();
+
+    for y in 0..5 {
+        for (x, Cx) in C.iter_mut().enumerate() {
+            *Cx = A[(x, y)];
+        }
+        for i in 0..6 {
+            let x = 3 * i;
+            A[(x, y)] = C[x % 5] ^ (!A[(x + 1, y)] & A[(x + 2, y)]);
+        }
+    }
+}
+
+/// Inverse of [`keccakf_1600_state_permute`].
+#[cfg(any(test, feature = "internals"))]
+fn keccakf_1600_state_permute_inverse(state: &mut State) {
+    state.lanes_to_le();
+    for round in (0..ROUNDS).rev() {
+        // ι is an involution
+        iota(state, round);
+        chi_inverse(state);
+        pi_inverse(state);
+        rho_inverse(state);
+        theta_inverse(state);
+    }
+
+    state.lanes_to_le();
+}
+
+/// [`keccakf_1600_state_permute`] where the processing order of independent
+/// lane operations in θ and χ is randomized using the `shuffler`.
+#[cfg(feature = "shuffling")]
+pub(crate) fn keccakf_1600_state_permute_shuffled(state: &mut State, shuffler: &mut Shuffler) {
     state.lanes_to_le();
     for round in 0..ROUNDS {
-        theta(state);
-        rho(state);
-        pi(state);
-        chi(state);
+        theta_shuffled(state, shuffler);
+        rho_pi(state);
+        chi_shuffled(state, shuffler);
         iota(state, round);
     }
 
     state.lanes_to_le();
 }
 
-/// 4. and 5. Sponge Construction instantiated with `pad10*1` and
-///    `KECCAK-p[1600, 24]`
-// TODO split this function into init/absorbing/squeezing stages. This would
-//  allow a lower level API  where data can be absorbed multiple times into the
-//  state (`update`)
-// TODO have domain separation suffix as parameter to reuse
-//  keccak for SHAEK XOFs (currently hard-coded for SHA3)
-// TODO Only have capacity as parameter and compute rate to be closer to spec?
-pub(crate) fn keccak(rate: usize, capacity: usize, input: &[u8], output: &mut [u8]) {
-    let mut state = State([0; 25]);
-    let rate_in_bytes = rate / 8;
-
-    debug_assert_eq!(
-        1600,
-        rate + capacity,
-        "rate + capacity must equal 1600 for SHA-3"
-    );
-    debug_assert_eq!(0, rate % 8, "rate must be divisible by 8");
-
-    // Absorb input blocks into state
-    let mut iter = input.chunks_exact(rate_in_bytes);
-    for input_block in iter.by_ref() {
-        xor_bytes(state.bytes_mut(), input_block);
-        keccakf_1600_state_permute(&mut state);
+/// [`theta`] which computes the column parities and adds the θ effect to the
+/// lanes in a random order.
+#[cfg(feature = "shuffling")]
+fn theta_shuffled(A: &mut State, shuffler: &mut Shuffler) {
+    let mut C: [Lane; 5] = Default::default();
+    for x in shuffler.permutation() {
+        let ys = shuffler.permutation();
+        C[x] = A[(x, ys[0])] ^ A[(x, ys[1])] ^ A[(x, ys[2])] ^ A[(x, ys[3])] ^ A[(x, ys[4])];
     }
 
-    xor_bytes(state.bytes_mut(), iter.remainder());
+    for x in shuffler.permutation() {
+        let D = C[(x + 4) % 5] ^ C[(x + 1) % 5].rotate_left(1);
+        for y in shuffler.permutation() {
+            A[(x, y)] ^= D;
+        }
+    }
+}
 
-    let end = iter.remainder().len();
-    // Add domain separator and first 1 bit of padding
-    state.bytes_mut()[end] ^= DELIMETED_SUFFIX;
-    // Add second 1 bit of padding
-    state.bytes_mut()[rate_in_bytes - 1] ^= 0b10000000_u8;
+/// [`chi`] which processes the rows and the lanes within a row in a random
+/// order.
+#[cfg(feature = "shuffling")]
+fn chi_shuffled(A: &mut State, shuffler: &mut Shuffler) {
+    let mut C: [Lane; 5] = Default::default();
 
-    // squeezing phase
-    for output_block in output.chunks_mut(rate_in_bytes) {
-        keccakf_1600_state_permute(&mut state);
-        let block_size = output_block.len();
-        output_block.copy_from_slice(&state.bytes_mut()[..block_size]);
+    for y in shuffler.permutation() {
+        let xs = shuffler.permutation();
+        for x in xs {
+            C[x] = A[(x, y)] ^ (!A[(x + 1, y)] & A[(x + 2, y)]);
+        }
+        for x in xs {
+            A[(x, y)] = C[x];
+        }
     }
 }
 
-fn xor_bytes(dest: &mut [u8], other: &[u8]) {
-    // for_each combinator can lead to better codegen
-    // TODO benchmark this
-    dest.iter_mut().zip(other).for_each(|(state, input)| {
-        *state ^= input;
-    });
+/// Small xorshift PRNG producing the processing orders for the shuffled
+/// permutation.
+///
+/// This is not a cryptographically secure PRNG. Its purpose is to make the
+/// order of operations unpredictable for an observer who does not know the
+/// seed.
+#[cfg(feature = "shuffling")]
+pub(crate) struct Shuffler(u64);
+
+#[cfg(feature = "shuffling")]
+impl Shuffler {
+    pub(crate) fn new(seed: u64) -> Self {
+        // xorshift must not be seeded with 0
+        match seed {
+            0 => Self(0x9e3779b97f4a7c15),
+            seed => Self(seed),
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        // xorshift64* by Sebastiano Vigna
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545f4914f6cdd1d)
+    }
+
+    /// Random permutation of `0..5` using the Fisher–Yates shuffle.
+    fn permutation(&mut self) -> [usize; 5] {
+        let mut perm = [0, 1, 2, 3, 4];
+        for i in (1..5).rev() {
+            // The high bits of xorshift64* have the best quality. The modulo
+            // bias is negligible for i < 5.
+            let j = ((self.next() >> 32) % (i as u64 + 1)) as usize;
+            perm.swap(i, j);
+        }
+        perm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "lane-complementing")]
+    use super::complement_lanes;
+    use super::{
+        KeccakF1600, ROUNDS, State, chi, chi_inverse, iota, keccak_p1600, keccak_p1600_12,
+        keccakf_1600_state_permute, keccakf_1600_state_permute_inverse, pi, pi_inverse, rho,
+        rho_inverse, rho_pi, round, theta, theta_inverse,
+    };
+
+    /// Some states with non-trivial lanes
+    fn states() -> impl Iterator<Item = State> {
+        let mut state = State([0; 25]);
+        state.0[0] = 1;
+        (0..10).map(move |_| {
+            keccakf_1600_state_permute(&mut state);
+            state
+        })
+    }
+
+    #[test]
+    fn permutation_of_zero_state() {
+        // Known answer from the XKCP KeccakF-1600 intermediate values
+        let mut state = State([0; 25]);
+        keccakf_1600_state_permute(&mut state);
+        assert_eq!(0xf1258f7940e1dde7, u64::from_le(state.0[0]));
+        assert_eq!(0x84d5ccf933c0478a, u64::from_le(state.0[1]));
+        assert_eq!(0xeaf1ff7b5ceca249, u64::from_le(state.0[24]));
+    }
+
+    #[test]
+    fn rho_pi_matches_steps() {
+        for state in states() {
+            let mut expected = state;
+            rho(&mut expected);
+            pi(&mut expected);
+            let mut combined = state;
+            rho_pi(&mut combined);
+            assert_eq!(expected, combined);
+        }
+    }
+
+    #[test]
+    fn fused_round_matches_steps() {
+        for (state, round_index) in states().zip((0..ROUNDS).step_by(3)) {
+            let mut expected = state;
+            theta(&mut expected);
+            rho(&mut expected);
+            pi(&mut expected);
+            chi(&mut expected);
+            iota(&mut expected, round_index);
+            #[allow(unused_mut)]
+            let mut input = state;
+            #[cfg(feature = "lane-complementing")]
+            complement_lanes(&mut input.0);
+            let mut fused = State::default();
+            round(&input.0, &mut fused.0, round_index);
+            #[cfg(feature = "lane-complementing")]
+            complement_lanes(&mut fused.0);
+            assert_eq!(expected, fused);
+        }
+    }
+
+    #[test]
+    fn reduced_round_permutation_of_zero_state() {
+        let mut lanes = [0; 25];
+        keccak_p1600_12(&mut lanes);
+        assert_eq!(0x8e5e5438b9a78617, lanes[0]);
+        assert_eq!(0xd9cd6a50f259d01e, lanes[1]);
+        assert_eq!(0xcffd0d76222ca01c, lanes[24]);
+    }
+
+    #[test]
+    fn configurable_round_count() {
+        let mut expected = State([0; 25]);
+        keccakf_1600_state_permute(&mut expected);
+        let mut lanes = [0; 25];
+        keccak_p1600(&mut lanes, 24);
+        assert_eq!(expected.0.map(u64::from_le), lanes);
+
+        let mut lanes = [0; 25];
+        keccak_p1600(&mut lanes, 0);
+        assert_eq!([0; 25], lanes);
+
+        let mut lanes = [0; 25];
+        keccak_p1600(&mut lanes, 4);
+        assert_eq!(0xd7cd974cfd1febe9, lanes[0]);
+        assert_eq!(0x2c2c243f3b6425e0, lanes[1]);
+        assert_eq!(0xa54f8291210c8a03, lanes[24]);
+
+        // A single round uses the last round constant 0x8000000080008008
+        let mut lanes = [0; 25];
+        lanes[0] = 1;
+        keccak_p1600(&mut lanes, 1);
+        assert_eq!(0x8000000080008009, lanes[0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_rounds() {
+        keccak_p1600(&mut [0; 25], 25);
+    }
+
+    #[test]
+    fn public_permutation() {
+        let mut expected = State([0; 25]);
+        expected.0[0] = 1;
+        expected.0[7] = 0x0123456789abcdef_u64.to_le();
+        let mut state = KeccakF1600::new();
+        state.set_lane(0, 0, 1);
+        state.set_lane(2, 1, 0x0123456789abcdef);
+        assert_eq!(0x0123456789abcdef, state.lane(2, 1));
+        assert_eq!(expected.0.map(u64::from_le), state.to_lanes());
+        assert!(KeccakF1600::from_bytes(&state.to_bytes()) == state);
+        assert!(KeccakF1600::from_lanes(state.to_lanes()) == state);
+        assert_eq!(0x01, state.to_bytes()[0]);
+        assert_eq!(0xef, state.to_bytes()[56]);
+
+        keccakf_1600_state_permute(&mut expected);
+        state.permute();
+        assert_eq!(expected.0.map(u64::from_le), state.to_lanes());
+        let mut lanes = state.to_lanes();
+        keccak_p1600(&mut lanes, 5);
+        state.permute_rounds(5);
+        assert_eq!(lanes, state.to_lanes());
+    }
+
+    #[test]
+    fn inverse_steps() {
+        type Step = fn(&mut State);
+        let steps: [(Step, Step); 4] = [
+            (theta, theta_inverse),
+            (rho, rho_inverse),
+            (pi, pi_inverse),
+            (chi, chi_inverse),
+        ];
+        for (step, inverse) in steps {
+            for expected in states() {
+                let mut state = expected;
+                step(&mut state);
+                assert!(state != expected);
+                inverse(&mut state);
+                assert!(state == expected);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_permutation() {
+        for expected in states() {
+            let mut state = expected;
+            keccakf_1600_state_permute(&mut state);
+            keccakf_1600_state_permute_inverse(&mut state);
+            assert!(state == expected);
+            keccakf_1600_state_permute_inverse(&mut state);
+            keccakf_1600_state_permute(&mut state);
+            assert!(state == expected);
+        }
+    }
+
+    #[cfg(feature = "shuffling")]
+    #[test]
+    fn shuffler_permutations() {
+        let mut shuffler = super::Shuffler::new(0);
+        let mut seen = std::collections::HashSet::new();
+        for _ in 0..5000 {
+            let mut perm = shuffler.permutation();
+            seen.insert(perm);
+            perm.sort();
+            assert_eq!([0, 1, 2, 3, 4], perm);
+        }
+        // All 120 permutations of 5 elements should occur
+        assert_eq!(120, seen.len());
+    }
 }
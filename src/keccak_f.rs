@@ -0,0 +1,532 @@
+//! Keccak-f permutations with smaller lanes
+//!
+//! Besides KECCAK-f\[1600\] with 64-bit lanes, the Keccak family defines
+//! permutations of width `b = 25 * w` for lanes of `w` bits. This module
+//! provides a permutation that is generic over the [`Lane`] type and a
+//! [`Sponge`] over it. The 800-bit permutation with 32-bit lanes has a smaller
+//! state, which is useful on 32-bit microcontrollers. The 400-bit and 200-bit
+//! permutations with 16-bit and 8-bit lanes fit on constrained devices and
+//! are small enough to follow by hand.
+//!
+//! KECCAK-f\[b\] has `12 + 2 * l` rounds for `w = 2^l`. The rotation offsets
+//! of ρ and the round constants of ι are derived for each width as specified
+//! in Section 3.2 of [FIPS 202]. The bytes of the state are the lanes in
+//! little-endian byte order.
+//!
+//! These permutations are not used by any of the standardized SHA-3
+//! functions. Their security level is bounded by the smaller capacity.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::keccak_f::{KeccakF800, Sponge800};
+//! #
+//! let mut state = KeccakF800::default();
+//! state.permute();
+//! assert_eq!(0xe531d45d, state.to_lanes()[0]);
+//!
+//! // Sponge with a rate of 544 and a capacity of 256 bits
+//! let mut sponge = Sponge800::new(544, 0x01);
+//! sponge.absorb(b"abc");
+//! let mut output = [0; 32];
+//! sponge.squeeze(&mut output);
+//! ```
+//!
+//! [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+#![allow(non_snake_case)]
+use std::{
+    fmt,
+    ops::{BitAnd, BitXor, BitXorAssign, Not},
+};
+
+/// Lane of a Keccak-f state with `BITS` bits.
+///
+/// This trait is sealed and implemented for `u8`, `u16` and `u32`.
+pub trait Lane:
+    sealed::Sealed
+    + Copy
+    + Default
+    + Eq
+    + fmt::Debug
+    + fmt::LowerHex
+    + BitAnd<Output = Self>
+    + BitXor<Output = Self>
+    + BitXorAssign
+    + Not<Output = Self>
+{
+    /// Width `w` of the lane in bits.
+    const BITS: u32;
+    /// Number of rounds of KECCAK-f with lanes of this width.
+    const ROUNDS: usize;
+}
+
+mod sealed {
+    pub trait Sealed: Sized + 'static {
+        /// Round constants for all rounds of KECCAK-f.
+        const ROUND_CONSTANTS: &'static [Self];
+        /// Offsets of ρ, where lane `(x, y)` is at index `x + 5 * y`.
+        const RHO_OFFSETS: [u32; 25];
+
+        fn rotate(self, n: u32) -> Self;
+        /// Byte `i` of the lane in little-endian order.
+        fn byte(self, i: usize) -> u8;
+        /// Lane with the byte `b` at little-endian position `i`.
+        fn from_byte(b: u8, i: usize) -> Self;
+    }
+}
+
+macro_rules! impl_lane {
+    ($lane:ty, $rounds:expr) => {
+        impl Lane for $lane {
+            const BITS: u32 = <$lane>::BITS;
+            const ROUNDS: usize = $rounds;
+        }
+
+        impl sealed::Sealed for $lane {
+            const ROUND_CONSTANTS: &'static [Self] = &{
+                let mut constants = [0; $rounds];
+                let mut round = 0;
+                while round < $rounds {
+                    constants[round] = round_constant(round) as $lane;
+                    round += 1;
+                }
+                constants
+            };
+            const RHO_OFFSETS: [u32; 25] = rho_offsets(<$lane>::BITS);
+
+            #[inline(always)]
+            fn rotate(self, n: u32) -> Self {
+                self.rotate_left(n)
+            }
+
+            #[inline(always)]
+            fn byte(self, i: usize) -> u8 {
+                (self >> (8 * i)) as u8
+            }
+
+            #[inline(always)]
+            fn from_byte(b: u8, i: usize) -> Self {
+                <$lane>::from(b) << (8 * i)
+            }
+        }
+    };
+}
+
+impl_lane!(u8, 18);
+impl_lane!(u16, 20);
+impl_lane!(u32, 22);
+
+/// 3.2.2 Algorithm 2: Offsets of ρ for lanes of `w` bits.
+const fn rho_offsets(w: u32) -> [u32; 25] {
+    let mut offsets = [0; 25];
+    let (mut x, mut y) = (1, 0);
+    let mut t = 0;
+    while t < 24 {
+        offsets[x + 5 * y] = ((t + 1) * (t + 2) / 2) % w;
+        (x, y) = (y, (2 * x + 3 * y) % 5);
+        t += 1;
+    }
+    offsets
+}
+
+/// 3.2.5 Algorithm 5: rc(t), the output of an LFSR with the feedback
+/// polynomial x^8 + x^6 + x^5 + x^4 + 1.
+const fn rc(t: usize) -> u64 {
+    // Bit i of R is R[i] in the spec
+    let mut R: u16 = 1;
+    let mut i = 0;
+    while i < t % 255 {
+        R <<= 1;
+        if R & 0x100 != 0 {
+            R ^= 0x171;
+        }
+        i += 1;
+    }
+    (R & 1) as u64
+}
+
+/// 3.2.5 Algorithm 6: RC for round `ir` with 64-bit lanes. The constants for
+/// `w`-bit lanes are the `w` least significant bits.
+const fn round_constant(ir: usize) -> u64 {
+    let mut RC = 0;
+    let mut j = 0;
+    while j <= 6 {
+        RC |= rc(j + 7 * ir) << ((1 << j) - 1);
+        j += 1;
+    }
+    RC
+}
+
+/// State of KECCAK-f\[25 * w\] with lanes of type `L`.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeccakState<L: Lane>([L; 25]);
+
+/// State of KECCAK-f\[200\] with 8-bit lanes.
+pub type KeccakF200 = KeccakState<u8>;
+/// State of KECCAK-f\[400\] with 16-bit lanes.
+pub type KeccakF400 = KeccakState<u16>;
+/// State of KECCAK-f\[800\] with 32-bit lanes.
+pub type KeccakF800 = KeccakState<u32>;
+
+/// Prints the lanes as a matrix of rows `y` with the `debug-state` feature and
+/// redacts them otherwise, as the state may contain secrets.
+impl<L: Lane> fmt::Debug for KeccakState<L> {
+    #[cfg(feature = "debug-state")]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Row<L>([L; 5]);
+
+        impl<L: Lane> fmt::Debug for Row<L> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let width = L::BITS as usize / 4;
+                f.write_str("[")?;
+                for (x, lane) in self.0.iter().enumerate() {
+                    if x > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{lane:0width$x}")?;
+                }
+                f.write_str("]")
+            }
+        }
+
+        f.debug_list()
+            .entries((0..5).map(|y| Row(std::array::from_fn(|x| self.0[x + 5 * y]))))
+            .finish()
+    }
+
+    #[cfg(not(feature = "debug-state"))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+impl<L: Lane> KeccakState<L> {
+    /// Width `b` of the permutation in bits.
+    pub const WIDTH: usize = 25 * L::BITS as usize;
+    /// Size of the state in bytes.
+    pub const BYTES: usize = Self::WIDTH / 8;
+
+    /// Create a state from its 25 lanes, where lane `(x, y)` is at index
+    /// `x + 5 * y`.
+    pub fn from_lanes(lanes: [L; 25]) -> Self {
+        Self(lanes)
+    }
+
+    /// Lanes of the state, where lane `(x, y)` is at index `x + 5 * y`.
+    pub fn to_lanes(&self) -> [L; 25] {
+        self.0
+    }
+
+    /// Apply the KECCAK-f permutation to the state.
+    pub fn permute(&mut self) {
+        for round in 0..L::ROUNDS {
+            self.round(round);
+        }
+    }
+
+    fn round(&mut self, round: usize) {
+        let A = &mut self.0;
+        // θ
+        let mut C = [L::default(); 5];
+        for (x, Cx) in C.iter_mut().enumerate() {
+            *Cx = A[x] ^ A[x + 5] ^ A[x + 10] ^ A[x + 15] ^ A[x + 20];
+        }
+        for x in 0..5 {
+            let D = C[(x + 4) % 5] ^ C[(x + 1) % 5].rotate(1);
+            for y in 0..5 {
+                A[x + 5 * y] ^= D;
+            }
+        }
+        // ρ and π
+        let mut B = [L::default(); 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                B[y + 5 * ((2 * x + 3 * y) % 5)] = A[x + 5 * y].rotate(L::RHO_OFFSETS[x + 5 * y]);
+            }
+        }
+        // χ
+        for x in 0..5 {
+            for y in 0..5 {
+                A[x + 5 * y] = B[x + 5 * y] ^ (!B[(x + 1) % 5 + 5 * y] & B[(x + 2) % 5 + 5 * y]);
+            }
+        }
+        // ι
+        A[0] ^= L::ROUND_CONSTANTS[round];
+    }
+
+    fn byte(&self, i: usize) -> u8 {
+        let lane_bytes = L::BITS as usize / 8;
+        self.0[i / lane_bytes].byte(i % lane_bytes)
+    }
+
+    fn xor_byte(&mut self, i: usize, b: u8) {
+        let lane_bytes = L::BITS as usize / 8;
+        self.0[i / lane_bytes] ^= L::from_byte(b, i % lane_bytes);
+    }
+}
+
+/// Sponge over KECCAK-f with lanes of type `L`.
+///
+/// The sponge absorbs until the first call to [`Sponge::squeeze`], which
+/// appends the delimited suffix and the `pad10*1` padding.
+#[derive(Clone, Debug)]
+pub struct Sponge<L: Lane> {
+    state: KeccakState<L>,
+    /// Rate in bytes
+    rate: usize,
+    pos: usize,
+    suffix: u8,
+    squeezing: bool,
+}
+
+/// Sponge over KECCAK-f\[200\].
+pub type Sponge200 = Sponge<u8>;
+/// Sponge over KECCAK-f\[400\].
+pub type Sponge400 = Sponge<u16>;
+/// Sponge over KECCAK-f\[800\].
+pub type Sponge800 = Sponge<u32>;
+
+impl<L: Lane> Sponge<L> {
+    /// Create a sponge with a rate of `rate` bits, which appends the
+    /// delimited `suffix` to the input, e.g. `0x01` for no suffix as in the
+    /// original Keccak padding.
+    ///
+    /// # Panics
+    /// If the rate is not a multiple of 8 or not less than the width of the
+    /// permutation, or if the suffix is 0.
+    pub fn new(rate: usize, suffix: u8) -> Self {
+        assert!(
+            rate.is_multiple_of(8) && rate > 0 && rate < KeccakState::<L>::WIDTH,
+            "rate must be a positive multiple of 8 less than the width"
+        );
+        assert_ne!(suffix, 0, "suffix must be delimited");
+        Self {
+            state: KeccakState::default(),
+            rate: rate / 8,
+            pos: 0,
+            suffix,
+            squeezing: false,
+        }
+    }
+
+    /// Rate of the sponge in bytes.
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    /// Absorb `input` into the sponge.
+    ///
+    /// # Panics
+    /// If the sponge is already squeezing.
+    pub fn absorb(&mut self, input: &[u8]) {
+        assert!(!self.squeezing, "cannot absorb after squeezing");
+        for &b in input {
+            self.state.xor_byte(self.pos, b);
+            self.pos += 1;
+            if self.pos == self.rate {
+                self.state.permute();
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Fill `output` with the next bytes of the sponge output.
+    pub fn squeeze(&mut self, output: &mut [u8]) {
+        if !self.squeezing {
+            self.pad();
+        }
+        for b in output {
+            if self.pos == self.rate {
+                self.state.permute();
+                self.pos = 0;
+            }
+            *b = self.state.byte(self.pos);
+            self.pos += 1;
+        }
+    }
+
+    fn pad(&mut self) {
+        self.state.xor_byte(self.pos, self.suffix);
+        // If the first 1 bit of padding is the last bit of the block, the
+        // second 1 bit is in the next block
+        if self.pos == self.rate - 1 && self.suffix & 0x80 != 0 {
+            self.state.permute();
+        }
+        self.state.xor_byte(self.rate - 1, 0x80);
+        self.state.permute();
+        self.pos = 0;
+        self.squeezing = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        KeccakF200, KeccakF400, KeccakF800, Lane, Sponge200, Sponge400, Sponge800, rho_offsets,
+        round_constant, sealed::Sealed,
+    };
+    use crate::keccak::{KECCAK_RHO_OFFSETS, KECCAK_ROUND_CONSTANTS};
+
+    #[test]
+    fn derived_tables_match_keccak_f1600() {
+        assert_eq!(KECCAK_RHO_OFFSETS, rho_offsets(64));
+        for (ir, constant) in KECCAK_ROUND_CONSTANTS.into_iter().enumerate() {
+            assert_eq!(constant, round_constant(ir));
+        }
+        assert_eq!(0x8a, <u8 as Sealed>::ROUND_CONSTANTS[2]);
+        assert_eq!(
+            <u16 as Lane>::ROUNDS,
+            <u16 as Sealed>::ROUND_CONSTANTS.len()
+        );
+        assert_eq!(14 % 8, <u8 as Sealed>::RHO_OFFSETS[24]);
+    }
+
+    // Permutations of the zero state from KeccakF-{800,400,200}-
+    // IntermediateValues.txt of XKCP, which lists the states as bytes in
+    // little-endian lane order
+
+    #[test]
+    fn keccak_f800_of_zero_state() {
+        let mut state = KeccakF800::default();
+        state.permute();
+        assert_eq!(
+            [
+                0xe531d45d, 0xf404c6fb, 0x23a0bf99, 0xf1f8452f, 0x51ffd042, 0xe539f578, 0xf00b80a7,
+                0xaf973664, 0xbf5af34c, 0x227a2424, 0x88172715, 0x9f685884, 0xb15cd054, 0x1bf4fc0e,
+                0x6166fa91, 0x1a9e599a, 0xa3970a1f, 0xab659687, 0xafab8d68, 0xe74b1015, 0x34001a98,
+                0x4119eff3, 0x930a0e76, 0x87b28070, 0x11efe996,
+            ],
+            state.to_lanes()
+        );
+    }
+
+    #[test]
+    fn sponge800() {
+        let mut sponge = Sponge800::new(544, 0x01);
+        sponge.absorb(b"abc");
+        let mut output = [0; 100];
+        sponge.squeeze(&mut output);
+        assert_eq!(
+            "9d734efa7587904dd24091dddabb5cc4b2f599e8c1bc73143c93be9b37e0c929\
+             f88bdd7a174345eab4212e7c56a88f1c2327060dabbf897951702c6cb3e9564b\
+             eec6b87e8cea072d9845e26fec1c2ed46cef779cc132600a5b19bae72d2903b8\
+             35164250",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn sponge800_in_pieces() {
+        let input: Vec<u8> = (0..200).collect();
+        let mut sponge = Sponge800::new(544, 0b110);
+        for piece in input.chunks(7) {
+            sponge.absorb(piece);
+        }
+        let mut output = [0; 40];
+        for piece in output.chunks_mut(3) {
+            sponge.squeeze(piece);
+        }
+        assert_eq!(
+            "42ae2fb6fdd8b04b49c8d1734a453597aa5cfe8ad78b1a4a73cd881b52b263a0\
+             720041f6b90530ca",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn padding_in_next_block() {
+        // The suffix fills the last byte of the block, so the final 1 bit of
+        // the padding is in the next block
+        let input = [0x5a; 67];
+        let mut sponge = Sponge800::new(544, 0xff);
+        sponge.absorb(&input);
+        let mut output = [0; 8];
+        sponge.squeeze(&mut output);
+
+        let mut state = KeccakF800::default();
+        for (i, &b) in input.iter().enumerate() {
+            state.xor_byte(i, b);
+        }
+        state.xor_byte(67, 0xff);
+        state.permute();
+        state.xor_byte(67, 0x80);
+        state.permute();
+        let expected: Vec<u8> = (0..8).map(|i| state.byte(i)).collect();
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn keccak_f400_and_f200_of_zero_state() {
+        let mut state = KeccakF400::default();
+        state.permute();
+        assert_eq!(
+            [
+                0x09f5, 0x40ac, 0x0fa9, 0x14f5, 0xe89f, 0xeca0, 0x5bd1, 0x7870, 0xeff0, 0xbf8f,
+                0x0337, 0x6052, 0xdc75, 0x0ec9, 0xe776, 0x5246, 0x59a1, 0x5d81, 0x6d95, 0x6e14,
+                0x633e, 0x58ee, 0x71ff, 0x714c, 0xb38e,
+            ],
+            state.to_lanes()
+        );
+
+        let mut state = KeccakF200::default();
+        state.permute();
+        assert_eq!(
+            [
+                0x3c, 0x28, 0x26, 0x84, 0x1c, 0xb3, 0x5c, 0x17, 0x1e, 0xaa, 0xe9, 0xb8, 0x11, 0x13,
+                0x4c, 0xea, 0xa3, 0x85, 0x2c, 0x69, 0xd2, 0xc5, 0xab, 0xaf, 0xea,
+            ],
+            state.to_lanes()
+        );
+    }
+
+    #[test]
+    fn sponge400_and_sponge200() {
+        let mut sponge = Sponge400::new(144, 0x01);
+        sponge.absorb(b"abc");
+        let mut output = [0; 50];
+        sponge.squeeze(&mut output);
+        assert_eq!(
+            "414dc05cd4505a4f218461c58c62577099c79be4ef3d5be68e9b420e4f92cf33\
+             8addfd882cc826e27d6d75cd12f89b9349dd",
+            hex::encode(output)
+        );
+
+        let input: Vec<u8> = (0..100).collect();
+        let mut sponge = Sponge400::new(144, 0b110);
+        sponge.absorb(&input);
+        let mut output = [0; 30];
+        sponge.squeeze(&mut output);
+        assert_eq!(
+            "42b6834c4c9be6d67b3f8cd4f890ee8a471bb5efee85dffeaa10cb546966",
+            hex::encode(output)
+        );
+
+        let mut sponge = Sponge200::new(40, 0x01);
+        sponge.absorb(b"abc");
+        let mut output = [0; 20];
+        sponge.squeeze(&mut output);
+        assert_eq!(
+            "37fbb0a32b4a316cd6fe54a181e5fa76a593dfbf",
+            hex::encode(output)
+        );
+    }
+
+    #[cfg(not(feature = "debug-state"))]
+    #[test]
+    fn debug_redacts_state() {
+        let mut sponge = Sponge800::new(256, 0x01);
+        sponge.absorb(b"secret");
+        assert_eq!(
+            "Sponge { state: <redacted>, rate: 32, pos: 6, suffix: 1, squeezing: false }",
+            format!("{sponge:?}")
+        );
+    }
+
+    #[cfg(feature = "debug-state")]
+    #[test]
+    fn debug_prints_lanes() {
+        let mut lanes = [0; 25];
+        lanes[1] = 0xab;
+        let state = KeccakF200::from_lanes(lanes);
+        assert!(format!("{state:?}").starts_with("[[00, ab, 00, 00, 00], [00,"));
+    }
+}
@@ -0,0 +1,357 @@
+//! KECCAK Message Authentication Code (KMAC)
+//!
+//! KMAC and its extendable-output variant KMACXOF are specified in Section 4 of
+//! [NIST SP 800-185]. Both are built on cSHAKE with the function name
+//! `"KMAC"`. The key and the optional customization string are absorbed
+//! before the message. KMAC encodes the requested output length at the end of
+//! the input, so outputs of different lengths are unrelated. KMACXOF encodes
+//! an output length of 0 and returns an [`XofReader`] instead.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::kmac::{Kmac256, KmacXof256};
+//! #
+//! let key = b"a secret key of at least 32 bytes";
+//! let mut kmac = Kmac256::new(key, b"my application");
+//! kmac.update(b"your input bytes");
+//! let mut tag = [0; 64];
+//! kmac.finalize(&mut tag);
+//!
+//! let mut kmac_xof = KmacXof256::new(key, b"my application");
+//! kmac_xof.update(b"your input bytes");
+//! let mut reader = kmac_xof.finalize_xof();
+//! let mut output = [0; 64];
+//! reader.squeeze(&mut output);
+//! // Unlike KMAC, the output of KMACXOF does not depend on its length
+//! assert_ne!(tag, output);
+//! ```
+//!
+//! [NIST SP 800-185]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+
+use crate::{
+    XofReader,
+    sponge::{AbsorbState, CSHAKE_DELIMETED_SUFFIX, SqueezeState},
+};
+
+/// KMAC128 with a variable output length.
+#[derive(Clone, Debug)]
+pub struct Kmac128 {
+    core: KmacCore,
+}
+
+/// KMAC256 with a variable output length.
+#[derive(Clone, Debug)]
+pub struct Kmac256 {
+    core: KmacCore,
+}
+
+/// KMACXOF128 extendable-output MAC.
+#[derive(Clone, Debug)]
+pub struct KmacXof128 {
+    core: KmacCore,
+}
+
+/// KMACXOF256 extendable-output MAC.
+#[derive(Clone, Debug)]
+pub struct KmacXof256 {
+    core: KmacCore,
+}
+
+impl Kmac128 {
+    /// Create a new KMAC128 instance with the `key` and the `customization`
+    /// string, which may be empty.
+    pub fn new(key: &[u8], customization: &[u8]) -> Self {
+        Self {
+            core: KmacCore::new(128, key, customization),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.sponge.absorb(data);
+    }
+
+    /// Fill `output` with the MAC. The length of `output` is part of the MAC
+    /// computation.
+    pub fn finalize(self, output: &mut [u8]) {
+        self.core.finish(output.len()).squeeze(output);
+    }
+}
+
+impl Kmac256 {
+    /// Create a new KMAC256 instance with the `key` and the `customization`
+    /// string, which may be empty.
+    pub fn new(key: &[u8], customization: &[u8]) -> Self {
+        Self {
+            core: KmacCore::new(256, key, customization),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.sponge.absorb(data);
+    }
+
+    /// Fill `output` with the MAC. The length of `output` is part of the MAC
+    /// computation.
+    pub fn finalize(self, output: &mut [u8]) {
+        self.core.finish(output.len()).squeeze(output);
+    }
+}
+
+impl KmacXof128 {
+    /// Create a new KMACXOF128 instance with the `key` and the
+    /// `customization` string, which may be empty.
+    pub fn new(key: &[u8], customization: &[u8]) -> Self {
+        Self {
+            core: KmacCore::new(128, key, customization),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.sponge.absorb(data);
+    }
+
+    /// Finish absorbing and return an [`XofReader`] for the output.
+    pub fn finalize_xof(self) -> XofReader {
+        XofReader::new(self.core.finish(0))
+    }
+
+    /// Finish absorbing and return the first `len` bytes of the output.
+    pub fn finalize_vec(self, len: usize) -> Vec<u8> {
+        let mut output = vec![0; len];
+        self.finalize_xof().squeeze(&mut output);
+        output
+    }
+}
+
+impl KmacXof256 {
+    /// Create a new KMACXOF256 instance with the `key` and the
+    /// `customization` string, which may be empty.
+    pub fn new(key: &[u8], customization: &[u8]) -> Self {
+        Self {
+            core: KmacCore::new(256, key, customization),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.core.sponge.absorb(data);
+    }
+
+    /// Finish absorbing and return an [`XofReader`] for the output.
+    pub fn finalize_xof(self) -> XofReader {
+        XofReader::new(self.core.finish(0))
+    }
+
+    /// Finish absorbing and return the first `len` bytes of the output.
+    pub fn finalize_vec(self, len: usize) -> Vec<u8> {
+        let mut output = vec![0; len];
+        self.finalize_xof().squeeze(&mut output);
+        output
+    }
+}
+
+/// cSHAKE sponge with the KMAC prefix absorbed.
+#[derive(Clone, Debug)]
+struct KmacCore {
+    sponge: AbsorbState,
+}
+
+impl KmacCore {
+    /// KMAC function name used for cSHAKE
+    const FUNCTION_NAME: &[u8] = b"KMAC";
+
+    fn new(security: usize, key: &[u8], customization: &[u8]) -> Self {
+        let capacity = 2 * security;
+        let rate = 1600 - capacity;
+        let mut sponge = AbsorbState::new(rate, capacity, CSHAKE_DELIMETED_SUFFIX);
+        // cSHAKE prefix: bytepad(encode_string(N) || encode_string(S), rate)
+        absorb_bytepad(&mut sponge, &[Self::FUNCTION_NAME, customization], rate / 8);
+        // KMAC key: bytepad(encode_string(K), rate)
+        absorb_bytepad(&mut sponge, &[key], rate / 8);
+        Self { sponge }
+    }
+
+    /// Absorb `right_encode(L)` for an output of `output_len` bytes and switch
+    /// to the squeezing phase.
+    fn finish(mut self, output_len: usize) -> SqueezeState {
+        let output_bits = (output_len as u64)
+            .checked_mul(8)
+            .expect("output length in bits overflows u64");
+        let (buf, len) = right_encode(output_bits);
+        self.sponge.absorb(&buf[..len]);
+        self.sponge.into_squeeze()
+    }
+}
+
+/// Absorb `bytepad(encode_string(s_1) || ... || encode_string(s_n), w)`.
+fn absorb_bytepad(sponge: &mut AbsorbState, strings: &[&[u8]], w: usize) {
+    let mut absorbed = 0;
+    let mut absorb = |data: &[u8]| {
+        sponge.absorb(data);
+        absorbed += data.len();
+    };
+    let (buf, len) = left_encode(w as u64);
+    absorb(&buf[..len]);
+    for s in strings {
+        // encode_string(S) = left_encode(len(S)) || S
+        let (buf, len) = left_encode(s.len() as u64 * 8);
+        absorb(&buf[..len]);
+        absorb(s);
+    }
+    // The padding is shorter than the rate, and thus than the state
+    const ZEROS: [u8; 200] = [0; 200];
+    let padding = (w - absorbed % w) % w;
+    sponge.absorb(&ZEROS[..padding]);
+}
+
+/// `left_encode(x)` of Section 2.3.1 of SP 800-185. Returns a buffer and the
+/// length of the encoding.
+fn left_encode(x: u64) -> ([u8; 9], usize) {
+    let n = (x.max(1).ilog2() / 8 + 1) as usize;
+    let mut buf = [0; 9];
+    buf[0] = n as u8;
+    buf[1..=n].copy_from_slice(&x.to_be_bytes()[8 - n..]);
+    (buf, n + 1)
+}
+
+/// `right_encode(x)` of Section 2.3.1 of SP 800-185. Returns a buffer and the
+/// length of the encoding.
+fn right_encode(x: u64) -> ([u8; 9], usize) {
+    let n = (x.max(1).ilog2() / 8 + 1) as usize;
+    let mut buf = [0; 9];
+    buf[..n].copy_from_slice(&x.to_be_bytes()[8 - n..]);
+    buf[n] = n as u8;
+    (buf, n + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Kmac128, Kmac256, KmacXof128, KmacXof256, left_encode, right_encode};
+
+    fn key() -> Vec<u8> {
+        (0x40..0x60).collect()
+    }
+
+    #[test]
+    fn encodings() {
+        let encode = |(buf, len): ([u8; 9], usize)| buf[..len].to_vec();
+        assert_eq!(vec![1, 0], encode(left_encode(0)));
+        assert_eq!(vec![0, 1], encode(right_encode(0)));
+        assert_eq!(vec![1, 168], encode(left_encode(168)));
+        assert_eq!(vec![2, 1, 0], encode(left_encode(256)));
+        assert_eq!(vec![1, 0, 2], encode(right_encode(256)));
+        assert_eq!(
+            vec![8, 255, 255, 255, 255, 255, 255, 255, 255, 8],
+            [encode(left_encode(u64::MAX)), vec![8]].concat()
+        );
+    }
+
+    // Samples from https://csrc.nist.gov/projects/cryptographic-standards-and-guidelines/example-values
+
+    #[test]
+    fn kmac128_samples() {
+        let mut kmac = Kmac128::new(&key(), b"");
+        kmac.update(&[0, 1, 2, 3]);
+        let mut output = [0; 32];
+        kmac.finalize(&mut output);
+        assert_eq!(
+            "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e",
+            hex::encode(output)
+        );
+
+        let mut kmac = Kmac128::new(&key(), b"My Tagged Application");
+        kmac.update(&[0, 1, 2, 3]);
+        kmac.finalize(&mut output);
+        assert_eq!(
+            "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn kmac256_sample() {
+        let mut kmac = Kmac256::new(&key(), b"My Tagged Application");
+        kmac.update(&[0, 1, 2, 3]);
+        let mut output = [0; 64];
+        kmac.finalize(&mut output);
+        assert_eq!(
+            "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7\
+             f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn clone_continues_independently() {
+        let mut kmac = Kmac256::new(&key(), b"");
+        kmac.update(b"common prefix");
+        let clone = kmac.clone();
+        kmac.update(b" and more");
+        kmac.finalize(&mut [0; 32]);
+        let mut expected = Kmac256::new(&key(), b"");
+        expected.update(b"common prefix");
+        let (mut output, mut expected_output) = ([0; 32], [0; 32]);
+        clone.finalize(&mut output);
+        expected.finalize(&mut expected_output);
+        assert_eq!(expected_output, output);
+    }
+
+    #[cfg(not(feature = "debug-state"))]
+    #[test]
+    fn debug_redacts_state() {
+        let kmac = Kmac128::new(&key(), b"");
+        assert!(format!("{kmac:?}").contains("state: <redacted>"));
+    }
+
+    #[test]
+    fn kmacxof128_samples() {
+        let mut kmac = KmacXof128::new(&key(), b"");
+        kmac.update(&[0, 1, 2, 3]);
+        let mut output = [0; 32];
+        kmac.finalize_xof().squeeze(&mut output);
+        assert_eq!(
+            "cd83740bbd92ccc8cf032b1481a0f4460e7ca9dd12b08a0c4031178bacd6ec35",
+            hex::encode(output)
+        );
+
+        let message: Vec<u8> = (0..200).collect();
+        let mut kmac = KmacXof128::new(&key(), b"My Tagged Application");
+        kmac.update(&message);
+        assert_eq!(
+            "47026c7cd793084aa0283c253ef658490c0db61438b8326fe9bddf281b83ae0f",
+            hex::encode(kmac.finalize_vec(32))
+        );
+    }
+
+    #[test]
+    fn kmacxof256_samples() {
+        let message: Vec<u8> = (0..200).collect();
+        let mut output = [0; 64];
+        let mut kmac = KmacXof256::new(&key(), b"");
+        kmac.update(&message);
+        kmac.finalize_xof().squeeze(&mut output);
+        assert_eq!(
+            "ff7b171f1e8a2b24683eed37830ee797538ba8dc563f6da1e667391a75edc02c\
+             a633079f81ce12a25f45615ec89972031d18337331d24ceb8f8ca8e6a19fd98b",
+            hex::encode(output)
+        );
+
+        let mut kmac = KmacXof256::new(&key(), b"My Tagged Application");
+        // Absorbing in pieces does not change the output
+        for piece in message.chunks(7) {
+            kmac.update(piece);
+        }
+        let mut reader = kmac.finalize_xof();
+        for piece in output.chunks_mut(5) {
+            reader.squeeze(piece);
+        }
+        assert_eq!(
+            "d5be731c954ed7732846bb59dbe3a8e30f83e77a4bff4459f2f1c2b4ecebb8ce\
+             67ba01c62e8ab8578d2d499bd1bb276768781190020a306a97de281dcc30305d",
+            hex::encode(output)
+        );
+    }
+}
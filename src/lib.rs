@@ -1,7 +1,11 @@
 //! SHA-3 Hash Functions
 //!
 //! This crate provides portable, pure Rust implementations of the SHA-3 hashing
-//! functions standardized in [FIPS 202].
+//! functions and the SHAKE extendable-output functions standardized in
+//! [FIPS 202], as well as KMAC and KMACXOF from [NIST SP 800-185] in the
+//! [`kmac`] module. For legacy systems like Ethereum, [`keccak256`] and
+//! [`keccak512`] provide the hash functions with the padding of the original
+//! Keccak submission.
 //!
 //! # Limitations
 //!
@@ -10,12 +14,10 @@
 //! Performance has thus far not been a priority. This implementation is likely
 //! orders of magnitude slower than optimized ones.
 //!
-//! We currently only expose functions to hash a complete byte slice `&[u8]`.
-//! Individual bits or multiple inputs that update the hash are currently not
-//! supported.
-//!
-//! We currently do not implement the SHAKE extendable-output functions
-//! described in [FIPS 202].
+//! Inputs can be hashed as a complete byte slice `&[u8]` or incrementally with
+//! a [`Hasher`]. The [`generic`] module provides variants of the one-shot
+//! functions that accept strings, vectors and arrays as well. Messages whose
+//! length is not a multiple of 8 bits are supported by [`Hasher::update_bits`].
 //!
 //! # Example Usage
 //! ```
@@ -27,54 +29,321 @@
 //! assert_eq!(expected, hex::encode(hash))
 //! ```
 //!
+//! # Heap Allocations
+//!
+//! Hashing does not allocate. This is guaranteed for the one-shot functions
+//! of the crate root that return arrays or write into a buffer, i.e., not
+//! those returning a `String` or `Vec`, and for [`Hasher`], [`Shake128`],
+//! [`Shake256`] and [`XofReader`] except for their methods returning a
+//! `String` or `Vec`. The guarantee is checked by a test with a counting
+//! allocator.
+//!
 //! [FIPS 202]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.202.pdf
+//! [NIST SP 800-185]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-185.pdf
+#![cfg_attr(not(any(feature = "mmap", feature = "ffi")), forbid(unsafe_code))]
 
+pub mod algorithm;
+pub mod commitment;
+mod ct;
+pub mod digest;
+pub mod digestible;
+pub mod duplex;
+mod encoding;
+pub mod expand_message;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod generic;
+mod hasher;
+pub mod hmac;
+#[cfg(feature = "internals")]
+pub mod internals;
+pub mod io;
 mod keccak;
+pub mod keccak_f;
+pub mod kmac;
+pub mod mgf;
+pub mod ml_dsa;
+pub mod ml_kem;
+pub mod mmr;
+pub mod params;
+pub mod prefix_mac;
+pub mod prng;
+#[cfg(feature = "rand_core")]
+pub mod rng;
+pub mod sakura;
+pub mod sampling;
+#[cfg(feature = "shuffling")]
+pub mod shuffling;
+pub mod sponge;
+pub mod spongewrap;
+pub mod token;
+pub mod transcript;
+pub mod transparency_log;
+mod xof;
+pub mod yielding;
 
-use crate::keccak::keccak;
+use crate::sponge::{KECCAK_DELIMETED_SUFFIX, SHA3_DELIMETED_SUFFIX, keccak};
+pub use crate::{
+    hasher::{
+        EXPORTED_STATE_LEN, Hasher, ImportStateError, OutputLengthError, Sha3_224, Sha3_256,
+        Sha3_384, Sha3_512,
+    },
+    keccak::{KeccakF1600, keccak_p1600, keccak_p1600_12},
+    xof::{Keccak, Shake128, Shake256, XofReader, shake128, shake256},
+};
 
-// TODO: remove code duplication. Use a macro?
+/// SHA-3 Hash with `BITS` bits (`N = BITS / 8` bytes) output.
+///
+/// `BITS` must be one of 224, 256, 384 or 512 and `N` the corresponding
+/// number of bytes. Both are checked at compile time. `N` is a separate
+/// parameter, as the output length cannot be computed from `BITS` in a
+/// function signature on stable Rust, but it can be inferred from the type of
+/// the result.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::{sha3, sha3_384};
+/// #
+/// let digest: [u8; 48] = sha3::<384, _>(b"your input bytes");
+/// assert_eq!(sha3_384(b"your input bytes"), digest);
+/// ```
+///
+/// Unsupported output sizes fail to compile.
+/// ```compile_fail
+/// let digest: [u8; 16] = sha3::sha3::<128, _>(b"your input bytes");
+/// ```
+#[inline]
+pub fn sha3<const BITS: usize, const N: usize>(message: &[u8]) -> [u8; N] {
+    const { assert!(N * 8 == BITS, "output must have BITS / 8 bytes") };
+    sha3_truncated::<BITS, N>(message)
+}
 
 /// SHA-3 Hash with 224 bits (28 bytes) output.
+#[inline]
 pub fn sha3_224(message: &[u8]) -> [u8; 28] {
-    let mut output = [0; 28];
-    const CAPACITY: usize = 224 * 2;
-    const RATE: usize = 1600 - CAPACITY;
-    keccak(RATE, CAPACITY, message, &mut output);
-    output
+    sha3::<224, 28>(message)
 }
 
 /// SHA-3 Hash with 256 bits (32 bytes) output.
+#[inline]
 pub fn sha3_256(message: &[u8]) -> [u8; 32] {
-    let mut output = [0; 32];
-    const CAPACITY: usize = 256 * 2;
-    const RATE: usize = 1600 - CAPACITY;
-    keccak(RATE, CAPACITY, message, &mut output);
-    output
+    sha3::<256, 32>(message)
 }
 
 /// SHA-3 Hash with 384 bits (48 bytes) output.
+#[inline]
 pub fn sha3_384(message: &[u8]) -> [u8; 48] {
-    let mut output = [0; 48];
-    const CAPACITY: usize = 384 * 2;
-    const RATE: usize = 1600 - CAPACITY;
-    keccak(RATE, CAPACITY, message, &mut output);
-    output
+    sha3::<384, 48>(message)
 }
 
 /// SHA-3 Hash with 512 bits (64 bytes) output.
+#[inline]
 pub fn sha3_512(message: &[u8]) -> [u8; 64] {
-    let mut output = [0; 64];
+    sha3::<512, 64>(message)
+}
+
+/// SHA-3 Hash with 224 bits (28 bytes) output written into `output`.
+///
+/// # Errors
+/// If `output` is not exactly 28 bytes long.
+pub fn sha3_224_into(message: &[u8], output: &mut [u8]) -> Result<(), OutputLengthError> {
+    copy_digest(&sha3_224(message), output)
+}
+
+/// SHA-3 Hash with 256 bits (32 bytes) output written into `output`.
+///
+/// # Errors
+/// If `output` is not exactly 32 bytes long.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::{sha3_256, sha3_256_into};
+/// #
+/// let mut output = vec![0; 32];
+/// sha3_256_into(b"your input bytes", &mut output).unwrap();
+/// assert_eq!(sha3_256(b"your input bytes")[..], output);
+/// assert!(sha3_256_into(b"your input bytes", &mut output[..31]).is_err());
+/// ```
+pub fn sha3_256_into(message: &[u8], output: &mut [u8]) -> Result<(), OutputLengthError> {
+    copy_digest(&sha3_256(message), output)
+}
+
+/// SHA-3 Hash with 384 bits (48 bytes) output written into `output`.
+///
+/// # Errors
+/// If `output` is not exactly 48 bytes long.
+pub fn sha3_384_into(message: &[u8], output: &mut [u8]) -> Result<(), OutputLengthError> {
+    copy_digest(&sha3_384(message), output)
+}
+
+/// SHA-3 Hash with 512 bits (64 bytes) output written into `output`.
+///
+/// # Errors
+/// If `output` is not exactly 64 bytes long.
+pub fn sha3_512_into(message: &[u8], output: &mut [u8]) -> Result<(), OutputLengthError> {
+    copy_digest(&sha3_512(message), output)
+}
+
+fn copy_digest(digest: &[u8], output: &mut [u8]) -> Result<(), OutputLengthError> {
+    if digest.len() != output.len() {
+        return Err(OutputLengthError {
+            expected: digest.len(),
+            actual: output.len(),
+        });
+    }
+    output.copy_from_slice(digest);
+    Ok(())
+}
+
+/// Lower case hex encoding of the SHA-3 Hash with 224 bits (28 bytes) output.
+pub fn sha3_224_hex(message: &[u8]) -> String {
+    encoding::hex_encode(&sha3_224(message))
+}
+
+/// Lower case hex encoding of the SHA-3 Hash with 256 bits (32 bytes) output.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::sha3_256_hex;
+/// #
+/// let expected = "414d4b6d11a92aaeeebe35f9374942f563848d345631bf5537407252dca6b378";
+/// assert_eq!(expected, sha3_256_hex(b"your input bytes"));
+/// ```
+pub fn sha3_256_hex(message: &[u8]) -> String {
+    encoding::hex_encode(&sha3_256(message))
+}
+
+/// Lower case hex encoding of the SHA-3 Hash with 384 bits (48 bytes) output.
+pub fn sha3_384_hex(message: &[u8]) -> String {
+    encoding::hex_encode(&sha3_384(message))
+}
+
+/// Lower case hex encoding of the SHA-3 Hash with 512 bits (64 bytes) output.
+pub fn sha3_512_hex(message: &[u8]) -> String {
+    encoding::hex_encode(&sha3_512(message))
+}
+
+/// SHA-3 Hash with 256 bits (32 bytes) output of each message in `messages`.
+///
+/// Currently, the messages are hashed one after another. The batch API allows
+/// future versions to hash several messages in parallel without changes to
+/// the callers.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::{sha3_256, sha3_256_batch};
+/// #
+/// let digests = sha3_256_batch(&[b"first", b"second"]);
+/// assert_eq!(vec![sha3_256(b"first"), sha3_256(b"second")], digests);
+/// ```
+pub fn sha3_256_batch(messages: &[&[u8]]) -> Vec<[u8; 32]> {
+    let mut digests = vec![[0; 32]; messages.len()];
+    sha3_256_batch_into(messages, &mut digests);
+    digests
+}
+
+/// SHA-3 Hash with 256 bits (32 bytes) output of each message in `messages`
+/// into the corresponding entry of `digests`.
+///
+/// Allocation-free variant of [`sha3_256_batch`].
+///
+/// # Panics
+/// If `messages` and `digests` differ in length.
+pub fn sha3_256_batch_into(messages: &[&[u8]], digests: &mut [[u8; 32]]) {
+    assert_eq!(
+        messages.len(),
+        digests.len(),
+        "number of messages and digests must be equal"
+    );
+    for (message, digest) in messages.iter().zip(digests) {
+        *digest = sha3_256(message);
+    }
+}
+
+/// SHA-3 Hash with 224 bits output truncated to the first `N` bytes.
+#[inline]
+pub fn sha3_224_truncated<const N: usize>(message: &[u8]) -> [u8; N] {
+    sha3_truncated::<224, N>(message)
+}
+
+/// SHA-3 Hash with 256 bits output truncated to the first `N` bytes.
+#[inline]
+pub fn sha3_256_truncated<const N: usize>(message: &[u8]) -> [u8; N] {
+    sha3_truncated::<256, N>(message)
+}
+
+/// SHA-3 Hash with 384 bits output truncated to the first `N` bytes.
+#[inline]
+pub fn sha3_384_truncated<const N: usize>(message: &[u8]) -> [u8; N] {
+    sha3_truncated::<384, N>(message)
+}
+
+/// SHA-3 Hash with 512 bits output truncated to the first `N` bytes.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::{sha3_512, sha3_512_truncated};
+/// #
+/// // SHA3-512 truncated to 256 bits
+/// let digest: [u8; 32] = sha3_512_truncated(b"your input bytes");
+/// assert_eq!(sha3_512(b"your input bytes")[..32], digest);
+/// ```
+///
+/// Truncating to more bytes than the digest has fails to compile.
+/// ```compile_fail
+/// let digest: [u8; 65] = sha3::sha3_512_truncated(b"your input bytes");
+/// ```
+#[inline]
+pub fn sha3_512_truncated<const N: usize>(message: &[u8]) -> [u8; N] {
+    sha3_truncated::<512, N>(message)
+}
+
+/// SHA-3 Hash with `BITS` bits output truncated to the first `N` bytes.
+#[inline]
+fn sha3_truncated<const BITS: usize, const N: usize>(message: &[u8]) -> [u8; N] {
+    const {
+        assert!(
+            matches!(BITS, 224 | 256 | 384 | 512),
+            "BITS must be 224, 256, 384 or 512"
+        );
+        assert!(N * 8 <= BITS, "cannot truncate to more than BITS / 8 bytes");
+    };
+    let capacity = 2 * BITS;
+    keccak(1600 - capacity, capacity, SHA3_DELIMETED_SUFFIX, message)
+}
+
+/// Legacy Keccak-256 hash with 256 bits (32 bytes) output.
+///
+/// Uses the padding of the original Keccak submission instead of the SHA-3
+/// domain separation, as done by Ethereum. The output differs from
+/// [`sha3_256`].
+#[inline]
+pub fn keccak256(message: &[u8]) -> [u8; 32] {
+    const CAPACITY: usize = 256 * 2;
+    const RATE: usize = 1600 - CAPACITY;
+    keccak(RATE, CAPACITY, KECCAK_DELIMETED_SUFFIX, message)
+}
+
+/// Legacy Keccak-512 hash with 512 bits (64 bytes) output.
+///
+/// Uses the padding of the original Keccak submission instead of the SHA-3
+/// domain separation. The output differs from [`sha3_512`].
+#[inline]
+pub fn keccak512(message: &[u8]) -> [u8; 64] {
     const CAPACITY: usize = 512 * 2;
     const RATE: usize = 1600 - CAPACITY;
-    keccak(RATE, CAPACITY, message, &mut output);
-    output
+    keccak(RATE, CAPACITY, KECCAK_DELIMETED_SUFFIX, message)
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::sha3_256;
+    use crate::{
+        OutputLengthError, Sha3_256, keccak256, keccak512, sha3, sha3_224, sha3_224_hex,
+        sha3_224_into, sha3_224_truncated, sha3_256, sha3_256_batch, sha3_256_batch_into,
+        sha3_256_hex, sha3_256_into, sha3_256_truncated, sha3_384, sha3_384_hex, sha3_384_into,
+        sha3_384_truncated, sha3_512, sha3_512_hex, sha3_512_into, sha3_512_truncated,
+    };
 
     #[test]
     fn can_hash() {
@@ -82,6 +351,112 @@ mod tests {
         sha3_256(&input[..]);
     }
 
+    #[test]
+    fn legacy_keccak() {
+        assert_eq!(
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470",
+            hex::encode(keccak256(b""))
+        );
+        assert_eq!(
+            "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15",
+            hex::encode(keccak256(b"The quick brown fox jumps over the lazy dog"))
+        );
+        assert_eq!(
+            "0eab42de4c3ceb9235fc91acffe746b29c29a8c366b7c60e4e67c466f36a4304\
+             c00fa9caf9d87976ba469bcbe06713b435f091ef2769fb160cdab33d3670680e",
+            hex::encode(keccak512(b""))
+        );
+        // Beyond one block
+        let input: Vec<u8> = (0..=255).cycle().take(768).collect();
+        assert_eq!(
+            "6ae4a0ce7aac0a366dd40e1c8c26acb9465dde90b702b33b6fbb6e05d0a3bfa5\
+             12b1638b0263bb99658ed7519558893ca7e17049caa3f4fe3a15993b6a56c819",
+            hex::encode(keccak512(&input))
+        );
+    }
+
+    #[test]
+    fn const_generic_output_size() {
+        // SHA3-256 example of the NIST Cryptographic Standards and Guidelines
+        assert_eq!(
+            "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532",
+            hex::encode(sha3::<256, 32>(b"abc"))
+        );
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        assert_eq!(sha3_224(&input), sha3::<224, 28>(&input));
+        assert_eq!(sha3_384(&input), sha3::<384, 48>(&input));
+        assert_eq!(sha3_512(&input), sha3::<512, 64>(&input));
+    }
+
+    #[test]
+    fn into_caller_buffer() {
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        let mut output = [0; 65];
+        sha3_224_into(&input, &mut output[..28]).unwrap();
+        assert_eq!(sha3_224(&input), output[..28]);
+        sha3_256_into(&input, &mut output[..32]).unwrap();
+        assert_eq!(sha3_256(&input), output[..32]);
+        sha3_384_into(&input, &mut output[..48]).unwrap();
+        assert_eq!(sha3_384(&input), output[..48]);
+        sha3_512_into(&input, &mut output[..64]).unwrap();
+        assert_eq!(sha3_512(&input), output[..64]);
+
+        assert_eq!(
+            Err(OutputLengthError {
+                expected: 64,
+                actual: 65
+            }),
+            sha3_512_into(&input, &mut output)
+        );
+    }
+
+    #[test]
+    fn hex_helpers() {
+        // Expected values from FIPS 202 examples
+        assert_eq!(
+            "6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7",
+            sha3_224_hex(b"")
+        );
+        assert_eq!(
+            "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a",
+            sha3_256_hex(b"")
+        );
+        assert_eq!(hex::encode(sha3_384(b"abc")), sha3_384_hex(b"abc"));
+        assert_eq!(hex::encode(sha3_512(b"abc")), sha3_512_hex(b"abc"));
+
+        let mut hasher = Sha3_256::new();
+        hasher.update(b"abc");
+        assert_eq!(hex::encode(sha3_256(b"abc")), hasher.finalize_hex());
+    }
+
+    #[test]
+    fn batch() {
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        let messages: Vec<&[u8]> = (0..input.len()).step_by(7).map(|i| &input[..i]).collect();
+        let digests = sha3_256_batch(&messages);
+        assert_eq!(messages.len(), digests.len());
+        for (message, digest) in messages.iter().zip(&digests) {
+            assert_eq!(sha3_256(message), *digest);
+        }
+        assert!(sha3_256_batch(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic = "number of messages and digests must be equal"]
+    fn batch_into_checks_length() {
+        sha3_256_batch_into(&[b"message"], &mut [[0; 32]; 2]);
+    }
+
+    #[test]
+    fn truncated_digests_are_prefixes() {
+        let input: Vec<u8> = (0..=255).cycle().take(300).collect();
+        assert_eq!(sha3_224(&input)[..16], sha3_224_truncated::<16>(&input));
+        assert_eq!(sha3_256(&input)[..20], sha3_256_truncated::<20>(&input));
+        assert_eq!(sha3_384(&input), sha3_384_truncated::<48>(&input));
+        assert_eq!(sha3_512(&input)[..32], sha3_512_truncated::<32>(&input));
+        assert_eq!([0; 0], sha3_512_truncated::<0>(&input));
+    }
+
     #[cfg(not(miri))]
     #[test]
     fn compare_to_libcrux() {
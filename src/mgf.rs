@@ -0,0 +1,84 @@
+//! Mask generation functions
+//!
+//! A mask generation function (MGF) expands a seed into a mask of arbitrary
+//! length, as used by the OAEP and PSS paddings of RSA. [`mgf1`] is the
+//! counter-based MGF1 of [RFC 8017] over one of the SHA-3 hash functions.
+//! As the SHAKE XOFs already produce outputs of arbitrary length,
+//! [`mgf_shake128`] and [`mgf_shake256`] use their output directly, as in
+//! [RFC 8702].
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::mgf::{mgf1, mgf_shake256};
+//! #
+//! let mut mask = [0; 100];
+//! mgf1::<32>(b"seed", &mut mask);
+//! mgf_shake256(b"seed", &mut mask);
+//! ```
+//!
+//! [RFC 8017]: https://www.rfc-editor.org/rfc/rfc8017.html#appendix-B.2.1
+//! [RFC 8702]: https://www.rfc-editor.org/rfc/rfc8702.html
+
+use crate::{hasher::Hasher, shake128, shake256};
+
+/// MGF1 with the SHA-3 hash function with `N` bytes output, filling all of
+/// `mask`.
+///
+/// Block `i` of the mask is the hash of the seed followed by the counter `i`
+/// as a 4 byte big-endian integer.
+///
+/// # Panics
+/// If `mask` is longer than 2^32 digests.
+pub fn mgf1<const N: usize>(seed: &[u8], mask: &mut [u8]) {
+    for (counter, block) in mask.chunks_mut(N).enumerate() {
+        let counter = u32::try_from(counter).expect("mask is too long for MGF1");
+        let mut hasher = Hasher::<N>::new();
+        hasher.update(seed);
+        hasher.update(&counter.to_be_bytes());
+        block.copy_from_slice(&hasher.finalize()[..block.len()]);
+    }
+}
+
+/// MGF based on SHAKE128, filling all of `mask` with the output of
+/// SHAKE128 for the `seed`.
+pub fn mgf_shake128(seed: &[u8], mask: &mut [u8]) {
+    shake128(seed, mask);
+}
+
+/// MGF based on SHAKE256, filling all of `mask` with the output of
+/// SHAKE256 for the `seed`.
+pub fn mgf_shake256(seed: &[u8], mask: &mut [u8]) {
+    shake256(seed, mask);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mgf1;
+
+    #[test]
+    fn mgf1_known_answers() {
+        let mut mask = [0; 100];
+        mgf1::<32>(b"seed", &mut mask);
+        assert_eq!(
+            "7fe2e122219eb3f5a8b31f3bab2fa73c7fe7cf915da2b28692a7630a7dd9cd99\
+             0100e302da9014519654dc979034a12ea9e08423477d1b3cddb0fe840d3c62ae\
+             9f545298a0f8ed341dd47c2fa1c9edf8516a0cf1cc66b67c7ce42916f72bd436\
+             40a46dd9",
+            hex::encode(mask)
+        );
+        let mut mask = [0; 70];
+        mgf1::<64>(b"seed", &mut mask);
+        assert_eq!(
+            "768acd7f40471d71383e542aaca733abb1fd3607bc13c32e80dff34236a9cfda\
+             453d62372ea7bdffe006b1fdec30f44a577e8406fd69e72831119015d227f862\
+             ba23e462cd75",
+            hex::encode(mask)
+        );
+        let mut mask = [0; 28];
+        mgf1::<28>(b"", &mut mask);
+        assert_eq!(
+            "a0aa4d5d4dc4d205d0d9ef3a1e0f39a41eafced528c273d1dcac8618",
+            hex::encode(mask)
+        );
+    }
+}
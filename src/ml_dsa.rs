@@ -0,0 +1,91 @@
+//! Symmetric primitives of ML-DSA
+//!
+//! ML-DSA uses SHAKE256 as `H` and SHAKE128 as `G`, as specified in Section
+//! 3.7 of [FIPS 204]. The incremental interfaces `H.Init`, `H.Absorb` and
+//! `H.Squeeze` correspond to [`Shake256::new`], [`Shake256::update`] and the
+//! [`XofReader`](crate::XofReader) returned by [`Shake256::finalize_xof`].
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::ml_dsa;
+//! #
+//! let xi = [0x42; 32];
+//! let (k, l) = (4, 4);
+//! let mut seeds = [0; 128];
+//! ml_dsa::h(&[&xi[..], &[k, l]].concat(), &mut seeds);
+//! let (rho, rest) = seeds.split_at(32);
+//!
+//! let mut g = ml_dsa::G::new();
+//! g.update(rho);
+//! g.update(&[0, 0]);
+//! let mut reader = g.finalize_xof();
+//! let mut bytes = [0; 3];
+//! reader.squeeze(&mut bytes);
+//! ```
+//!
+//! [FIPS 204]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.204.pdf
+
+use crate::{Shake128, Shake256, shake128, shake256};
+
+/// Incremental `H`, i.e. SHAKE256.
+pub type H = Shake256;
+/// Incremental `G`, i.e. SHAKE128.
+pub type G = Shake128;
+
+/// H(str, ℓ) = SHAKE256(str, 8ℓ), where ℓ is the length of `output`.
+pub fn h(input: &[u8], output: &mut [u8]) {
+    shake256(input, output);
+}
+
+/// G(str, ℓ) = SHAKE128(str, 8ℓ), where ℓ is the length of `output`.
+pub fn g(input: &[u8], output: &mut [u8]) {
+    shake128(input, output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{G, H, g, h};
+
+    #[test]
+    fn known_answers() {
+        let mut output = [0; 32];
+        h(b"", &mut output);
+        assert_eq!(
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f",
+            hex::encode(output)
+        );
+        g(b"", &mut output);
+        assert_eq!(
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let mut h_state = H::new();
+        h_state.update(&[0x42; 32]);
+        h_state.update(&[4, 4]);
+        let mut output = [0; 32];
+        h_state.finalize_xof().squeeze(&mut output);
+        assert_eq!(
+            "dfab4158c8952a54f8bd019ae3ccba701bd8f0baf78e308d71c2b6a7f95a7066",
+            hex::encode(output)
+        );
+
+        let mut g_state = G::new();
+        g_state.update(&[0x42; 32]);
+        g_state.update(&[0, 0]);
+        let mut reader = g_state.finalize_xof();
+        for piece in output.chunks_mut(3) {
+            reader.squeeze(piece);
+        }
+        assert_eq!(
+            "74fed3a5fa5b6159c0a8952c1ec2032f55b685d826eba2d6afddc7a97bd49346",
+            hex::encode(output)
+        );
+        let mut one_shot = [0; 32];
+        g(&[[0x42; 32].as_slice(), &[0, 0]].concat(), &mut one_shot);
+        assert_eq!(one_shot, output);
+    }
+}
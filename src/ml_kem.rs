@@ -0,0 +1,104 @@
+//! Symmetric primitives of ML-KEM
+//!
+//! The hash functions, the pseudorandom function and the XOF used by ML-KEM,
+//! named as in Section 4.1 of [FIPS 203].
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::ml_kem;
+//! #
+//! let d = [0x42; 32];
+//! let k = 3;
+//! let (rho, sigma) = ml_kem::g(&[&d[..], &[k]].concat());
+//!
+//! // Expand the matrix entry A[i, j] from the seed rho
+//! let (i, j) = (0, 1);
+//! let mut reader = ml_kem::xof(&[&rho[..], &[j, i]].concat());
+//! let mut bytes = [0; 3];
+//! reader.squeeze(&mut bytes);
+//!
+//! // Sample a noise polynomial with eta = 2
+//! let noise: [u8; 128] = ml_kem::prf(&sigma, 0);
+//! ```
+//!
+//! [FIPS 203]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+
+use crate::{Shake128, XofReader, sha3_256, sha3_512, shake256};
+
+/// H(s) = SHA3-256(s)
+pub fn h(s: &[u8]) -> [u8; 32] {
+    sha3_256(s)
+}
+
+/// G(c) = SHA3-512(c), split into two outputs of 32 bytes.
+pub fn g(c: &[u8]) -> ([u8; 32], [u8; 32]) {
+    let digest = sha3_512(c);
+    let (a, b) = digest.split_at(32);
+    (
+        a.try_into().expect("digest has 64 bytes"),
+        b.try_into().expect("digest has 64 bytes"),
+    )
+}
+
+/// J(s) = SHAKE256(s, 8 * 32)
+pub fn j(s: &[u8]) -> [u8; 32] {
+    let mut output = [0; 32];
+    shake256(s, &mut output);
+    output
+}
+
+/// PRF_η(s, b) = SHAKE256(s || b, 8 * 64 * η)
+///
+/// The output length `LEN` must be `64 * η` bytes, i.e. 128 for η = 2 and 192
+/// for η = 3.
+pub fn prf<const LEN: usize>(s: &[u8; 32], b: u8) -> [u8; LEN] {
+    const { assert!(LEN == 128 || LEN == 192, "LEN must be 128 or 192") };
+    let mut input = [0; 33];
+    input[..32].copy_from_slice(s);
+    input[32] = b;
+    let mut output = [0; LEN];
+    shake256(&input, &mut output);
+    output
+}
+
+/// XOF.Init followed by XOF.Absorb(`input`), which returns the reader for
+/// XOF.Squeeze.
+///
+/// For SampleNTT, the input is `ρ || j || i`.
+pub fn xof(input: &[u8]) -> XofReader {
+    let mut shake = Shake128::new();
+    shake.update(input);
+    shake.finalize_xof()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{g, h, j, prf, xof};
+    use crate::{sha3_256, sha3_512, shake128, shake256};
+
+    #[test]
+    fn match_hash_functions() {
+        let input = [0x5a; 33];
+        assert_eq!(sha3_256(&input), h(&input));
+        let (a, b) = g(&input);
+        assert_eq!(sha3_512(&input), [a, b].concat().as_slice());
+
+        let mut expected = [0; 32];
+        shake256(&input, &mut expected);
+        assert_eq!(expected, j(&input));
+
+        let mut expected = [0; 192];
+        shake256(&input, &mut expected);
+        let s = input[..32].try_into().unwrap();
+        assert_eq!(expected, prf::<192>(s, input[32]));
+
+        let mut expected = [0; 504];
+        shake128(&input, &mut expected);
+        let mut output = [0; 504];
+        let mut reader = xof(&input);
+        for piece in output.chunks_mut(3) {
+            reader.squeeze(piece);
+        }
+        assert_eq!(expected, output);
+    }
+}
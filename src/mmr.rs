@@ -0,0 +1,278 @@
+//! Merkle Mountain Range over SHA3-256
+//!
+//! A Merkle Mountain Range (MMR) is an append-only authenticated data
+//! structure. It consists of a list of perfect binary Merkle trees (the
+//! *peaks*) with strictly decreasing heights, one for every set bit in the
+//! number of leaves. Appending a leaf merges peaks of equal height, similar to
+//! incrementing a binary counter. The peaks are combined ("bagged") into a
+//! single root hash.
+//!
+//! Leaves, inner nodes and the bagging of peaks are domain separated by a one
+//! byte prefix before hashing with [`sha3_256`].
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::mmr::MerkleMountainRange;
+//! #
+//! let mut mmr = MerkleMountainRange::new();
+//! for entry in [&b"first"[..], b"second", b"third"] {
+//!     mmr.push(entry);
+//! }
+//! let root = mmr.root().expect("mmr is not empty");
+//! let proof = mmr.prove(1).expect("leaf 1 exists");
+//! assert!(proof.verify(&root, b"second"));
+//! assert!(!proof.verify(&root, b"third"));
+//! ```
+
+use crate::sha3_256;
+
+/// SHA3-256 hash of a leaf, node or root.
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const BAG_PREFIX: u8 = 0x02;
+
+/// Append-only Merkle Mountain Range accumulator.
+#[derive(Debug, Clone, Default)]
+pub struct MerkleMountainRange {
+    /// `levels[h]` contains the roots of all perfect subtrees of height `h`
+    /// in left to right order. `levels[0]` contains the leaf hashes.
+    levels: Vec<Vec<Hash>>,
+}
+
+/// Proof that a leaf is included in a [`MerkleMountainRange`] with a given
+/// root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    /// Index of the proven leaf.
+    pub leaf_index: u64,
+    /// Number of leaves in the MMR the proof was created for.
+    pub leaf_count: u64,
+    /// Sibling hashes on the path from the leaf to its peak, starting at the
+    /// leaf.
+    pub siblings: Vec<Hash>,
+    /// All peaks of the MMR except the one containing the leaf, from left to
+    /// right.
+    pub peaks: Vec<Hash>,
+}
+
+impl MerkleMountainRange {
+    /// Create an empty MMR.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a leaf and return its index.
+    pub fn push(&mut self, data: &[u8]) -> u64 {
+        let index = self.leaf_count();
+        let mut hash = hash_leaf(data);
+        let mut height = 0;
+        loop {
+            if self.levels.len() == height {
+                self.levels.push(vec![]);
+            }
+            let level = &mut self.levels[height];
+            level.push(hash);
+            // A new perfect subtree is completed if the level now contains an
+            // even number of nodes.
+            if level.len() % 2 == 1 {
+                break;
+            }
+            let right = level[level.len() - 1];
+            let left = level[level.len() - 2];
+            hash = hash_node(&left, &right);
+            height += 1;
+        }
+        index
+    }
+
+    /// Number of leaves appended so far.
+    pub fn leaf_count(&self) -> u64 {
+        self.levels.first().map_or(0, |leaves| leaves.len() as u64)
+    }
+
+    /// Returns `true` if no leaves have been appended.
+    pub fn is_empty(&self) -> bool {
+        self.leaf_count() == 0
+    }
+
+    /// Roots of the perfect subtrees, from the highest (leftmost) to the
+    /// lowest (rightmost).
+    pub fn peaks(&self) -> Vec<Hash> {
+        self.levels
+            .iter()
+            .rev()
+            .filter(|level| level.len() % 2 == 1)
+            .map(|level| level[level.len() - 1])
+            .collect()
+    }
+
+    /// Bagged root of all peaks or `None` if the MMR is empty.
+    pub fn root(&self) -> Option<Hash> {
+        bag_peaks(&self.peaks())
+    }
+
+    /// Create an [`InclusionProof`] for the leaf at `leaf_index` or `None` if
+    /// there is no such leaf.
+    pub fn prove(&self, leaf_index: u64) -> Option<InclusionProof> {
+        let leaf_count = self.leaf_count();
+        let (peak_pos, height) = locate_peak(leaf_index, leaf_count)?;
+        let index = leaf_index as usize;
+        let siblings = (0..height)
+            .map(|h| self.levels[h][(index >> h) ^ 1])
+            .collect();
+        let mut peaks = self.peaks();
+        peaks.remove(peak_pos);
+        Some(InclusionProof {
+            leaf_index,
+            leaf_count,
+            siblings,
+            peaks,
+        })
+    }
+}
+
+impl InclusionProof {
+    /// Verify that `data` is the leaf at [`Self::leaf_index`] in an MMR with
+    /// the given `root`.
+    pub fn verify(&self, root: &Hash, data: &[u8]) -> bool {
+        let Some((peak_pos, height)) = locate_peak(self.leaf_index, self.leaf_count) else {
+            return false;
+        };
+        if self.siblings.len() != height
+            || self.peaks.len() + 1 != self.leaf_count.count_ones() as usize
+        {
+            return false;
+        }
+        let mut hash = hash_leaf(data);
+        for (h, sibling) in self.siblings.iter().enumerate() {
+            hash = if (self.leaf_index >> h) & 1 == 0 {
+                hash_node(&hash, sibling)
+            } else {
+                hash_node(sibling, &hash)
+            };
+        }
+        let mut peaks = self.peaks.clone();
+        peaks.insert(peak_pos, hash);
+        bag_peaks(&peaks).as_ref() == Some(root)
+    }
+}
+
+/// Returns the position of the peak containing `leaf_index` in the list of
+/// peaks and the height of that peak.
+fn locate_peak(leaf_index: u64, leaf_count: u64) -> Option<(usize, usize)> {
+    if leaf_index >= leaf_count {
+        return None;
+    }
+    let mut start = 0;
+    // Peaks correspond to the set bits of leaf_count, highest first
+    for (pos, height) in (0..u64::BITS)
+        .rev()
+        .filter(|h| leaf_count >> h & 1 == 1)
+        .enumerate()
+    {
+        let size = 1 << height;
+        if leaf_index < start + size {
+            return Some((pos, height as usize));
+        }
+        start += size;
+    }
+    unreachable!("leaf_index < leaf_count is covered by a peak")
+}
+
+fn hash_leaf(data: &[u8]) -> Hash {
+    let mut input = Vec::with_capacity(1 + data.len());
+    input.push(LEAF_PREFIX);
+    input.extend_from_slice(data);
+    sha3_256(&input)
+}
+
+fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    hash_pair(NODE_PREFIX, left, right)
+}
+
+fn hash_pair(prefix: u8, left: &Hash, right: &Hash) -> Hash {
+    let mut input = [0; 65];
+    input[0] = prefix;
+    input[1..33].copy_from_slice(left);
+    input[33..].copy_from_slice(right);
+    sha3_256(&input)
+}
+
+/// Combine the peaks from right to left into a single root.
+fn bag_peaks(peaks: &[Hash]) -> Option<Hash> {
+    let (last, rest) = peaks.split_last()?;
+    Some(
+        rest.iter()
+            .rev()
+            .fold(*last, |acc, peak| hash_pair(BAG_PREFIX, peak, &acc)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MerkleMountainRange, hash_leaf, hash_node};
+
+    fn leaf(i: u64) -> [u8; 8] {
+        i.to_le_bytes()
+    }
+
+    #[test]
+    fn peaks_follow_leaf_count() {
+        let mut mmr = MerkleMountainRange::new();
+        assert!(mmr.root().is_none());
+        for i in 0..40 {
+            assert_eq!(i, mmr.push(&leaf(i)));
+            assert_eq!(mmr.leaf_count().count_ones() as usize, mmr.peaks().len());
+        }
+    }
+
+    #[test]
+    fn four_leaves_form_single_tree() {
+        let mut mmr = MerkleMountainRange::new();
+        for i in 0..4 {
+            mmr.push(&leaf(i));
+        }
+        let left = hash_node(&hash_leaf(&leaf(0)), &hash_leaf(&leaf(1)));
+        let right = hash_node(&hash_leaf(&leaf(2)), &hash_leaf(&leaf(3)));
+        assert_eq!(Some(hash_node(&left, &right)), mmr.root());
+    }
+
+    #[test]
+    fn proofs_verify() {
+        let mut mmr = MerkleMountainRange::new();
+        for n in 0..33 {
+            mmr.push(&leaf(n));
+            let root = mmr.root().unwrap();
+            for i in 0..=n {
+                let proof = mmr.prove(i).unwrap();
+                assert!(proof.verify(&root, &leaf(i)), "leaf {i} of {}", n + 1);
+                assert!(!proof.verify(&root, &leaf(i + 1)));
+            }
+            assert!(mmr.prove(n + 1).is_none());
+        }
+    }
+
+    #[test]
+    fn tampered_proof_fails() {
+        let mut mmr = MerkleMountainRange::new();
+        for i in 0..11 {
+            mmr.push(&leaf(i));
+        }
+        let root = mmr.root().unwrap();
+        let proof = mmr.prove(9).unwrap();
+
+        let mut wrong_index = proof.clone();
+        wrong_index.leaf_index = 8;
+        assert!(!wrong_index.verify(&root, &leaf(9)));
+
+        let mut wrong_sibling = proof.clone();
+        wrong_sibling.siblings[0][0] ^= 1;
+        assert!(!wrong_sibling.verify(&root, &leaf(9)));
+
+        let mut missing_peak = proof;
+        missing_peak.peaks.pop();
+        assert!(!missing_peak.verify(&root, &leaf(9)));
+    }
+}
@@ -0,0 +1,83 @@
+//! Parameters of the SHA-3 algorithms
+//!
+//! Output sizes, rates and capacities of the sponges in bytes. The rate is
+//! the block size, e.g., for padding keys in [HMAC](crate::hmac). Generic code
+//! over the output size of a [`Hasher`](crate::Hasher) can use its associated
+//! constants instead.
+//!
+//! # Example Usage
+//! ```
+//! use sha3::{Sha3_256, params};
+//!
+//! assert_eq!(136, params::SHA3_256_RATE);
+//! assert_eq!(params::SHA3_256_RATE, Sha3_256::BLOCK_SIZE);
+//! ```
+
+use crate::{
+    hasher::{Sha3_224, Sha3_256, Sha3_384, Sha3_512},
+    xof::{Shake128, Shake256},
+};
+
+/// Output size of SHA3-224 in bytes.
+pub const SHA3_224_OUTPUT_SIZE: usize = Sha3_224::OUTPUT_SIZE;
+/// Rate of SHA3-224 in bytes.
+pub const SHA3_224_RATE: usize = Sha3_224::RATE;
+/// Capacity of SHA3-224 in bytes.
+pub const SHA3_224_CAPACITY: usize = Sha3_224::CAPACITY;
+
+/// Output size of SHA3-256 in bytes.
+pub const SHA3_256_OUTPUT_SIZE: usize = Sha3_256::OUTPUT_SIZE;
+/// Rate of SHA3-256 in bytes.
+pub const SHA3_256_RATE: usize = Sha3_256::RATE;
+/// Capacity of SHA3-256 in bytes.
+pub const SHA3_256_CAPACITY: usize = Sha3_256::CAPACITY;
+
+/// Output size of SHA3-384 in bytes.
+pub const SHA3_384_OUTPUT_SIZE: usize = Sha3_384::OUTPUT_SIZE;
+/// Rate of SHA3-384 in bytes.
+pub const SHA3_384_RATE: usize = Sha3_384::RATE;
+/// Capacity of SHA3-384 in bytes.
+pub const SHA3_384_CAPACITY: usize = Sha3_384::CAPACITY;
+
+/// Output size of SHA3-512 in bytes.
+pub const SHA3_512_OUTPUT_SIZE: usize = Sha3_512::OUTPUT_SIZE;
+/// Rate of SHA3-512 in bytes.
+pub const SHA3_512_RATE: usize = Sha3_512::RATE;
+/// Capacity of SHA3-512 in bytes.
+pub const SHA3_512_CAPACITY: usize = Sha3_512::CAPACITY;
+
+/// Rate of SHAKE128 in bytes.
+pub const SHAKE128_RATE: usize = Shake128::RATE;
+/// Capacity of SHAKE128 in bytes.
+pub const SHAKE128_CAPACITY: usize = Shake128::CAPACITY;
+
+/// Rate of SHAKE256 in bytes.
+pub const SHAKE256_RATE: usize = Shake256::RATE;
+/// Capacity of SHAKE256 in bytes.
+pub const SHAKE256_CAPACITY: usize = Shake256::CAPACITY;
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SHA3_224_CAPACITY, SHA3_224_OUTPUT_SIZE, SHA3_224_RATE, SHA3_256_CAPACITY,
+        SHA3_256_OUTPUT_SIZE, SHA3_256_RATE, SHA3_384_CAPACITY, SHA3_384_OUTPUT_SIZE,
+        SHA3_384_RATE, SHA3_512_CAPACITY, SHA3_512_OUTPUT_SIZE, SHA3_512_RATE, SHAKE128_CAPACITY,
+        SHAKE128_RATE, SHAKE256_CAPACITY, SHAKE256_RATE,
+    };
+
+    #[test]
+    fn fips202_parameters() {
+        let sha3 = [
+            (SHA3_224_OUTPUT_SIZE, SHA3_224_RATE, SHA3_224_CAPACITY),
+            (SHA3_256_OUTPUT_SIZE, SHA3_256_RATE, SHA3_256_CAPACITY),
+            (SHA3_384_OUTPUT_SIZE, SHA3_384_RATE, SHA3_384_CAPACITY),
+            (SHA3_512_OUTPUT_SIZE, SHA3_512_RATE, SHA3_512_CAPACITY),
+        ];
+        assert_eq!(
+            [(28, 144, 56), (32, 136, 64), (48, 104, 96), (64, 72, 128)],
+            sha3
+        );
+        assert_eq!((168, 32), (SHAKE128_RATE, SHAKE128_CAPACITY));
+        assert_eq!((136, 64), (SHAKE256_RATE, SHAKE256_CAPACITY));
+    }
+}
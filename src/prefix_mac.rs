@@ -0,0 +1,105 @@
+//! Keyed SHA-3 as a prefix MAC
+//!
+//! In contrast to SHA-2, SHA-3 is not vulnerable to length extension attacks.
+//! Hashing the key followed by the message is thus a secure MAC. To make the
+//! boundary between key and message unambiguous, the key is prefixed with
+//! its length in bytes as a 64-bit little-endian integer, i.e., the MAC is
+//! `SHA3(len(key) || key || message)`.
+//!
+//! The prefix MAC is a lightweight alternative to [KMAC](crate::kmac) for
+//! protocols that do not need its customization string or variable output
+//! length.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::{Sha3_256, prefix_mac::{PrefixMac, sha3_256_keyed}};
+//! #
+//! let mut mac = PrefixMac::<Sha3_256>::new(b"key");
+//! mac.update(b"your input ");
+//! mac.update(b"bytes");
+//! assert_eq!(sha3_256_keyed(b"key", b"your input bytes"), mac.finalize());
+//! ```
+
+use crate::hasher::Hasher;
+
+/// Prefix MAC over the SHA-3 hasher `H`, e.g., `PrefixMac<Sha3_256>`.
+pub struct PrefixMac<H> {
+    /// Hasher with the length-prefixed key already absorbed
+    hasher: H,
+}
+
+impl<const N: usize> PrefixMac<Hasher<N>> {
+    /// Create a new prefix MAC instance with the `key`.
+    pub fn new(key: &[u8]) -> Self {
+        let mut hasher = Hasher::new();
+        hasher.update(&(key.len() as u64).to_le_bytes());
+        hasher.update(key);
+        Self { hasher }
+    }
+
+    /// Absorb `data` into the hasher.
+    pub fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    /// Finish the computation and return the MAC.
+    pub fn finalize(self) -> [u8; N] {
+        self.hasher.finalize()
+    }
+}
+
+fn keyed<const N: usize>(key: &[u8], message: &[u8]) -> [u8; N] {
+    let mut mac = PrefixMac::<Hasher<N>>::new(key);
+    mac.update(message);
+    mac.finalize()
+}
+
+/// Keyed SHA3-224 of `message` with 224 bits (28 bytes) output.
+pub fn sha3_224_keyed(key: &[u8], message: &[u8]) -> [u8; 28] {
+    keyed::<28>(key, message)
+}
+
+/// Keyed SHA3-256 of `message` with 256 bits (32 bytes) output.
+pub fn sha3_256_keyed(key: &[u8], message: &[u8]) -> [u8; 32] {
+    keyed::<32>(key, message)
+}
+
+/// Keyed SHA3-384 of `message` with 384 bits (48 bytes) output.
+pub fn sha3_384_keyed(key: &[u8], message: &[u8]) -> [u8; 48] {
+    keyed::<48>(key, message)
+}
+
+/// Keyed SHA3-512 of `message` with 512 bits (64 bytes) output.
+pub fn sha3_512_keyed(key: &[u8], message: &[u8]) -> [u8; 64] {
+    keyed::<64>(key, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sha3_224_keyed, sha3_256_keyed, sha3_512_keyed};
+
+    #[test]
+    fn known_answers() {
+        assert_eq!(
+            "3c8cd684fa78f7625c9f4aecfb3323fdd240ab2308d1f01adf4420f9d4dd90b5",
+            hex::encode(sha3_256_keyed(b"secret key", b"message"))
+        );
+        assert_eq!(
+            "19ffb24c9960d4a864fa319fe6b81e73388fac5f0f160e4ae160ad2ea3903ee4\
+             0e2f7db6cb7e5a5567632aeda7125058ccfbe5d9a9f41c71eb0a30e2fc842293",
+            hex::encode(sha3_512_keyed(b"secret key", b"message"))
+        );
+        assert_eq!(
+            "065a4a35e57712ac33f6c801b6e99703e8f34a0ebbd37e927befd304",
+            hex::encode(sha3_224_keyed(b"", b""))
+        );
+    }
+
+    #[test]
+    fn key_boundary_is_unambiguous() {
+        assert_ne!(
+            sha3_256_keyed(b"key", b"message"),
+            sha3_256_keyed(b"keym", b"essage")
+        );
+    }
+}
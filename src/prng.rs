@@ -0,0 +1,172 @@
+//! Forkable sponge-based pseudorandom number generator
+//!
+//! A [`SpongeRng`] squeezes its output from a SHAKE256 sponge. In contrast to
+//! the generators in the `rng` module, new entropy can be absorbed into the
+//! live state with [`SpongeRng::reseed`], and independent child generators can
+//! be derived with [`SpongeRng::fork`]. Forking with a label derives the child
+//! deterministically from the parent, so a whole tree of generators can be
+//! reproduced from a single seed.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::prng::SpongeRng;
+//! #
+//! let mut root = SpongeRng::new(b"simulation seed");
+//! let mut network = root.fork(b"network");
+//! let mut disk = root.fork(b"disk");
+//! let latency = network.next_u64();
+//! let failure = disk.next_u64();
+//!
+//! // The same seed and labels reproduce the same generators
+//! let mut root = SpongeRng::new(b"simulation seed");
+//! assert_eq!(latency, root.fork(b"network").next_u64());
+//! assert_eq!(failure, root.fork(b"disk").next_u64());
+//! ```
+
+use crate::sponge::{AbsorbState, SHAKE_DELIMETED_SUFFIX, SqueezeState};
+
+/// Length of the key a parent squeezes for a forked child
+const FORK_KEY_LEN: usize = 32;
+
+/// Pseudorandom number generator squeezing a SHAKE256 sponge, which supports
+/// reseeding and forking.
+#[derive(Debug)]
+pub struct SpongeRng {
+    sponge: SqueezeState,
+}
+
+impl SpongeRng {
+    const CAPACITY: usize = 256 * 2;
+    const RATE: usize = 1600 - Self::CAPACITY;
+
+    /// Create a new generator from the `seed`.
+    ///
+    /// The output stream is the SHAKE256 output of the seed.
+    pub fn new(seed: &[u8]) -> Self {
+        let mut sponge = AbsorbState::new(Self::RATE, Self::CAPACITY, SHAKE_DELIMETED_SUFFIX);
+        sponge.absorb(seed);
+        Self {
+            sponge: sponge.into_squeeze(),
+        }
+    }
+
+    /// Fill `output` with the next pseudorandom bytes.
+    pub fn fill_bytes(&mut self, output: &mut [u8]) {
+        self.sponge.squeeze(output);
+    }
+
+    /// Return the next pseudorandom `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.sponge.squeeze(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    /// Return the next pseudorandom `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.sponge.squeeze(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    /// Absorb additional `entropy` into the live state.
+    ///
+    /// The output after reseeding depends on the previous state and the
+    /// entropy.
+    pub fn reseed(&mut self, entropy: &[u8]) {
+        self.sponge.reabsorb(entropy, SHAKE_DELIMETED_SUFFIX);
+    }
+
+    /// Derive an independent child generator for the `label`.
+    ///
+    /// The parent squeezes a key for the child, which is seeded with the key
+    /// and the label. Forking therefore advances the parent, and children
+    /// forked with the same label at different points are independent.
+    pub fn fork(&mut self, label: &[u8]) -> SpongeRng {
+        let mut key = [0; FORK_KEY_LEN];
+        self.sponge.squeeze(&mut key);
+        // The key has a fixed length, so key || label is unambiguous
+        let mut sponge = AbsorbState::new(Self::RATE, Self::CAPACITY, SHAKE_DELIMETED_SUFFIX);
+        sponge.absorb(&key);
+        sponge.absorb(label);
+        SpongeRng {
+            sponge: sponge.into_squeeze(),
+        }
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for SpongeRng {
+    fn next_u32(&mut self) -> u32 {
+        SpongeRng::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        SpongeRng::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        SpongeRng::fill_bytes(self, dst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SpongeRng;
+    use crate::shake256;
+
+    #[test]
+    fn output_is_shake256() {
+        let mut expected = [0; 500];
+        shake256(b"seed", &mut expected);
+        let mut rng = SpongeRng::new(b"seed");
+        let mut output = [0; 500];
+        for piece in output.chunks_mut(33) {
+            rng.fill_bytes(piece);
+        }
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn reseed_changes_output() {
+        let mut a = SpongeRng::new(b"seed");
+        let mut b = SpongeRng::new(b"seed");
+        a.next_u64();
+        b.next_u64();
+        a.reseed(b"entropy");
+        let after_reseed = a.next_u64();
+        assert_ne!(b.next_u64(), after_reseed);
+
+        let mut c = SpongeRng::new(b"seed");
+        c.next_u64();
+        c.reseed(b"entropy");
+        assert_eq!(after_reseed, c.next_u64());
+
+        let mut d = SpongeRng::new(b"seed");
+        d.next_u64();
+        d.reseed(b"other entropy");
+        assert_ne!(after_reseed, d.next_u64());
+    }
+
+    #[test]
+    fn forks_are_independent() {
+        let mut parent = SpongeRng::new(b"seed");
+        let mut a = parent.fork(b"a");
+        let mut a_again = parent.fork(b"a");
+        let mut b = parent.fork(b"b");
+        let values = [
+            a.next_u64(),
+            a_again.next_u64(),
+            b.next_u64(),
+            parent.next_u64(),
+        ];
+        for i in 0..values.len() {
+            for j in i + 1..values.len() {
+                assert_ne!(values[i], values[j]);
+            }
+        }
+
+        let mut parent = SpongeRng::new(b"seed");
+        assert_eq!(values[0], parent.fork(b"a").next_u64());
+    }
+}
@@ -0,0 +1,133 @@
+//! Deterministic random number generators backed by SHAKE
+//!
+//! [`Shake128Rng`] and [`Shake256Rng`] absorb a seed and squeeze the output
+//! stream of the XOF. They implement the [`rand_core`] traits and can be used
+//! with the `rand` ecosystem. The same seed always produces the same stream,
+//! which makes them suitable for reproducible simulations and tests.
+//!
+//! # Example Usage
+//! ```
+//! # use rand_core::{RngCore, SeedableRng};
+//! # use sha3::rng::Shake128Rng;
+//! #
+//! let mut rng = Shake128Rng::from_seed([42; 32]);
+//! let value = rng.next_u64();
+//!
+//! let mut expected = [0; 8];
+//! sha3::shake128(&[42; 32], &mut expected);
+//! assert_eq!(u64::from_le_bytes(expected), value);
+//! ```
+
+use rand_core::{RngCore, SeedableRng};
+
+use crate::{Shake128, Shake256, XofReader};
+
+/// Random number generator squeezing the output of SHAKE128.
+#[derive(Debug)]
+pub struct Shake128Rng {
+    reader: XofReader,
+}
+
+/// Random number generator squeezing the output of SHAKE256.
+#[derive(Debug)]
+pub struct Shake256Rng {
+    reader: XofReader,
+}
+
+impl SeedableRng for Shake128Rng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut shake = Shake128::new();
+        shake.update(&seed);
+        Self {
+            reader: shake.finalize_xof(),
+        }
+    }
+}
+
+impl RngCore for Shake128Rng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.reader.squeeze(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.reader.squeeze(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.reader.squeeze(dst);
+    }
+}
+
+impl SeedableRng for Shake256Rng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut shake = Shake256::new();
+        shake.update(&seed);
+        Self {
+            reader: shake.finalize_xof(),
+        }
+    }
+}
+
+impl RngCore for Shake256Rng {
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0; 4];
+        self.reader.squeeze(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0; 8];
+        self.reader.squeeze(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.reader.squeeze(dst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::{RngCore, SeedableRng};
+
+    use super::{Shake128Rng, Shake256Rng};
+    use crate::{shake128, shake256};
+
+    #[test]
+    fn output_is_xof_stream() {
+        let seed = [7; 32];
+        let mut expected = [0; 300];
+        shake128(&seed, &mut expected);
+        let mut rng = Shake128Rng::from_seed(seed);
+        let mut output = [0; 300];
+        output[..4].copy_from_slice(&rng.next_u32().to_le_bytes());
+        output[4..12].copy_from_slice(&rng.next_u64().to_le_bytes());
+        rng.fill_bytes(&mut output[12..]);
+        assert_eq!(expected, output);
+
+        shake256(&seed, &mut expected);
+        let mut rng = Shake256Rng::from_seed(seed);
+        output[..4].copy_from_slice(&rng.next_u32().to_le_bytes());
+        output[4..12].copy_from_slice(&rng.next_u64().to_le_bytes());
+        rng.fill_bytes(&mut output[12..]);
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn seed_from_u64_is_deterministic() {
+        let mut a = Shake128Rng::seed_from_u64(1);
+        let mut b = Shake128Rng::seed_from_u64(1);
+        let mut c = Shake128Rng::seed_from_u64(2);
+        let (a, b, c) = (a.next_u64(), b.next_u64(), c.next_u64());
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
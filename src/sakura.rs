@@ -0,0 +1,359 @@
+//! Sakura coding for tree hashing
+//!
+//! [Sakura] is a coding of tree nodes proposed by the Keccak Team. Every
+//! node of a tree hash mode ends with frame bits identifying the kind of the
+//! node, which makes any tree hash mode using the coding sound. In Sakura, a
+//! node is a *message hop*, the message followed by the bit `1`, or a
+//! *chaining hop*, chaining values followed by their number, the interleaving
+//! and the bit `0`. A *kangaroo hop* starts a node with a message hop as an
+//! inner node, followed by a chaining hop. Inner nodes end with the padding
+//! `10*` and the bit `0`, the final node ends with the bit `1`.
+//!
+//! This module provides the byte-aligned framing of these nodes as used by
+//! KangarooTwelve and a generic [`tree_hash_with`] driver. It builds trees
+//! of any number of levels from a [`TreeLayout`], parameterized by the inner
+//! function, the chunk length, the length of the chaining values, the number
+//! of chaining values per node and whether the final node starts with a
+//! kangaroo hop. [`tree_hash`] is the driver for the layout of
+//! KangarooTwelve.
+//!
+//! The frame bits at the end of a node are followed by the first bit of the
+//! `pad10*1` padding and are given as a delimited suffix, like the suffixes of
+//! the SHA-3 and SHAKE functions.
+//!
+//! # Example Usage
+//! KangarooTwelve of the empty message, with TurboSHAKE128 as the inner
+//! function, is computed by the following. The output of the inner function
+//! is the first block of the sponge after applying KECCAK-p\[1600, 12\].
+//! ```
+//! # use sha3::{keccak_p1600_12, sakura};
+//! #
+//! fn turbo_shake128(parts: &[&[u8]], suffix: u8, output: &mut [u8]) {
+//!     const RATE: usize = 168;
+//!     let mut bytes: Vec<u8> = parts.concat();
+//!     bytes.push(suffix);
+//!     bytes.resize(bytes.len().next_multiple_of(RATE), 0);
+//!     *bytes.last_mut().unwrap() ^= 0x80;
+//!     let mut lanes = [0; 25];
+//!     for block in bytes.chunks(RATE) {
+//!         for (lane, chunk) in lanes.iter_mut().zip(block.chunks(8)) {
+//!             *lane ^= u64::from_le_bytes(chunk.try_into().unwrap());
+//!         }
+//!         keccak_p1600_12(&mut lanes);
+//!     }
+//!     let state: Vec<u8> = lanes.iter().flat_map(|lane| lane.to_le_bytes()).collect();
+//!     output.copy_from_slice(&state[..output.len()]);
+//! }
+//!
+//! // The input of KangarooTwelve is M || C || length_encode(|C|)
+//! let (encoded, len) = sakura::length_encode(0);
+//! let input = &encoded[..len];
+//! let mut output = [0; 32];
+//! sakura::tree_hash(input, 8192, 32, turbo_shake128, &mut output);
+//! assert_eq!(
+//!     "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5",
+//!     hex::encode(output)
+//! );
+//! ```
+//!
+//! [Sakura]: https://keccak.team/files/Sakura.pdf
+
+/// Suffix of a tree consisting of a single node, the frame bits `11`.
+pub const SINGLE_NODE_SUFFIX: u8 = 0b111;
+/// Suffix of the final node, which contains chaining values, the frame bits
+/// `01`.
+pub const FINAL_NODE_SUFFIX: u8 = 0b110;
+/// Suffix of the leaves, the frame bits `110`.
+pub const LEAF_SUFFIX: u8 = 0b1011;
+/// Suffix of inner nodes which contain chaining values, the frame bits
+/// `010`.
+pub const INNER_CHAINING_SUFFIX: u8 = 0b1010;
+/// Bits `110^62` separating the first message chunk from the chaining values
+/// in the final node.
+pub const KANGAROO_HOP: [u8; 8] = [0b11, 0, 0, 0, 0, 0, 0, 0];
+/// Suffix of the chaining hop after the number of chaining values, which
+/// indicates that the chaining values are not interleaved.
+pub const CHAINING_HOP_SUFFIX: [u8; 2] = [0xff, 0xff];
+
+/// Encode `x` as its big-endian bytes without leading zeros, followed by the
+/// number of these bytes. Returns a buffer and the length of the encoding.
+///
+/// In contrast to `right_encode` of NIST SP 800-185, 0 is encoded as the
+/// single byte `0x00`.
+pub fn length_encode(x: u64) -> ([u8; 9], usize) {
+    let n = (u64::BITS - x.leading_zeros()).div_ceil(8) as usize;
+    let mut buf = [0; 9];
+    buf[..n].copy_from_slice(&x.to_be_bytes()[8 - n..]);
+    buf[n] = n as u8;
+    (buf, n + 1)
+}
+
+/// Shape of a tree built by [`tree_hash_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeLayout {
+    /// Length of the message chunks in bytes. Messages of at most this length
+    /// are hashed as a single node.
+    pub chunk_len: usize,
+    /// Length of the chaining values in bytes.
+    pub cv_len: usize,
+    /// Maximum number of chaining values in a node. If there are more, they
+    /// are combined by inner nodes on additional levels of the tree. `None`
+    /// puts all chaining values of the leaves into the final node.
+    pub arity: Option<usize>,
+    /// Whether the final node starts with the first chunk of the message,
+    /// followed by a kangaroo hop. Otherwise, all chunks are leaves.
+    pub kangaroo_hop: bool,
+}
+
+impl TreeLayout {
+    /// Layout of KangarooTwelve with chunks of `chunk_len` and chaining values
+    /// of `cv_len` bytes.
+    pub fn kangaroo_twelve(chunk_len: usize, cv_len: usize) -> Self {
+        Self {
+            chunk_len,
+            cv_len,
+            arity: None,
+            kangaroo_hop: true,
+        }
+    }
+}
+
+/// Tree hash of `message` with the inner function `f` and the layout of
+/// KangarooTwelve, filling all of `output`.
+///
+/// `f(parts, suffix, output)` must hash the concatenation of the `parts`
+/// followed by the delimited `suffix`. Messages of at most `chunk_len` bytes
+/// are hashed as a single node. Longer messages are split into chunks. The
+/// chunks except the first are hashed as leaves with a chaining value of
+/// `cv_len` bytes. The final node consists of the first chunk, the
+/// [`KANGAROO_HOP`], the chaining values and the chaining hop suffix.
+///
+/// # Panics
+/// If `chunk_len` is 0.
+pub fn tree_hash(
+    message: &[u8],
+    chunk_len: usize,
+    cv_len: usize,
+    f: impl FnMut(&[&[u8]], u8, &mut [u8]),
+    output: &mut [u8],
+) {
+    tree_hash_with(
+        message,
+        &TreeLayout::kangaroo_twelve(chunk_len, cv_len),
+        f,
+        output,
+    );
+}
+
+/// Tree hash of `message` with the inner function `f` and the given
+/// `layout`, filling all of `output`.
+///
+/// `f(parts, suffix, output)` must hash the concatenation of the `parts`
+/// followed by the delimited `suffix`. Messages of at most
+/// `layout.chunk_len` bytes are hashed as a single node. Longer messages are
+/// split into chunks, which are hashed as leaves with a chaining value of
+/// `layout.cv_len` bytes, except for the first chunk with a kangaroo hop.
+/// While there are more chaining values than `layout.arity`, they are
+/// combined in groups of `layout.arity` by inner nodes on the next level.
+/// The final node consists of the first chunk and the [`KANGAROO_HOP`] if
+/// enabled, followed by the remaining chaining values and the chaining hop
+/// suffix.
+///
+/// # Panics
+/// If `layout.chunk_len` is 0 or `layout.arity` is less than 2.
+pub fn tree_hash_with(
+    message: &[u8],
+    layout: &TreeLayout,
+    mut f: impl FnMut(&[&[u8]], u8, &mut [u8]),
+    output: &mut [u8],
+) {
+    let TreeLayout {
+        chunk_len,
+        cv_len,
+        arity,
+        kangaroo_hop,
+    } = *layout;
+    assert!(chunk_len > 0, "chunk_len must be positive");
+    assert!(
+        arity.is_none_or(|arity| arity >= 2),
+        "arity must be at least 2"
+    );
+    if message.len() <= chunk_len {
+        f(&[message], SINGLE_NODE_SUFFIX, output);
+        return;
+    }
+
+    let (first, rest) = if kangaroo_hop {
+        message.split_at(chunk_len)
+    } else {
+        (&[][..], message)
+    };
+    let mut nodes = rest.len().div_ceil(chunk_len);
+    let mut chaining_values = vec![0; nodes * cv_len];
+    for (leaf, cv) in rest
+        .chunks(chunk_len)
+        .zip(chaining_values.chunks_mut(cv_len))
+    {
+        f(&[leaf], LEAF_SUFFIX, cv);
+    }
+    if let Some(arity) = arity {
+        while nodes > arity {
+            let group_len = arity * cv_len;
+            nodes = nodes.div_ceil(arity);
+            let mut next = vec![0; nodes * cv_len];
+            for (group, cv) in chaining_values
+                .chunks(group_len)
+                .zip(next.chunks_mut(cv_len))
+            {
+                let (encoded, len) = length_encode((group.len() / cv_len) as u64);
+                f(
+                    &[group, &encoded[..len], &CHAINING_HOP_SUFFIX],
+                    INNER_CHAINING_SUFFIX,
+                    cv,
+                );
+            }
+            chaining_values = next;
+        }
+    }
+
+    let (encoded_nodes, len) = length_encode(nodes as u64);
+    let mut parts: Vec<&[u8]> = Vec::with_capacity(5);
+    if kangaroo_hop {
+        parts.extend([first, &KANGAROO_HOP]);
+    }
+    parts.extend([
+        &chaining_values[..],
+        &encoded_nodes[..len],
+        &CHAINING_HOP_SUFFIX,
+    ]);
+    f(&parts, FINAL_NODE_SUFFIX, output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CHAINING_HOP_SUFFIX, FINAL_NODE_SUFFIX, INNER_CHAINING_SUFFIX, LEAF_SUFFIX, TreeLayout,
+        length_encode, tree_hash, tree_hash_with,
+    };
+    use crate::{keccak::keccakp_1600_12_state_permute, sponge::AbsorbState};
+
+    fn turbo_shake128(parts: &[&[u8]], suffix: u8, output: &mut [u8]) {
+        let mut sponge = AbsorbState::new(1344, 256, suffix);
+        for part in parts {
+            sponge.absorb_with(part, keccakp_1600_12_state_permute);
+        }
+        let digest: [u8; 32] = sponge.finalize_with(keccakp_1600_12_state_permute);
+        output.copy_from_slice(&digest);
+    }
+
+    fn kangaroo_twelve(message: &[u8], customization: &[u8]) -> [u8; 32] {
+        let (encoded, len) = length_encode(customization.len() as u64);
+        let input = [message, customization, &encoded[..len]].concat();
+        let mut output = [0; 32];
+        tree_hash(&input, 8192, 32, turbo_shake128, &mut output);
+        output
+    }
+
+    /// Repeating pattern of the KangarooTwelve test vectors
+    fn ptn(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn multi_level_tree() {
+        let layout = TreeLayout {
+            chunk_len: 4,
+            cv_len: 32,
+            arity: Some(2),
+            kangaroo_hop: false,
+        };
+        let message = ptn(13);
+        let mut output = [0; 32];
+        tree_hash_with(&message, &layout, turbo_shake128, &mut output);
+
+        // Four leaves are combined by two inner nodes on the second level
+        let node = |parts: &[&[u8]], suffix| {
+            let mut cv = [0; 32];
+            turbo_shake128(parts, suffix, &mut cv);
+            cv
+        };
+        let leaves: Vec<[u8; 32]> = message
+            .chunks(4)
+            .map(|chunk| node(&[chunk], LEAF_SUFFIX))
+            .collect();
+        let inner: Vec<[u8; 32]> = leaves
+            .chunks(2)
+            .map(|pair| {
+                node(
+                    &[&pair.concat(), &[2, 1], &CHAINING_HOP_SUFFIX],
+                    INNER_CHAINING_SUFFIX,
+                )
+            })
+            .collect();
+        let expected = node(
+            &[&inner.concat(), &[2, 1], &CHAINING_HOP_SUFFIX],
+            FINAL_NODE_SUFFIX,
+        );
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn encode_length() {
+        let encode = |(buf, len): ([u8; 9], usize)| buf[..len].to_vec();
+        assert_eq!(vec![0], encode(length_encode(0)));
+        assert_eq!(vec![12, 1], encode(length_encode(12)));
+        assert_eq!(vec![1, 0, 2], encode(length_encode(256)));
+    }
+
+    // Test vectors from Section 5 of RFC 9861
+
+    #[test]
+    fn kangaroo_twelve_single_node() {
+        assert_eq!(
+            "1ac2d450fc3b4205d19da7bfca1b37513c0803577ac7167f06fe2ce1f0ef39e5",
+            hex::encode(kangaroo_twelve(b"", b""))
+        );
+        assert_eq!(
+            "6bf75fa2239198db4772e36478f8e19b0f371205f6a9a93a273f51df37122888",
+            hex::encode(kangaroo_twelve(&ptn(17), b""))
+        );
+        assert_eq!(
+            "cb552e2ec77d9910701d578b457ddf772c12e322e4ee7fe417f92c758f0d59d0",
+            hex::encode(kangaroo_twelve(&ptn(17 * 17 * 17), b""))
+        );
+        assert_eq!(
+            "fab658db63e94a246188bf7af69a133045f46ee984c56e3c3328caaf1aa1a583",
+            hex::encode(kangaroo_twelve(b"", &ptn(1)))
+        );
+        assert_eq!(
+            "d848c5068ced736f4462159b9867fd4c20b808acc3d5bc48e0b06ba0a3762ec4",
+            hex::encode(kangaroo_twelve(&[0xff], &ptn(41)))
+        );
+        assert_eq!(
+            "c389e5009ae57120854c2e8c64670ac01358cf4c1baf89447a724234dc7ced74",
+            hex::encode(kangaroo_twelve(&[0xff; 3], &ptn(41 * 41)))
+        );
+        assert_eq!(
+            "75d2f86a2e644566726b4fbcfc5657b9dbcf070c7b0dca06450ab291d7443bcf",
+            hex::encode(kangaroo_twelve(&[0xff; 7], &ptn(41 * 41 * 41)))
+        );
+    }
+
+    #[test]
+    fn kangaroo_twelve_tree() {
+        // The encoded customization string fills one chunk exactly or moves
+        // the input beyond it
+        assert_eq!(
+            "1b577636f723643e990cc7d6a659837436fd6a103626600eb8301cd1dbe553d6",
+            hex::encode(kangaroo_twelve(&ptn(8191), b""))
+        );
+        assert_eq!(
+            "48f256f6772f9edfb6a8b661ec92dc93b95ebd05a08a17b39ae3490870c926c3",
+            hex::encode(kangaroo_twelve(&ptn(8192), b""))
+        );
+        assert_eq!(
+            "8701045e22205345ff4dda05555cbb5c3af1a771c2b89baef37db43d9998b9fe",
+            hex::encode(kangaroo_twelve(&ptn(17 * 17 * 17 * 17), b""))
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Deterministic shuffling and sampling driven by SHAKE256
+//!
+//! The functions in this module expand a seed with SHAKE256 and use the
+//! output stream as a source of randomness. The same seed always produces
+//! the same result on every platform, which makes them suitable for
+//! committee selection from a public random beacon or for reproducible
+//! experiments.
+//!
+//! Uniform integers below a bound are drawn from the stream as 64-bit
+//! little-endian integers with rejection sampling, so the results are
+//! unbiased.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::sampling::{sample_indices, shuffle};
+//! #
+//! let mut candidates = ["alice", "bob", "carol", "dave"];
+//! shuffle(b"beacon round 42", &mut candidates);
+//!
+//! // Select a committee of 3 members out of 1000
+//! let committee = sample_indices(b"beacon round 42", 1000, 3);
+//! assert_eq!(3, committee.len());
+//! ```
+
+use std::collections::HashMap;
+
+use crate::{Shake256, XofReader};
+
+/// Shuffle `items` with the Fisher–Yates shuffle driven by SHAKE256 of
+/// `seed`.
+pub fn shuffle<T>(seed: &[u8], items: &mut [T]) {
+    let mut reader = reader(seed);
+    for i in (1..items.len()).rev() {
+        let j = uniform_below(&mut reader, i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+/// Sample `k` distinct indices from `0..n` without replacement, driven by
+/// SHAKE256 of `seed`.
+///
+/// The indices are returned in the order in which they were sampled. This
+/// is a partial Fisher–Yates shuffle of `0..n`, which only stores the swapped
+/// entries and thus needs memory linear in `k`.
+///
+/// # Panics
+/// If `k` is greater than `n`.
+pub fn sample_indices(seed: &[u8], n: usize, k: usize) -> Vec<usize> {
+    assert!(k <= n, "cannot sample more than n indices");
+    let mut reader = reader(seed);
+    // Entries of the virtual array 0..n which differ from their index
+    let mut swapped = HashMap::new();
+    (0..k)
+        .map(|i| {
+            let j = i + uniform_below(&mut reader, (n - i) as u64) as usize;
+            let value_j = swapped.get(&j).copied().unwrap_or(j);
+            let value_i = swapped.get(&i).copied().unwrap_or(i);
+            swapped.insert(j, value_i);
+            value_j
+        })
+        .collect()
+}
+
+/// Draw a uniform integer from `0..bound` from the output of `reader`.
+///
+/// # Panics
+/// If `bound` is 0.
+pub fn uniform_below(reader: &mut XofReader, bound: u64) -> u64 {
+    assert_ne!(bound, 0, "bound must be positive");
+    // Reject the 2^64 mod bound smallest values, so that every remainder is
+    // equally likely
+    let threshold = bound.wrapping_neg() % bound;
+    loop {
+        let value = reader.next_u64();
+        if value >= threshold {
+            return value % bound;
+        }
+    }
+}
+
+fn reader(seed: &[u8]) -> XofReader {
+    let mut shake = Shake256::new();
+    shake.update(seed);
+    shake.finalize_xof()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sample_indices, shuffle, uniform_below};
+
+    #[test]
+    fn shuffle_known_answer() {
+        let mut items: Vec<usize> = (0..10).collect();
+        shuffle(b"seed", &mut items);
+        assert_eq!(vec![1, 7, 3, 0, 4, 6, 5, 2, 8, 9], items);
+
+        let mut empty: [u8; 0] = [];
+        shuffle(b"seed", &mut empty);
+    }
+
+    #[test]
+    fn sample_known_answer() {
+        assert_eq!(
+            vec![118479, 807453, 192520, 960238, 845775],
+            sample_indices(b"seed", 1_000_000, 5)
+        );
+        assert_eq!(
+            vec![9, 0, 4, 8, 1, 2, 6, 7, 3, 5],
+            sample_indices(b"seed", 10, 10)
+        );
+        assert!(sample_indices(b"seed", 0, 0).is_empty());
+    }
+
+    #[test]
+    fn uniform_below_is_in_range() {
+        let mut reader = super::reader(b"seed");
+        for bound in [1, 2, 3, 7, 1 << 63, u64::MAX] {
+            assert!(uniform_below(&mut reader, bound) < bound);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! SHA-3 with operation shuffling
+//!
+//! This module is only available with the `shuffling` feature. The functions
+//! compute the same hashes as their counterparts in the crate root, but the
+//! order in which the independent lane operations of the θ and χ steps are
+//! processed is randomized in every round. The randomness is derived from a
+//! `seed` passed to every call, which should be chosen freshly at random for
+//! every invocation.
+//!
+//! Shuffling is a *hiding* countermeasure intended to raise the bar against
+//! simple power analysis on embedded targets, as it makes it harder to align
+//! power traces with the processed lanes. It is a much lighter alternative to
+//! masking, but it does not provide provable side-channel resistance and the
+//! effectiveness depends heavily on the target. Note that the seed itself is
+//! processed with operations that are not shuffled.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::shuffling;
+//! #
+//! let message = b"your input bytes";
+//! // Use a fresh random seed in practice
+//! let seed = 0x5eed;
+//! assert_eq!(sha3::sha3_256(message), shuffling::sha3_256(message, seed));
+//! ```
+
+use crate::{
+    hasher::Hasher,
+    keccak::{Shuffler, keccakf_1600_state_permute_shuffled},
+    sponge::{AbsorbState, SHA3_DELIMETED_SUFFIX},
+};
+
+/// SHA-3 Hash with 224 bits (28 bytes) output and operation shuffling.
+pub fn sha3_224(message: &[u8], seed: u64) -> [u8; 28] {
+    sha3_shuffled::<28>(message, seed)
+}
+
+/// SHA-3 Hash with 256 bits (32 bytes) output and operation shuffling.
+pub fn sha3_256(message: &[u8], seed: u64) -> [u8; 32] {
+    sha3_shuffled::<32>(message, seed)
+}
+
+/// SHA-3 Hash with 384 bits (48 bytes) output and operation shuffling.
+pub fn sha3_384(message: &[u8], seed: u64) -> [u8; 48] {
+    sha3_shuffled::<48>(message, seed)
+}
+
+/// SHA-3 Hash with 512 bits (64 bytes) output and operation shuffling.
+pub fn sha3_512(message: &[u8], seed: u64) -> [u8; 64] {
+    sha3_shuffled::<64>(message, seed)
+}
+
+/// SHA-3 with `N` bytes output and a shuffled permutation.
+fn sha3_shuffled<const N: usize>(input: &[u8], seed: u64) -> [u8; N] {
+    let capacity = Hasher::<N>::BITS * 2;
+    let mut shuffler = Shuffler::new(seed);
+    let mut sponge = AbsorbState::new(1600 - capacity, capacity, SHA3_DELIMETED_SUFFIX);
+    sponge.absorb_with(input, |state| {
+        keccakf_1600_state_permute_shuffled(state, &mut shuffler)
+    });
+    sponge.finalize_with(|state| keccakf_1600_state_permute_shuffled(state, &mut shuffler))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sha3_224, sha3_256, sha3_384, sha3_512};
+
+    #[test]
+    fn same_output_as_unshuffled() {
+        // Go beyond one block
+        for len in (0..300).step_by(7) {
+            let input: Vec<u8> = (0..len as u8).collect();
+            for seed in [0, 1, 42, u64::MAX] {
+                let seed = seed ^ len as u64;
+                assert_eq!(crate::sha3_224(&input), sha3_224(&input, seed));
+                assert_eq!(crate::sha3_256(&input), sha3_256(&input, seed));
+                assert_eq!(crate::sha3_384(&input), sha3_384(&input, seed));
+                assert_eq!(crate::sha3_512(&input), sha3_512(&input, seed));
+            }
+        }
+    }
+}
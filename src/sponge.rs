@@ -0,0 +1,490 @@
+//! 4. Sponge Construction
+//!
+//! The sponge construction instantiated with `pad10*1` and
+//! `KECCAK-p[1600, 24]`. In contrast to the one-shot hash functions, the
+//! [`AbsorbState`] allows absorbing the input in multiple calls and the
+//! [`SqueezeState`] squeezing an arbitrary amount of output in multiple calls.
+//!
+//! The sponge is parameterized by its rate and a delimited suffix, which
+//! contains the domain separation bits followed by the first bit of the
+//! padding. This is the core of all functions in this crate and can be used
+//! to build other Keccak-based primitives without reimplementing the padding.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::sponge::AbsorbState;
+//! #
+//! // SHAKE128 with a rate of 1344 bits and the suffix of the SHAKE XOFs
+//! let mut sponge = AbsorbState::new(1344, 256, 0b11111);
+//! sponge.absorb(b"your input ");
+//! sponge.absorb(b"bytes");
+//! let mut squeeze = sponge.into_squeeze();
+//! let mut output = [0; 32];
+//! squeeze.squeeze(&mut output);
+//!
+//! let mut expected = [0; 32];
+//! sha3::shake128(b"your input bytes", &mut expected);
+//! assert_eq!(expected, output);
+//! ```
+
+use crate::keccak::{State, keccakf_1600_state_permute};
+
+/// Bits that are appended to the end of the input for domain separation and
+/// padding. For SHA-3, this is the bit pattern 0b10 + the first 1 bit of the
+/// pad10*1 padding.
+pub(crate) const SHA3_DELIMETED_SUFFIX: u8 = 0b110;
+
+/// Delimited suffix of the original Keccak submission, which only consists of
+/// the first 1 bit of the pad10*1 padding. Used by legacy systems like
+/// Ethereum.
+pub(crate) const KECCAK_DELIMETED_SUFFIX: u8 = 0b1;
+
+/// Delimited suffix for the SHAKE XOFs, the bit pattern 0b1111 + the first 1
+/// bit of the pad10*1 padding. See Section 6.2.
+pub(crate) const SHAKE_DELIMETED_SUFFIX: u8 = 0b11111;
+
+/// Delimited suffix for cSHAKE, the bit pattern 0b00 + the first 1 bit of the
+/// pad10*1 padding. See Section 3.3 of NIST SP 800-185.
+pub(crate) const CSHAKE_DELIMETED_SUFFIX: u8 = 0b100;
+
+/// Absorbing phase of the sponge.
+#[derive(Clone, Debug)]
+pub struct AbsorbState {
+    state: State,
+    /// Rate in bytes
+    rate: usize,
+    /// Number of bytes absorbed into the current block
+    pos: usize,
+    /// Domain separation bits and first bit of the padding, preceded by the
+    /// trailing bits of an input that is not a multiple of 8 bits
+    suffix: u16,
+    /// Whether the input overwrites the rate instead of being XORed onto it
+    overwrite: bool,
+}
+
+impl AbsorbState {
+    /// Create a new sponge with the given `rate` and `capacity` in bits, which
+    /// appends the delimited `suffix` to the input.
+    ///
+    /// # Panics
+    /// If `rate + capacity` is not 1600, the rate is 0 or not a multiple of 8,
+    /// or the suffix is 0.
+    #[inline]
+    pub fn new(rate: usize, capacity: usize, suffix: u8) -> Self {
+        assert_eq!(
+            1600,
+            rate + capacity,
+            "rate + capacity must equal 1600 for KECCAK-p[1600]"
+        );
+        assert!(
+            rate > 0 && rate.is_multiple_of(8),
+            "rate must be a positive multiple of 8"
+        );
+        assert_ne!(suffix, 0, "suffix must be delimited");
+        Self {
+            state: State::default(),
+            rate: rate / 8,
+            pos: 0,
+            suffix: suffix.into(),
+            overwrite: false,
+        }
+    }
+
+    /// Recreate a sponge with `rate` in bytes from the parts returned by
+    /// [`AbsorbState::to_parts`].
+    pub(crate) fn from_parts(rate: usize, bytes: &[u8; 200], pos: usize, suffix: u16) -> Self {
+        let mut state = State::default();
+        state.with_bytes(|state| *state = *bytes);
+        Self {
+            state,
+            rate,
+            pos,
+            suffix,
+            overwrite: false,
+        }
+    }
+
+    /// Bytes of the state, number of bytes absorbed into the current block
+    /// and suffix.
+    pub(crate) fn to_parts(&self) -> ([u8; 200], usize, u16) {
+        (self.state.bytes(), self.pos, self.suffix)
+    }
+
+    /// Absorb `input` into the state.
+    #[inline]
+    pub fn absorb(&mut self, input: &[u8]) {
+        self.absorb_with(input, keccakf_1600_state_permute);
+    }
+
+    /// [`AbsorbState::absorb`] with a custom implementation of
+    /// `KECCAK-p[1600, 24]`.
+    #[inline(always)]
+    pub(crate) fn absorb_with(&mut self, input: &[u8], permute: impl FnMut(&mut State)) {
+        self.absorb_blocks(input, permute, xor_bytes, State::xor_lanes);
+    }
+
+    /// Absorb `input` in overwrite mode, replacing the bytes of the rate
+    /// instead of XORing the input onto them.
+    ///
+    /// In overwrite mode, the padded last block also replaces the whole rate.
+    /// For inputs that fit into a single block, the output is the same as
+    /// with [`AbsorbState::absorb`], as the rate of the initial state is
+    /// zero. Overwriting allows duplex-based encryption schemes to decrypt by
+    /// absorbing the ciphertext without keeping the plaintext. All input of a
+    /// sponge should be absorbed in the same mode.
+    pub fn absorb_overwrite(&mut self, input: &[u8]) {
+        self.overwrite = true;
+        self.absorb_blocks(
+            input,
+            keccakf_1600_state_permute,
+            overwrite_bytes,
+            |state, block| state.with_bytes(|bytes| overwrite_bytes(bytes, block)),
+        );
+    }
+
+    #[inline(always)]
+    fn absorb_blocks(
+        &mut self,
+        mut input: &[u8],
+        mut permute: impl FnMut(&mut State),
+        combine: fn(&mut [u8], &[u8]),
+        combine_block: fn(&mut State, &[u8]),
+    ) {
+        // Fill up a partially absorbed block first
+        if self.pos > 0 {
+            let len = input.len().min(self.rate - self.pos);
+            let (head, tail) = input.split_at(len);
+            self.state
+                .with_bytes(|bytes| combine(&mut bytes[self.pos..], head));
+            self.pos += len;
+            input = tail;
+            if self.pos < self.rate {
+                return;
+            }
+            permute(&mut self.state);
+            self.pos = 0;
+        }
+
+        let mut iter = input.chunks_exact(self.rate);
+        // Full blocks start at the first lane, so `combine_block` may work
+        // on whole lanes
+        for input_block in iter.by_ref() {
+            combine_block(&mut self.state, input_block);
+            permute(&mut self.state);
+        }
+
+        self.state
+            .with_bytes(|bytes| combine(bytes, iter.remainder()));
+        self.pos = iter.remainder().len();
+    }
+
+    /// Absorb the first `bit_len` bits of `input` into the state.
+    ///
+    /// Bits are numbered from the least significant bit of each byte as in
+    /// Appendix B.1. Trailing bits of an incomplete byte are prepended to the
+    /// suffix, so nothing may be absorbed afterwards.
+    pub(crate) fn absorb_bits(&mut self, input: &[u8], bit_len: usize) {
+        let (bytes, bits) = (bit_len / 8, bit_len % 8);
+        self.absorb(&input[..bytes]);
+        if bits > 0 {
+            let trailing = u16::from(input[bytes]) & ((1 << bits) - 1);
+            self.suffix = trailing | (self.suffix << bits);
+        }
+    }
+
+    /// Pad the input and squeeze a single block of output.
+    ///
+    /// The output of all SHA-3 functions fits into a single block of `rate`
+    /// bits, so the squeezing phase is specialized to a single permutation.
+    #[inline]
+    pub(crate) fn finalize<const OUT: usize>(self) -> [u8; OUT] {
+        self.finalize_with(keccakf_1600_state_permute)
+    }
+
+    /// [`AbsorbState::finalize`] with a custom implementation of
+    /// `KECCAK-p[1600, 24]`.
+    #[inline(always)]
+    pub(crate) fn finalize_with<const OUT: usize>(
+        mut self,
+        mut permute: impl FnMut(&mut State),
+    ) -> [u8; OUT] {
+        debug_assert!(OUT <= self.rate, "output must fit into one block");
+        self.pad(&mut permute);
+        permute(&mut self.state);
+        // The output fits into the first block, so it is read from the
+        // lanes directly without the position bookkeeping of squeezing
+        let mut output = [0; OUT];
+        self.state.read_bytes(0, &mut output);
+        output
+    }
+
+    /// Pad the input and switch to the squeezing phase.
+    #[inline]
+    pub fn into_squeeze(mut self) -> SqueezeState {
+        self.pad(keccakf_1600_state_permute);
+        keccakf_1600_state_permute(&mut self.state);
+        SqueezeState {
+            state: self.state,
+            rate: self.rate,
+            pos: 0,
+        }
+    }
+
+    /// Pad the input absorbed so far, fill `output` with the sponge output
+    /// and continue absorbing on the resulting state.
+    ///
+    /// Absorbing after squeezing overwrites the output bytes in the rate by
+    /// XORing the input onto them, as in the duplex construction.
+    pub fn squeeze_and_continue(&mut self, output: &mut [u8]) {
+        self.pad(keccakf_1600_state_permute);
+        keccakf_1600_state_permute(&mut self.state);
+        let mut sponge = SqueezeState {
+            state: self.state,
+            rate: self.rate,
+            pos: 0,
+        };
+        sponge.squeeze(output);
+        self.state = sponge.state;
+        self.pos = 0;
+    }
+
+    fn pad(&mut self, mut permute: impl FnMut(&mut State)) {
+        let rate = self.rate;
+        let mut pos = self.pos;
+        if self.overwrite {
+            self.state.with_bytes(|bytes| bytes[pos..rate].fill(0));
+        }
+        // Add domain separator and first 1 bit of padding, which extend to the
+        // next byte if they follow trailing bits of the input
+        let [low, high] = self.suffix.to_le_bytes();
+        self.state.xor_byte(pos, low);
+        let mut last = low;
+        if high != 0 {
+            pos += 1;
+            if pos == rate {
+                permute(&mut self.state);
+                pos = 0;
+            }
+            self.state.xor_byte(pos, high);
+            last = high;
+        }
+        // If the first 1 bit of padding is the last bit of the block, the
+        // second 1 bit is in the next block
+        if pos == rate - 1 && last & 0b10000000 != 0 {
+            permute(&mut self.state);
+        }
+        // Add second 1 bit of padding
+        self.state.xor_byte(rate - 1, 0b10000000);
+    }
+}
+
+/// Squeezing phase of the sponge.
+#[derive(Clone, Debug)]
+pub struct SqueezeState {
+    state: State,
+    /// Rate in bytes
+    rate: usize,
+    /// Number of bytes squeezed from the current block
+    pos: usize,
+}
+
+impl SqueezeState {
+    /// Fill `output` with the next bytes of the sponge output.
+    pub fn squeeze(&mut self, mut output: &mut [u8]) {
+        while !output.is_empty() {
+            if self.pos == self.rate {
+                keccakf_1600_state_permute(&mut self.state);
+                self.pos = 0;
+            }
+            let len = output.len().min(self.rate - self.pos);
+            let (head, tail) = output.split_at_mut(len);
+            self.state.read_bytes(self.pos, head);
+            self.pos += len;
+            output = tail;
+        }
+    }
+
+    /// Skip the next `len` bytes of the sponge output.
+    pub(crate) fn skip(&mut self, mut len: u64) {
+        while len > 0 {
+            if self.pos == self.rate {
+                keccakf_1600_state_permute(&mut self.state);
+                self.pos = 0;
+            }
+            let step = len.min((self.rate - self.pos) as u64);
+            self.pos += step as usize;
+            len -= step;
+        }
+    }
+
+    /// Absorb `input` into the live state and switch back to squeezing.
+    ///
+    /// The input is absorbed starting at the beginning of the rate and padded
+    /// with the delimited `suffix`, independent of how many bytes have already
+    /// been squeezed from the current block.
+    ///
+    /// # Panics
+    /// If the suffix is 0.
+    pub fn reabsorb(&mut self, input: &[u8], suffix: u8) {
+        assert_ne!(suffix, 0, "suffix must be delimited");
+        let mut sponge = AbsorbState {
+            state: self.state,
+            rate: self.rate,
+            pos: 0,
+            suffix: suffix.into(),
+            overwrite: false,
+        };
+        sponge.absorb(input);
+        *self = sponge.into_squeeze();
+    }
+}
+
+/// 4. and 5. Sponge Construction instantiated with `pad10*1` and
+///    `KECCAK-p[1600, 24]` for fixed-length hash functions, which append the
+///    delimited `suffix` to the input.
+// TODO Only have capacity as parameter and compute rate to be closer to spec?
+#[inline]
+pub(crate) fn keccak<const OUT: usize>(
+    rate: usize,
+    capacity: usize,
+    suffix: u8,
+    input: &[u8],
+) -> [u8; OUT] {
+    let mut sponge = AbsorbState::new(rate, capacity, suffix);
+    sponge.absorb(input);
+    sponge.finalize()
+}
+
+fn xor_bytes(dest: &mut [u8], other: &[u8]) {
+    // for_each combinator can lead to better codegen
+    // TODO benchmark this
+    dest.iter_mut().zip(other).for_each(|(state, input)| {
+        *state ^= input;
+    });
+}
+
+fn overwrite_bytes(dest: &mut [u8], other: &[u8]) {
+    dest[..other.len()].copy_from_slice(other);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AbsorbState, SHA3_DELIMETED_SUFFIX, SHAKE_DELIMETED_SUFFIX, keccak};
+    use crate::KeccakF1600;
+
+    #[test]
+    fn absorb_in_pieces() {
+        const RATE: usize = 1088;
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let expected: [u8; 32] = keccak(RATE, 1600 - RATE, SHA3_DELIMETED_SUFFIX, &input);
+        for piece_len in [1, 3, 100, 135, 136, 137, 500] {
+            let mut sponge = AbsorbState::new(RATE, 1600 - RATE, SHA3_DELIMETED_SUFFIX);
+            for piece in input.chunks(piece_len) {
+                sponge.absorb(piece);
+            }
+            assert_eq!(expected, sponge.finalize(), "piece length {piece_len}");
+        }
+    }
+
+    #[test]
+    fn absorb_blocks_of_partial_lanes() {
+        // 137 bytes, so full blocks end in a partial lane
+        const RATE: usize = 1096;
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let expected: [u8; 32] = keccak(RATE, 1600 - RATE, SHA3_DELIMETED_SUFFIX, &input);
+        // Single bytes are never absorbed as full blocks
+        let mut sponge = AbsorbState::new(RATE, 1600 - RATE, SHA3_DELIMETED_SUFFIX);
+        for byte in input.chunks(1) {
+            sponge.absorb(byte);
+        }
+        assert_eq!(expected, sponge.finalize());
+    }
+
+    #[test]
+    fn squeeze_in_pieces() {
+        const RATE: usize = 1344;
+        let new_sponge = || {
+            let mut sponge = AbsorbState::new(RATE, 1600 - RATE, SHAKE_DELIMETED_SUFFIX);
+            sponge.absorb(b"squeeze");
+            sponge.into_squeeze()
+        };
+        let mut expected = vec![0; 1000];
+        new_sponge().squeeze(&mut expected);
+        for piece_len in [1, 3, 100, 167, 168, 169, 500] {
+            let mut sponge = new_sponge();
+            let mut output = vec![0; 1000];
+            for piece in output.chunks_mut(piece_len) {
+                sponge.squeeze(piece);
+            }
+            assert_eq!(expected, output, "piece length {piece_len}");
+        }
+    }
+
+    #[test]
+    fn squeeze_and_continue() {
+        const RATE: usize = 1344;
+        let mut expected = vec![0; 500];
+        let mut sponge = AbsorbState::new(RATE, 1600 - RATE, SHAKE_DELIMETED_SUFFIX);
+        sponge.absorb(b"squeeze");
+        sponge.into_squeeze().squeeze(&mut expected);
+
+        let mut sponge = AbsorbState::new(RATE, 1600 - RATE, SHAKE_DELIMETED_SUFFIX);
+        sponge.absorb(b"squeeze");
+        let mut output = vec![0; 500];
+        sponge.squeeze_and_continue(&mut output);
+        assert_eq!(expected, output);
+
+        // Absorbing continues on the squeezed state
+        sponge.absorb(b"more");
+        let mut next = vec![0; 500];
+        sponge.squeeze_and_continue(&mut next);
+        assert_ne!(output, next);
+    }
+
+    #[test]
+    #[should_panic]
+    fn rate_and_capacity_must_fill_state() {
+        AbsorbState::new(1088, 256, SHA3_DELIMETED_SUFFIX);
+    }
+
+    #[test]
+    fn overwrite_matches_xor_for_single_block() {
+        const RATE: usize = 1088;
+        for len in [0, 1, 100, 134, 135] {
+            let input = vec![0x5a; len];
+            let expected: [u8; 32] = keccak(RATE, 1600 - RATE, SHA3_DELIMETED_SUFFIX, &input);
+            let mut sponge = AbsorbState::new(RATE, 1600 - RATE, SHA3_DELIMETED_SUFFIX);
+            sponge.absorb_overwrite(&input);
+            assert_eq!(expected, sponge.finalize(), "input length {len}");
+        }
+    }
+
+    #[test]
+    fn overwrite_replaces_rate() {
+        const RATE: usize = 136;
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        for len in [136, 272, 300, 1000] {
+            let input = &input[..len];
+            let mut padded = input.to_vec();
+            padded.push(SHA3_DELIMETED_SUFFIX);
+            padded.resize(padded.len().next_multiple_of(RATE), 0);
+            *padded.last_mut().unwrap() ^= 0x80;
+            let mut state = KeccakF1600::new();
+            for block in padded.chunks(RATE) {
+                let mut rate = [0; RATE];
+                state.extract_bytes(0, &mut rate);
+                state.xor_bytes(0, &rate);
+                state.xor_bytes(0, block);
+                state.permute();
+            }
+            let mut expected = [0; 32];
+            state.extract_bytes(0, &mut expected);
+
+            let mut sponge = AbsorbState::new(RATE * 8, 1600 - RATE * 8, SHA3_DELIMETED_SUFFIX);
+            for piece in input.chunks(77) {
+                sponge.absorb_overwrite(piece);
+            }
+            assert_eq!(expected, sponge.finalize(), "input length {len}");
+        }
+    }
+}
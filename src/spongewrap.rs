@@ -0,0 +1,284 @@
+//! Authenticated encryption with SpongeWrap
+//!
+//! A byte-oriented variant of the SpongeWrap mode of [Bertoni et al.], built
+//! on the rate-limited [`Duplex`] with a capacity of 256 bits. The key, the
+//! nonce, the associated data and the message are split into blocks of
+//! [`BLOCK_LEN`] bytes. Every block is absorbed with one duplex call and
+//! followed by a frame byte, which identifies the field the block belongs to
+//! and whether it is the last block of the field. Empty fields are absorbed as
+//! a single empty block.
+//!
+//! The ciphertext block `i` is the plaintext block `i` XORed with the output
+//! of the previous duplex call. As the plaintext is absorbed, the output of
+//! the duplex call for the last message block is the tag.
+//!
+//! A nonce must never be used twice with the same key. Otherwise, the XOR of
+//! two plaintexts leaks.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::spongewrap::SpongeWrap;
+//! #
+//! let key = [7; 16];
+//! let nonce = [0; 12];
+//! let mut buffer = *b"your input bytes";
+//! let tag = SpongeWrap::new(&key, &nonce).encrypt(b"header", &mut buffer);
+//!
+//! SpongeWrap::new(&key, &nonce)
+//!     .decrypt(b"header", &mut buffer, &tag)
+//!     .expect("tag is valid");
+//! assert_eq!(b"your input bytes", &buffer);
+//! ```
+//!
+//! [Bertoni et al.]: https://keccak.team/files/SpongeDuplex.pdf
+
+use std::{error::Error, fmt};
+
+use crate::{ct::ct_eq, duplex::Duplex};
+
+/// Capacity of the duplex object in bits.
+const CAPACITY: usize = 256;
+/// Length of the key, nonce, associated data and message blocks in bytes.
+///
+/// Two bytes of the rate are reserved for the frame byte and the padding.
+pub const BLOCK_LEN: usize = (1600 - CAPACITY) / 8 - 2;
+/// Length of the authentication tag in bytes.
+pub const TAG_LEN: usize = 16;
+
+/// Frame bytes identifying the field of a block
+const KEY_FRAME: u8 = 0x01;
+const NONCE_FRAME: u8 = 0x02;
+const ASSOCIATED_DATA_FRAME: u8 = 0x03;
+const MESSAGE_FRAME: u8 = 0x04;
+/// Added to the frame byte of the last block of a field
+const LAST_BLOCK: u8 = 0x80;
+
+/// SpongeWrap instance with a key and a nonce absorbed.
+pub struct SpongeWrap {
+    duplex: Duplex,
+    /// Output of the last duplex call
+    keystream: [u8; BLOCK_LEN],
+}
+
+/// Redacts the keystream unless the `debug-state` feature is enabled, as it
+/// encrypts the next message block.
+impl fmt::Debug for SpongeWrap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("SpongeWrap");
+        debug.field("duplex", &self.duplex);
+        #[cfg(feature = "debug-state")]
+        debug.field("keystream", &self.keystream);
+        #[cfg(not(feature = "debug-state"))]
+        debug.field("keystream", &format_args!("<redacted>"));
+        debug.finish()
+    }
+}
+
+impl SpongeWrap {
+    /// Create a new instance for the `key` and the `nonce`.
+    ///
+    /// The key should be at least 16 bytes long.
+    pub fn new(key: &[u8], nonce: &[u8]) -> Self {
+        let mut wrap = Self {
+            duplex: Duplex::new(CAPACITY),
+            keystream: [0; BLOCK_LEN],
+        };
+        wrap.absorb_field(key, KEY_FRAME);
+        wrap.absorb_field(nonce, NONCE_FRAME);
+        wrap
+    }
+
+    /// Encrypt `buffer` in place and return the tag authenticating it and the
+    /// `associated_data`.
+    pub fn encrypt(mut self, associated_data: &[u8], buffer: &mut [u8]) -> [u8; TAG_LEN] {
+        self.absorb_field(associated_data, ASSOCIATED_DATA_FRAME);
+        self.for_each_message_block(buffer, |block, keystream| {
+            let mut plaintext = [0; BLOCK_LEN];
+            plaintext[..block.len()].copy_from_slice(block);
+            xor_keystream(block, keystream);
+            plaintext
+        });
+        self.tag()
+    }
+
+    /// Decrypt `buffer` in place and verify the `tag`.
+    ///
+    /// If the tag is invalid, the buffer is zeroed to not release
+    /// unauthenticated plaintext.
+    pub fn decrypt(
+        mut self,
+        associated_data: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8; TAG_LEN],
+    ) -> Result<(), AuthenticationError> {
+        self.absorb_field(associated_data, ASSOCIATED_DATA_FRAME);
+        self.for_each_message_block(buffer, |block, keystream| {
+            xor_keystream(block, keystream);
+            let mut plaintext = [0; BLOCK_LEN];
+            plaintext[..block.len()].copy_from_slice(block);
+            plaintext
+        });
+        if ct_eq(&self.tag(), tag) {
+            Ok(())
+        } else {
+            buffer.fill(0);
+            Err(AuthenticationError)
+        }
+    }
+
+    /// Absorb `data` block by block, followed by the frame byte.
+    fn absorb_field(&mut self, data: &[u8], frame: u8) {
+        let last = data.len().saturating_sub(1) / BLOCK_LEN;
+        // chunks does not yield a block for empty data
+        let blocks = data
+            .chunks(BLOCK_LEN)
+            .chain(data.is_empty().then_some(&[][..]));
+        for (i, block) in blocks.enumerate() {
+            self.duplex_block(block, frame_byte(frame, i == last));
+        }
+    }
+
+    /// Encrypt or decrypt the message blocks with `process`, which returns the
+    /// plaintext of the block to absorb.
+    fn for_each_message_block(
+        &mut self,
+        buffer: &mut [u8],
+        mut process: impl FnMut(&mut [u8], &[u8]) -> [u8; BLOCK_LEN],
+    ) {
+        let last = buffer.len().saturating_sub(1) / BLOCK_LEN;
+        if buffer.is_empty() {
+            self.duplex_block(&[], frame_byte(MESSAGE_FRAME, true));
+        }
+        for (i, block) in buffer.chunks_mut(BLOCK_LEN).enumerate() {
+            let plaintext = process(block, &self.keystream);
+            let frame = frame_byte(MESSAGE_FRAME, i == last);
+            self.duplex_block(&plaintext[..block.len()], frame);
+        }
+    }
+
+    fn duplex_block(&mut self, block: &[u8], frame: u8) {
+        let mut input = [0; BLOCK_LEN + 1];
+        input[..block.len()].copy_from_slice(block);
+        input[block.len()] = frame;
+        self.duplex
+            .duplex(&input[..block.len() + 1], &mut self.keystream);
+    }
+
+    fn tag(&self) -> [u8; TAG_LEN] {
+        self.keystream[..TAG_LEN]
+            .try_into()
+            .expect("keystream is longer than the tag")
+    }
+}
+
+fn frame_byte(frame: u8, last: bool) -> u8 {
+    if last { frame | LAST_BLOCK } else { frame }
+}
+
+fn xor_keystream(block: &mut [u8], keystream: &[u8]) {
+    block
+        .iter_mut()
+        .zip(keystream)
+        .for_each(|(byte, key)| *byte ^= key);
+}
+
+/// Error returned by [`SpongeWrap::decrypt`] for an invalid tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticationError;
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("authentication tag is invalid")
+    }
+}
+
+impl Error for AuthenticationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuthenticationError, SpongeWrap};
+
+    const KEY: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+    const NONCE: [u8; 12] = [16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27];
+
+    #[test]
+    fn known_answers() {
+        let mut buffer = [];
+        let tag = SpongeWrap::new(&KEY, &NONCE).encrypt(b"", &mut buffer);
+        assert_eq!("37abb12db6acc015d144cd194f5e24da", hex::encode(tag));
+
+        let mut buffer = *b"hello world";
+        let tag = SpongeWrap::new(&KEY, &NONCE).encrypt(b"header", &mut buffer);
+        assert_eq!("1a210b206ad3d703bba1b0", hex::encode(buffer));
+        assert_eq!("dd776bc85a8c9193e7170ff0c7f3b6ad", hex::encode(tag));
+
+        // Multiple blocks of associated data and message
+        let associated_data: Vec<u8> = (0..200).collect();
+        let mut buffer: Vec<u8> = (0..=255).cycle().take(512).collect();
+        let tag = SpongeWrap::new(&KEY, &NONCE).encrypt(&associated_data, &mut buffer);
+        assert_eq!(
+            "a030a8cd70d2af545b16a2e3690febac7e8504c9585f9588fa498c190eb91e1c",
+            hex::encode(&buffer[..32])
+        );
+        assert_eq!("2fdbffe6f1f1b62219dcc46f0576d17b", hex::encode(tag));
+    }
+
+    #[test]
+    fn roundtrip() {
+        for len in [0, 1, 165, 166, 167, 1000] {
+            let plaintext: Vec<u8> = (0..=255).cycle().take(len).collect();
+            let mut buffer = plaintext.clone();
+            let tag = SpongeWrap::new(&KEY, &NONCE).encrypt(b"ad", &mut buffer);
+            if len > 0 {
+                assert_ne!(plaintext, buffer);
+            }
+            SpongeWrap::new(&KEY, &NONCE)
+                .decrypt(b"ad", &mut buffer, &tag)
+                .expect("tag is valid");
+            assert_eq!(plaintext, buffer, "len {len}");
+        }
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let plaintext = *b"attack at dawn";
+        let mut ciphertext = plaintext;
+        let tag = SpongeWrap::new(&KEY, &NONCE).encrypt(b"ad", &mut ciphertext);
+
+        let decrypt = |key: &[u8], nonce: &[u8], ad: &[u8], ciphertext: &[u8], tag| {
+            let mut buffer = ciphertext.to_vec();
+            let result = SpongeWrap::new(key, nonce).decrypt(ad, &mut buffer, tag);
+            (result, buffer)
+        };
+        assert_eq!(Ok(()), decrypt(&KEY, &NONCE, b"ad", &ciphertext, &tag).0);
+
+        let mut modified = ciphertext;
+        modified[3] ^= 1;
+        let mut modified_tag = tag;
+        modified_tag[15] ^= 0x80;
+        let failures = [
+            decrypt(&KEY, &NONCE, b"ad", &modified, &tag),
+            decrypt(&KEY, &NONCE, b"ad", &ciphertext, &modified_tag),
+            decrypt(&KEY, &NONCE, b"ae", &ciphertext, &tag),
+            decrypt(&KEY, &NONCE[1..], b"ad", &ciphertext, &tag),
+            decrypt(&KEY[1..], &NONCE, b"ad", &ciphertext, &tag),
+            // Moving bytes between fields changes the frames
+            decrypt(&KEY, &[&NONCE[..], b"a"].concat(), b"d", &ciphertext, &tag),
+        ];
+        for (result, buffer) in failures {
+            assert_eq!(Err(AuthenticationError), result);
+            assert_eq!([0; 14], buffer.as_slice());
+        }
+    }
+
+    #[cfg(not(feature = "debug-state"))]
+    #[test]
+    fn debug_redacts_state() {
+        let wrap = SpongeWrap::new(&KEY, &NONCE);
+        assert_eq!(
+            "SpongeWrap { duplex: Duplex { state: <redacted>, rate: 168, input_block: 168 }, \
+             keystream: <redacted> }",
+            format!("{wrap:?}")
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Stateless signed tokens with KMAC256
+//!
+//! [`sign`] authenticates a payload and an expiry time with [`Kmac256`] under
+//! a secret key and encodes both as a compact, URL-safe token. [`verify`]
+//! checks the tag in constant time, rejects expired tokens and returns the
+//! payload. Tokens are not encrypted, anyone can read the payload without the
+//! key.
+//!
+//! A token consists of two unpadded base64url strings separated by a `.`. The
+//! first encodes the expiry in seconds since the Unix epoch as a 64-bit
+//! little-endian integer followed by the payload, the second the 32 byte
+//! KMAC256 tag of the first with the customization string `sha3 token`.
+//!
+//! # Example Usage
+//! ```
+//! # use std::time::{Duration, SystemTime};
+//! # use sha3::token::{TokenError, sign, verify, verify_at};
+//! #
+//! // The key must be secret and uniformly random
+//! let key = [0x42; 32];
+//! let expires_at = SystemTime::now() + Duration::from_secs(3600);
+//! let token = sign(&key, b"user=alice", expires_at);
+//!
+//! assert_eq!(b"user=alice", &verify(&key, &token).unwrap()[..]);
+//! assert_eq!(
+//!     Err(TokenError::Expired),
+//!     verify_at(&key, &token, expires_at + Duration::from_secs(1))
+//! );
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    ct::ct_eq,
+    encoding::{BASE64_URL, base64_decode, base64_encode},
+    kmac::Kmac256,
+};
+
+/// Minimum length of the key in bytes.
+pub const MIN_KEY_LEN: usize = 32;
+/// Length of the tag in bytes.
+const TAG_LEN: usize = 32;
+/// Customization string of KMAC256, which separates tokens from other uses of
+/// the key.
+const CUSTOMIZATION: &[u8] = b"sha3 token";
+
+/// Create a token for `payload` which expires at `expires_at`.
+///
+/// The expiry is stored with a precision of seconds, rounded down. Times
+/// before the Unix epoch are stored as the epoch.
+///
+/// # Panics
+/// If `key` is shorter than [`MIN_KEY_LEN`].
+pub fn sign(key: &[u8], payload: &[u8], expires_at: SystemTime) -> String {
+    let expiry = expires_at
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let body = [&expiry.to_le_bytes()[..], payload].concat();
+    let tag = tag(key, &body);
+    let mut token = base64_encode(&body, BASE64_URL, false);
+    token.push('.');
+    token.push_str(&base64_encode(&tag, BASE64_URL, false));
+    token
+}
+
+/// Verify `token` with the current system time and return its payload.
+///
+/// # Errors
+/// If `key` is shorter than [`MIN_KEY_LEN`], the token is malformed, its tag
+/// is invalid for `key`, or it has expired.
+pub fn verify(key: &[u8], token: &str) -> Result<Vec<u8>, TokenError> {
+    verify_at(key, token, SystemTime::now())
+}
+
+/// Verify `token` at the time `now` and return its payload.
+///
+/// A token is valid before its expiry time. The tag is checked before the
+/// expiry, so an expired token is only reported as such if it is authentic.
+///
+/// # Errors
+/// If `key` is shorter than [`MIN_KEY_LEN`], the token is malformed, its tag
+/// is invalid for `key`, or it has expired.
+pub fn verify_at(key: &[u8], token: &str, now: SystemTime) -> Result<Vec<u8>, TokenError> {
+    if key.len() < MIN_KEY_LEN {
+        return Err(TokenError::InvalidKey);
+    }
+    let (body, tag_str) = token.split_once('.').ok_or(TokenError::Malformed)?;
+    let mut body = base64_decode(body, BASE64_URL, false, 0).map_err(|_| TokenError::Malformed)?;
+    let received =
+        base64_decode(tag_str, BASE64_URL, false, 0).map_err(|_| TokenError::Malformed)?;
+    if body.len() < 8 || received.len() != TAG_LEN {
+        return Err(TokenError::Malformed);
+    }
+    if !ct_eq(&tag(key, &body), &received) {
+        return Err(TokenError::InvalidTag);
+    }
+    let expiry = u64::from_le_bytes(body[..8].try_into().expect("slice has 8 bytes"));
+    let now = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    if now >= expiry {
+        return Err(TokenError::Expired);
+    }
+    Ok(body.split_off(8))
+}
+
+/// KMAC256 tag of the token body.
+fn tag(key: &[u8], body: &[u8]) -> [u8; TAG_LEN] {
+    assert!(
+        key.len() >= MIN_KEY_LEN,
+        "key must have at least {MIN_KEY_LEN} bytes"
+    );
+    let mut kmac = Kmac256::new(key, CUSTOMIZATION);
+    kmac.update(body);
+    let mut tag = [0; TAG_LEN];
+    kmac.finalize(&mut tag);
+    tag
+}
+
+/// Error returned by [`verify`] and [`verify_at`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// The key is shorter than [`MIN_KEY_LEN`].
+    InvalidKey,
+    /// The token is not a valid encoding of a token.
+    Malformed,
+    /// The tag of the token is not valid for the key.
+    InvalidTag,
+    /// The token has expired.
+    Expired,
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenError::InvalidKey => f.write_str("token key is too short"),
+            TokenError::Malformed => f.write_str("malformed token"),
+            TokenError::InvalidTag => f.write_str("invalid token tag"),
+            TokenError::Expired => f.write_str("token has expired"),
+        }
+    }
+}
+
+impl Error for TokenError {}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use super::{TokenError, sign, verify_at};
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn round_trip() {
+        let token = sign(&KEY, b"payload", at(1000));
+        assert!(!token.contains(['+', '/', '=']));
+        assert_eq!(Ok(b"payload".to_vec()), verify_at(&KEY, &token, at(999)));
+        assert_eq!(
+            Ok(Vec::new()),
+            verify_at(&KEY, &sign(&KEY, b"", at(1)), at(0))
+        );
+    }
+
+    #[test]
+    fn rejects_expired() {
+        let token = sign(&KEY, b"payload", at(1000));
+        assert_eq!(Err(TokenError::Expired), verify_at(&KEY, &token, at(1000)));
+        assert_eq!(Err(TokenError::Expired), verify_at(&KEY, &token, at(5000)));
+    }
+
+    /// Body and tag of a token.
+    fn split(token: &str) -> (String, String) {
+        let (body, tag) = token.split_once('.').unwrap();
+        (body.to_owned(), tag.to_owned())
+    }
+
+    #[test]
+    fn rejects_forgeries() {
+        let token = sign(&KEY, b"user=alice", at(1000));
+        assert_eq!(
+            Err(TokenError::InvalidTag),
+            verify_at(&[0x43; 32], &token, at(0))
+        );
+        // Replace the payload or the expiry, but keep the tag
+        let (_, tag) = split(&token);
+        for other in [
+            sign(&KEY, b"user=admin", at(1000)),
+            sign(&KEY, b"user=alice", at(2000)),
+        ] {
+            let (body, _) = split(&other);
+            assert_eq!(
+                Err(TokenError::InvalidTag),
+                verify_at(&KEY, &format!("{body}.{tag}"), at(0))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        let token = sign(&KEY, b"payload", at(1000));
+        for malformed in [
+            "",
+            "no separator",
+            &token.replace('.', "!"),
+            &format!("{token}A"),
+            &token[..token.len() - 1],
+            "AAAA.AAAA",
+        ] {
+            assert_eq!(
+                Err(TokenError::Malformed),
+                verify_at(&KEY, malformed, at(0)),
+                "{malformed}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn sign_rejects_short_key() {
+        sign(&[0; 16], b"payload", at(1000));
+    }
+
+    #[test]
+    fn verify_rejects_short_key() {
+        let token = sign(&KEY, b"payload", at(1000));
+        assert_eq!(
+            Err(TokenError::InvalidKey),
+            verify_at(&KEY[..16], &token, at(0))
+        );
+    }
+}
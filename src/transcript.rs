@@ -0,0 +1,161 @@
+//! Transcripts for the Fiat–Shamir transform
+//!
+//! A [`Transcript`] is modeled after the [Merlin] transcripts. The prover and
+//! the verifier append the same labeled messages to their transcripts and
+//! derive challenges from them, which makes an interactive proof
+//! non-interactive. Each challenge depends on the transcript label and all
+//! messages and challenges before it.
+//!
+//! The transcript is a SHAKE128-sized sponge. Every operation is absorbed
+//! with a frame of an operation byte and the length-prefixed label, so that
+//! different sequences of messages never result in the same sponge input. To
+//! derive a challenge, the sponge is padded and squeezed, and absorbing
+//! continues on the resulting state.
+//!
+//! To derive a challenge scalar for a group of prime order `q`, request at
+//! least 64 bytes with [`Transcript::challenge_bytes`] and reduce them modulo
+//! `q`, which results in a negligible bias.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::transcript::Transcript;
+//! #
+//! let commitment = b"prover commitment";
+//! let mut prover = Transcript::new(b"my proof system");
+//! prover.append_message(b"commitment", commitment);
+//! let mut challenge = [0; 64];
+//! prover.challenge_bytes(b"challenge", &mut challenge);
+//!
+//! // The verifier recomputes the challenge from the proof
+//! let mut verifier = Transcript::new(b"my proof system");
+//! verifier.append_message(b"commitment", commitment);
+//! let mut expected = [0; 64];
+//! verifier.challenge_bytes(b"challenge", &mut expected);
+//! assert_eq!(expected, challenge);
+//! ```
+//!
+//! [Merlin]: https://merlin.cool
+
+use crate::sponge::{AbsorbState, SHAKE_DELIMETED_SUFFIX};
+
+/// Domain separator absorbed when creating a transcript
+const PROTOCOL_LABEL: &[u8] = b"sha3-rs transcript v1";
+
+/// Operation bytes preceding every framed operation
+const APPEND_OPERATION: u8 = 0x01;
+const CHALLENGE_OPERATION: u8 = 0x02;
+
+/// Transcript of a public-coin protocol, from which challenges are derived.
+#[derive(Debug)]
+pub struct Transcript {
+    sponge: AbsorbState,
+}
+
+impl Transcript {
+    const CAPACITY: usize = 128 * 2;
+    const RATE: usize = 1600 - Self::CAPACITY;
+
+    /// Create a new transcript for the protocol identified by `label`.
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Self {
+            sponge: AbsorbState::new(Self::RATE, Self::CAPACITY, SHAKE_DELIMETED_SUFFIX),
+        };
+        transcript.sponge.absorb(PROTOCOL_LABEL);
+        transcript.append_message(b"dom-sep", label);
+        transcript
+    }
+
+    /// Append the `message` with the `label` to the transcript.
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.absorb_frame(APPEND_OPERATION, label);
+        self.absorb_length(message.len());
+        self.sponge.absorb(message);
+    }
+
+    /// Append the `value` with the `label` to the transcript, encoded as 8
+    /// little-endian bytes.
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    /// Fill `output` with challenge bytes for the `label`.
+    ///
+    /// The challenge depends on the length of `output` and is appended to
+    /// the transcript, so later challenges depend on it.
+    pub fn challenge_bytes(&mut self, label: &[u8], output: &mut [u8]) {
+        self.absorb_frame(CHALLENGE_OPERATION, label);
+        self.absorb_length(output.len());
+        self.sponge.squeeze_and_continue(output);
+    }
+
+    fn absorb_frame(&mut self, operation: u8, label: &[u8]) {
+        self.sponge.absorb(&[operation]);
+        self.absorb_length(label.len());
+        self.sponge.absorb(label);
+    }
+
+    fn absorb_length(&mut self, len: usize) {
+        self.sponge.absorb(&(len as u64).to_le_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transcript;
+
+    fn challenge(transcript: &mut Transcript) -> [u8; 32] {
+        let mut output = [0; 32];
+        transcript.challenge_bytes(b"challenge", &mut output);
+        output
+    }
+
+    #[test]
+    fn framing_is_unambiguous() {
+        let mut a = Transcript::new(b"test");
+        a.append_message(b"a", b"bc");
+        let mut b = Transcript::new(b"test");
+        b.append_message(b"ab", b"c");
+        let mut c = Transcript::new(b"test");
+        c.append_message(b"a", b"b");
+        c.append_message(b"a", b"c");
+        let mut d = Transcript::new(b"tes");
+        d.append_message(b"ta", b"bc");
+        let challenges = [
+            challenge(&mut a),
+            challenge(&mut b),
+            challenge(&mut c),
+            challenge(&mut d),
+        ];
+        for i in 0..challenges.len() {
+            for j in i + 1..challenges.len() {
+                assert_ne!(challenges[i], challenges[j]);
+            }
+        }
+    }
+
+    #[test]
+    fn challenges_depend_on_history() {
+        let mut a = Transcript::new(b"test");
+        a.append_u64(b"n", 42);
+        let first = challenge(&mut a);
+        let second = challenge(&mut a);
+        assert_ne!(first, second);
+
+        let mut b = Transcript::new(b"test");
+        b.append_u64(b"n", 42);
+        assert_eq!(first, challenge(&mut b));
+        b.append_message(b"m", b"");
+        assert_ne!(second, challenge(&mut b));
+    }
+
+    #[test]
+    fn challenge_depends_on_length() {
+        let mut a = Transcript::new(b"test");
+        let mut short = [0; 16];
+        a.challenge_bytes(b"challenge", &mut short);
+        let mut b = Transcript::new(b"test");
+        let mut long = [0; 300];
+        b.challenge_bytes(b"challenge", &mut long);
+        assert_ne!(short, long[..16]);
+    }
+}
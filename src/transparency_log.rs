@@ -0,0 +1,294 @@
+//! RFC 6962 style transparency log hashing over SHA3-256
+//!
+//! This module implements the Merkle tree hash, audit paths and consistency
+//! proofs of [RFC 6962] (Certificate Transparency), instantiated with
+//! [`sha3_256`] instead of SHA-256. Leaves are hashed with a `0x00` prefix and
+//! inner nodes with a `0x01` prefix. The verification algorithms follow Section
+//! 2.1.3.2 and 2.1.4.2 of [RFC 9162].
+//!
+//! The proof generation functions operate on the list of leaf hashes (see
+//! [`leaf_hash`]) so that callers only need to store 32 bytes per entry.
+//!
+//! # Example Usage
+//! ```
+//! # use sha3::transparency_log::{
+//! #     audit_path, consistency_proof, leaf_hash, tree_root, verify_consistency,
+//! #     verify_inclusion,
+//! # };
+//! #
+//! let entries = [&b"a"[..], b"b", b"c", b"d", b"e"];
+//! let leaves: Vec<_> = entries.iter().map(|entry| leaf_hash(entry)).collect();
+//!
+//! let root = tree_root(&leaves);
+//! let path = audit_path(&leaves, 2).unwrap();
+//! assert!(verify_inclusion(2, 5, &leaf_hash(b"c"), &path, &root));
+//!
+//! let old_root = tree_root(&leaves[..3]);
+//! let proof = consistency_proof(&leaves, 3).unwrap();
+//! assert!(verify_consistency(3, 5, &old_root, &root, &proof));
+//! ```
+//!
+//! [RFC 6962]: https://www.rfc-editor.org/rfc/rfc6962
+//! [RFC 9162]: https://www.rfc-editor.org/rfc/rfc9162
+
+use crate::sha3_256;
+
+/// SHA3-256 hash of a leaf, node or tree.
+pub type Hash = [u8; 32];
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Hash of a log entry: `SHA3-256(0x00 || data)`.
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut input = Vec::with_capacity(1 + data.len());
+    input.push(LEAF_PREFIX);
+    input.extend_from_slice(data);
+    sha3_256(&input)
+}
+
+/// Hash of an inner node: `SHA3-256(0x01 || left || right)`.
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut input = [0; 65];
+    input[0] = NODE_PREFIX;
+    input[1..33].copy_from_slice(left);
+    input[33..].copy_from_slice(right);
+    sha3_256(&input)
+}
+
+/// Merkle Tree Hash (MTH) of a list of leaf hashes.
+///
+/// The hash of an empty tree is the hash of the empty string.
+pub fn tree_root(leaves: &[Hash]) -> Hash {
+    match leaves {
+        [] => sha3_256(&[]),
+        [leaf] => *leaf,
+        _ => {
+            let k = split_point(leaves.len());
+            node_hash(&tree_root(&leaves[..k]), &tree_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path (`PATH(m, D[n])`) for the leaf at `index` or `None` if `index`
+/// is out of bounds.
+pub fn audit_path(leaves: &[Hash], index: usize) -> Option<Vec<Hash>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    let mut path = vec![];
+    collect_path(index, leaves, &mut path);
+    Some(path)
+}
+
+fn collect_path(m: usize, leaves: &[Hash], path: &mut Vec<Hash>) {
+    if leaves.len() <= 1 {
+        return;
+    }
+    let k = split_point(leaves.len());
+    if m < k {
+        collect_path(m, &leaves[..k], path);
+        path.push(tree_root(&leaves[k..]));
+    } else {
+        collect_path(m - k, &leaves[k..], path);
+        path.push(tree_root(&leaves[..k]));
+    }
+}
+
+/// Consistency proof (`PROOF(m, D[n])`) between the tree formed by the first
+/// `old_size` leaves and the tree of all `leaves`, or `None` if `old_size` is
+/// larger than the number of leaves.
+pub fn consistency_proof(leaves: &[Hash], old_size: usize) -> Option<Vec<Hash>> {
+    if old_size > leaves.len() {
+        return None;
+    }
+    let mut proof = vec![];
+    if old_size > 0 {
+        collect_subproof(old_size, leaves, true, &mut proof);
+    }
+    Some(proof)
+}
+
+fn collect_subproof(m: usize, leaves: &[Hash], complete: bool, proof: &mut Vec<Hash>) {
+    let n = leaves.len();
+    if m == n {
+        if !complete {
+            proof.push(tree_root(leaves));
+        }
+        return;
+    }
+    let k = split_point(n);
+    if m <= k {
+        collect_subproof(m, &leaves[..k], complete, proof);
+        proof.push(tree_root(&leaves[k..]));
+    } else {
+        collect_subproof(m - k, &leaves[k..], false, proof);
+        proof.push(tree_root(&leaves[..k]));
+    }
+}
+
+/// Verify that `leaf` is the leaf at `index` of a tree with `tree_size` leaves
+/// and the given `root`.
+pub fn verify_inclusion(
+    index: u64,
+    tree_size: u64,
+    leaf: &Hash,
+    path: &[Hash],
+    root: &Hash,
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let mut fnode = index;
+    let mut snode = tree_size - 1;
+    let mut r = *leaf;
+    for p in path {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            r = node_hash(p, &r);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            r = node_hash(&r, p);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    snode == 0 && r == *root
+}
+
+/// Verify that the tree with `old_size` leaves and `old_root` is a prefix of
+/// the tree with `new_size` leaves and `new_root`.
+pub fn verify_consistency(
+    old_size: u64,
+    new_size: u64,
+    old_root: &Hash,
+    new_root: &Hash,
+    proof: &[Hash],
+) -> bool {
+    if old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+    if old_size == 0 {
+        // Every tree is an extension of the empty tree
+        return proof.is_empty();
+    }
+    let mut path = proof.iter();
+    // If the old tree is a complete subtree, its root is the first node of the
+    // path but is omitted from the proof.
+    let first = if old_size.is_power_of_two() {
+        old_root
+    } else {
+        match path.next() {
+            Some(first) => first,
+            None => return false,
+        }
+    };
+
+    let mut fnode = old_size - 1;
+    let mut snode = new_size - 1;
+    while fnode & 1 == 1 {
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    let mut fr = *first;
+    let mut sr = *first;
+    for c in path {
+        if snode == 0 {
+            return false;
+        }
+        if fnode & 1 == 1 || fnode == snode {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            while fnode & 1 == 0 && fnode != 0 {
+                fnode >>= 1;
+                snode >>= 1;
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+        fnode >>= 1;
+        snode >>= 1;
+    }
+    snode == 0 && fr == *old_root && sr == *new_root
+}
+
+/// Largest power of two smaller than `n`. Requires `n > 1`.
+fn split_point(n: usize) -> usize {
+    debug_assert!(n > 1);
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Hash, audit_path, consistency_proof, leaf_hash, node_hash, split_point, tree_root,
+        verify_consistency, verify_inclusion,
+    };
+
+    fn leaves(n: usize) -> Vec<Hash> {
+        (0..n).map(|i| leaf_hash(&i.to_le_bytes())).collect()
+    }
+
+    #[test]
+    fn split_point_is_largest_smaller_power_of_two() {
+        assert_eq!(1, split_point(2));
+        assert_eq!(2, split_point(3));
+        assert_eq!(2, split_point(4));
+        assert_eq!(4, split_point(5));
+        assert_eq!(64, split_point(100));
+    }
+
+    #[test]
+    fn three_leaf_root() {
+        let leaves = leaves(3);
+        let expected = node_hash(&node_hash(&leaves[0], &leaves[1]), &leaves[2]);
+        assert_eq!(expected, tree_root(&leaves));
+    }
+
+    #[test]
+    fn inclusion_proofs_verify() {
+        for n in 1..=20 {
+            let leaves = leaves(n);
+            let root = tree_root(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let path = audit_path(&leaves, i).unwrap();
+                let (i, n) = (i as u64, n as u64);
+                assert!(verify_inclusion(i, n, leaf, &path, &root));
+                if !path.is_empty() {
+                    assert!(!verify_inclusion(i, n, leaf, &path[1..], &root));
+                }
+                assert!(!verify_inclusion(i, n, &leaf_hash(b"x"), &path, &root));
+            }
+            assert!(audit_path(&leaves, n).is_none());
+        }
+    }
+
+    #[test]
+    fn consistency_proofs_verify() {
+        for n in 1..=20 {
+            let leaves = leaves(n);
+            let root = tree_root(&leaves);
+            for m in 0..=n {
+                let old_root = tree_root(&leaves[..m]);
+                let proof = consistency_proof(&leaves, m).unwrap();
+                let (m, n) = (m as u64, n as u64);
+                assert!(verify_consistency(m, n, &old_root, &root, &proof));
+                if 0 < m && m < n {
+                    let mut tampered = proof.clone();
+                    tampered[0][0] ^= 1;
+                    assert!(!verify_consistency(m, n, &old_root, &root, &tampered));
+                    assert!(!verify_consistency(m, n, &root, &root, &proof));
+                }
+            }
+            assert!(consistency_proof(&leaves, n + 1).is_none());
+        }
+    }
+}
@@ -0,0 +1,627 @@
+//! 6.2 SHA-3 Extendable-Output Functions
+//!
+//! The SHAKE XOFs are instantiated with the [`SHAKE_DELIMETED_SUFFIX`] and
+//! can produce an output of arbitrary length. [`Keccak`] is the underlying
+//! sponge `KECCAK[c]` of Section 5.2 with a capacity chosen at runtime and
+//! optional domain separation bits.
+
+use std::io::{self, Read};
+
+use crate::sponge::{
+    AbsorbState, CSHAKE_DELIMETED_SUFFIX, KECCAK_DELIMETED_SUFFIX, SHA3_DELIMETED_SUFFIX,
+    SHAKE_DELIMETED_SUFFIX, SqueezeState,
+};
+
+/// Incremental SHAKE128 extendable-output function.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::Shake128;
+/// #
+/// let mut shake = Shake128::new();
+/// shake.update(b"your input ");
+/// shake.update(b"bytes");
+/// let mut reader = shake.finalize_xof();
+/// let mut output = [0; 64];
+/// reader.squeeze(&mut output);
+///
+/// let mut expected = [0; 64];
+/// sha3::shake128(b"your input bytes", &mut expected);
+/// assert_eq!(expected, output);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Shake128 {
+    sponge: AbsorbState,
+}
+
+impl Shake128 {
+    /// Capacity of the sponge in bytes, twice the security level of 128 bits.
+    pub const CAPACITY: usize = 128 * 2 / 8;
+    /// Rate of the sponge in bytes.
+    pub const RATE: usize = 200 - Self::CAPACITY;
+    /// Block size in bytes, which equals the rate.
+    pub const BLOCK_SIZE: usize = Self::RATE;
+
+    /// Create a new SHAKE128 instance.
+    pub fn new() -> Self {
+        Self {
+            sponge: AbsorbState::new(Self::RATE * 8, Self::CAPACITY * 8, SHAKE_DELIMETED_SUFFIX),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    /// Finish absorbing and return an [`XofReader`] for the output.
+    pub fn finalize_xof(self) -> XofReader {
+        XofReader::new(self.sponge.into_squeeze())
+    }
+
+    /// Finish absorbing and return the first `len` bytes of the output.
+    pub fn finalize_vec(self, len: usize) -> Vec<u8> {
+        let mut output = vec![0; len];
+        self.finalize_xof().squeeze(&mut output);
+        output
+    }
+}
+
+impl Default for Shake128 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Incremental SHAKE256 extendable-output function.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::Shake256;
+/// #
+/// let mut shake = Shake256::new();
+/// shake.update(b"your input ");
+/// shake.update(b"bytes");
+/// let mut reader = shake.finalize_xof();
+/// let mut output = [0; 64];
+/// reader.squeeze(&mut output);
+///
+/// let mut expected = [0; 64];
+/// sha3::shake256(b"your input bytes", &mut expected);
+/// assert_eq!(expected, output);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Shake256 {
+    sponge: AbsorbState,
+}
+
+impl Shake256 {
+    /// Capacity of the sponge in bytes, twice the security level of 256 bits.
+    pub const CAPACITY: usize = 256 * 2 / 8;
+    /// Rate of the sponge in bytes.
+    pub const RATE: usize = 200 - Self::CAPACITY;
+    /// Block size in bytes, which equals the rate.
+    pub const BLOCK_SIZE: usize = Self::RATE;
+
+    /// Create a new SHAKE256 instance.
+    pub fn new() -> Self {
+        Self {
+            sponge: AbsorbState::new(Self::RATE * 8, Self::CAPACITY * 8, SHAKE_DELIMETED_SUFFIX),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    /// Finish absorbing and return an [`XofReader`] for the output.
+    pub fn finalize_xof(self) -> XofReader {
+        XofReader::new(self.sponge.into_squeeze())
+    }
+
+    /// Finish absorbing and return the first `len` bytes of the output.
+    pub fn finalize_vec(self, len: usize) -> Vec<u8> {
+        let mut output = vec![0; len];
+        self.finalize_xof().squeeze(&mut output);
+        output
+    }
+}
+
+impl Default for Shake256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Incremental `KECCAK[c]` sponge with a capacity of `c` bits.
+///
+/// By default, the input is only padded with `pad10*1`, as in the original
+/// Keccak submission. `KECCAK[512]` truncated to 256 bits is thus
+/// [`keccak256`](crate::keccak256). With [`Keccak::with_suffix`], domain
+/// separation bits are appended to the input before the padding.
+///
+/// # Example Usage
+/// ```
+/// # use sha3::Keccak;
+/// #
+/// let mut keccak = Keccak::new(256);
+/// keccak.update(b"your input bytes");
+/// let mut output = [0; 64];
+/// keccak.finalize_xof().squeeze(&mut output);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Keccak {
+    sponge: AbsorbState,
+}
+
+impl Keccak {
+    /// Delimited suffix without domain separation bits, as in the original
+    /// Keccak submission.
+    pub const KECCAK_SUFFIX: u8 = KECCAK_DELIMETED_SUFFIX;
+    /// Delimited suffix of the SHA-3 hash functions, the bits `01`.
+    pub const SHA3_SUFFIX: u8 = SHA3_DELIMETED_SUFFIX;
+    /// Delimited suffix of the SHAKE XOFs, the bits `1111`.
+    pub const SHAKE_SUFFIX: u8 = SHAKE_DELIMETED_SUFFIX;
+    /// Delimited suffix of cSHAKE from NIST SP 800-185, the bits `00`.
+    pub const CSHAKE_SUFFIX: u8 = CSHAKE_DELIMETED_SUFFIX;
+
+    /// Create a new `KECCAK[c]` instance with a capacity of `capacity` bits.
+    ///
+    /// # Panics
+    /// If the capacity is not a multiple of 8 or not less than 1600.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_suffix(capacity, KECCAK_DELIMETED_SUFFIX)
+    }
+
+    /// Create a new `KECCAK[c]` instance with a capacity of `capacity` bits,
+    /// which appends the domain separation bits of the delimited `suffix` to
+    /// the input.
+    ///
+    /// The delimited suffix consists of the domain separation bits, starting
+    /// at the least significant bit, followed by a single 1 bit. For example,
+    /// the bits `01` of SHA-3 are given as `0b110`. Nodes of tree hash modes
+    /// can use the Sakura suffixes in [`sakura`](crate::sakura).
+    ///
+    /// # Panics
+    /// If the capacity is not a multiple of 8 or not less than 1600, or if the
+    /// suffix is 0.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Keccak;
+    /// #
+    /// let mut keccak = Keccak::with_suffix(256, Keccak::SHAKE_SUFFIX);
+    /// keccak.update(b"your input bytes");
+    /// let mut output = [0; 32];
+    /// keccak.finalize_xof().squeeze(&mut output);
+    ///
+    /// let mut expected = [0; 32];
+    /// sha3::shake128(b"your input bytes", &mut expected);
+    /// assert_eq!(expected, output);
+    /// ```
+    pub fn with_suffix(capacity: usize, suffix: u8) -> Self {
+        assert!(
+            capacity.is_multiple_of(8) && capacity < 1600,
+            "capacity must be a multiple of 8 less than 1600"
+        );
+        assert_ne!(suffix, 0, "suffix must be delimited");
+        Self {
+            sponge: AbsorbState::new(1600 - capacity, capacity, suffix),
+        }
+    }
+
+    /// Absorb `data` into the state.
+    pub fn update(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    /// Finish absorbing and return an [`XofReader`] for the output.
+    pub fn finalize_xof(self) -> XofReader {
+        XofReader::new(self.sponge.into_squeeze())
+    }
+
+    /// Finish absorbing and return the first `len` bytes of the output.
+    pub fn finalize_vec(self, len: usize) -> Vec<u8> {
+        let mut output = vec![0; len];
+        self.finalize_xof().squeeze(&mut output);
+        output
+    }
+}
+
+/// Reader for the output of an extendable-output function.
+#[derive(Clone, Debug)]
+pub struct XofReader {
+    sponge: SqueezeState,
+    /// State before squeezing the first byte, to seek backwards
+    initial: SqueezeState,
+    /// Number of bytes squeezed so far
+    position: u64,
+}
+
+impl XofReader {
+    pub(crate) fn new(sponge: SqueezeState) -> Self {
+        Self {
+            initial: sponge.clone(),
+            sponge,
+            position: 0,
+        }
+    }
+
+    /// Fill `output` with the next bytes of the XOF output.
+    ///
+    /// Squeezing `n` bytes in multiple calls produces the same output as
+    /// squeezing `n` bytes at once.
+    pub fn squeeze(&mut self, output: &mut [u8]) {
+        self.sponge.squeeze(output);
+        self.position += output.len() as u64;
+    }
+
+    /// Continue squeezing at the absolute byte `offset` of the output.
+    ///
+    /// Seeking forward permutes the state once per skipped block, without
+    /// copying the skipped output. Seeking backwards restarts from the first
+    /// block.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Shake128;
+    /// #
+    /// let mut reader = Shake128::new().finalize_xof();
+    /// let mut expected = [0; 10_000];
+    /// reader.squeeze(&mut expected);
+    ///
+    /// reader.seek(9_000);
+    /// let mut output = [0; 1000];
+    /// reader.squeeze(&mut output);
+    /// assert_eq!(expected[9_000..], output);
+    /// ```
+    pub fn seek(&mut self, offset: u64) {
+        if offset < self.position {
+            self.sponge = self.initial.clone();
+            self.position = 0;
+        }
+        self.sponge.skip(offset - self.position);
+        self.position = offset;
+    }
+
+    /// Branch off an independent reader for `label`.
+    ///
+    /// The fork absorbs `label` into a copy of the current state, so that
+    /// forks with different labels and the original reader produce
+    /// independent output streams, e.g., one per worker thread. Cloning the
+    /// reader instead produces a copy of the same stream.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Shake256;
+    /// #
+    /// let mut shake = Shake256::new();
+    /// shake.update(b"common transcript");
+    /// let reader = shake.finalize_xof();
+    /// let workers: Vec<_> = (0..4_u32).map(|i| reader.fork(&i.to_le_bytes())).collect();
+    /// # let mut outputs = workers.into_iter().map(|mut worker| worker.next_u64());
+    /// # assert_ne!(outputs.next(), outputs.next());
+    /// ```
+    pub fn fork(&self, label: &[u8]) -> XofReader {
+        let mut sponge = self.sponge.clone();
+        sponge.reabsorb(label, SHAKE_DELIMETED_SUFFIX);
+        XofReader::new(sponge)
+    }
+
+    /// Absolute byte offset of the next output byte.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Squeeze the next 4 bytes as a little-endian `u32`.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.squeeze(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Squeeze the next 8 bytes as a little-endian `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.squeeze(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Iterator over the next bytes of the XOF output.
+    ///
+    /// The iterator is infinite.
+    ///
+    /// # Example Usage
+    /// ```
+    /// # use sha3::Shake128;
+    /// #
+    /// let mut reader = Shake128::new().finalize_xof();
+    /// let nonzero: Vec<u8> = reader.iter_bytes().filter(|&b| b != 0).take(16).collect();
+    /// assert_eq!(16, nonzero.len());
+    /// ```
+    pub fn iter_bytes(&mut self) -> impl Iterator<Item = u8> + '_ {
+        std::iter::repeat_with(|| {
+            let mut byte = [0];
+            self.squeeze(&mut byte);
+            byte[0]
+        })
+    }
+}
+
+/// Reading never fails and always fills the whole buffer, as the output of an
+/// XOF is unbounded. Use [`Read::take`] to limit the output length.
+///
+/// # Example Usage
+/// ```
+/// # use std::io::Read;
+/// # use sha3::Shake256;
+/// #
+/// let mut shake = Shake256::new();
+/// shake.update(b"your input bytes");
+/// let mut output = Vec::new();
+/// shake.finalize_xof().take(100).read_to_end(&mut output).unwrap();
+///
+/// let mut expected = [0; 100];
+/// sha3::shake256(b"your input bytes", &mut expected);
+/// assert_eq!(expected[..], output);
+/// ```
+impl Read for XofReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.squeeze(buf);
+        Ok(buf.len())
+    }
+}
+
+/// SHAKE128 extendable-output function, filling all of `output`.
+pub fn shake128(message: &[u8], output: &mut [u8]) {
+    let mut shake = Shake128::new();
+    shake.update(message);
+    shake.finalize_xof().squeeze(output);
+}
+
+/// SHAKE256 extendable-output function, filling all of `output`.
+pub fn shake256(message: &[u8], output: &mut [u8]) {
+    let mut shake = Shake256::new();
+    shake.update(message);
+    shake.finalize_xof().squeeze(output);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Keccak, Shake128, Shake256, shake128, shake256};
+    use crate::{keccak256, sha3_256};
+
+    #[test]
+    fn shake128_empty_message() {
+        let mut output = [0; 32];
+        shake128(b"", &mut output);
+        assert_eq!(
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn shake256_empty_message() {
+        let mut output = [0; 64];
+        shake256(b"", &mut output);
+        assert_eq!(
+            "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f\
+             d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn incremental_matches_one_shot() {
+        let input: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut expected = vec![0; 500];
+        shake128(&input, &mut expected);
+
+        let mut shake = Shake128::new();
+        for piece in input.chunks(77) {
+            shake.update(piece);
+        }
+        let mut reader = shake.finalize_xof();
+        let mut output = vec![0; 500];
+        for piece in output.chunks_mut(33) {
+            reader.squeeze(piece);
+        }
+        assert_eq!(expected, output);
+
+        shake256(&input, &mut expected);
+        let mut shake = Shake256::new();
+        for piece in input.chunks(77) {
+            shake.update(piece);
+        }
+        let mut reader = shake.finalize_xof();
+        for piece in output.chunks_mut(33) {
+            reader.squeeze(piece);
+        }
+        assert_eq!(expected, output);
+    }
+
+    #[test]
+    fn reader_implements_read() {
+        use std::io::{self, Read};
+
+        let mut expected = [0; 1000];
+        shake128(b"abc", &mut expected);
+
+        let mut shake = Shake128::new();
+        shake.update(b"abc");
+        let mut reader = shake.finalize_xof();
+        let mut output = [0; 10];
+        reader.read_exact(&mut output).unwrap();
+        assert_eq!(expected[..10], output);
+        let mut output = Vec::new();
+        io::copy(&mut (&mut reader).take(990), &mut output).unwrap();
+        assert_eq!(expected[10..], output);
+    }
+
+    #[test]
+    fn word_and_byte_access() {
+        let mut expected = [0; 100];
+        shake256(b"abc", &mut expected);
+
+        let mut shake = Shake256::new();
+        shake.update(b"abc");
+        let mut reader = shake.finalize_xof();
+        assert_eq!(
+            u32::from_le_bytes(expected[..4].try_into().unwrap()),
+            reader.next_u32()
+        );
+        assert_eq!(
+            u64::from_le_bytes(expected[4..12].try_into().unwrap()),
+            reader.next_u64()
+        );
+        let bytes: Vec<u8> = reader.iter_bytes().take(80).collect();
+        assert_eq!(expected[12..92], bytes);
+        let mut rest = [0; 8];
+        reader.squeeze(&mut rest);
+        assert_eq!(expected[92..], rest);
+    }
+
+    #[test]
+    fn seek() {
+        let mut expected = vec![0; 2000];
+        shake256(b"abc", &mut expected);
+
+        let mut shake = Shake256::new();
+        shake.update(b"abc");
+        let mut reader = shake.finalize_xof();
+        // Forward and backward, within and across blocks of 136 bytes
+        for offset in [0, 5, 136, 135, 1000, 1, 1500, 1500, 272] {
+            reader.seek(offset);
+            assert_eq!(offset, reader.position());
+            let mut output = [0; 300];
+            reader.squeeze(&mut output);
+            let offset = offset as usize;
+            assert_eq!(expected[offset..offset + 300], output, "offset {offset}");
+            assert_eq!(offset as u64 + 300, reader.position());
+        }
+    }
+
+    #[test]
+    fn fork() {
+        let mut shake = Shake128::new();
+        shake.update(b"abc");
+        let mut reader = shake.finalize_xof();
+        let mut skipped = [0; 10];
+        reader.squeeze(&mut skipped);
+
+        let mut outputs = Vec::new();
+        for label in [&b"a"[..], b"b", b"", b"a"] {
+            let mut fork = reader.fork(label);
+            let mut output = [0; 200];
+            fork.squeeze(&mut output);
+            outputs.push(output);
+        }
+        let mut clone = reader.clone();
+        let mut output = [0; 200];
+        clone.squeeze(&mut output);
+        outputs.push(output);
+        reader.squeeze(&mut output);
+
+        // Forking leaves the reader unchanged and is deterministic
+        assert_eq!(outputs[4], output);
+        assert_eq!(outputs[0], outputs[3]);
+        for i in 0..3 {
+            for j in i + 1..5 {
+                if j != 3 {
+                    assert_ne!(outputs[i], outputs[j], "{i} {j}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn finalize_vec() {
+        let mut expected = [0; 300];
+        shake128(b"abc", &mut expected);
+        let mut shake = Shake128::new();
+        shake.update(b"abc");
+        assert_eq!(expected[..], shake.finalize_vec(300));
+
+        shake256(b"abc", &mut expected);
+        let mut shake = Shake256::new();
+        shake.update(b"abc");
+        assert_eq!(expected[..7], shake.finalize_vec(7));
+
+        let mut keccak = Keccak::new(512);
+        keccak.update(b"abc");
+        assert_eq!(keccak256(b"abc")[..], keccak.finalize_vec(32));
+        assert!(Shake128::new().finalize_vec(0).is_empty());
+    }
+
+    #[test]
+    fn keccak_with_runtime_capacity() {
+        let mut keccak = Keccak::new(512);
+        keccak.update(b"abc");
+        let mut output = [0; 32];
+        keccak.finalize_xof().squeeze(&mut output);
+        assert_eq!(keccak256(b"abc"), output);
+
+        let mut keccak = Keccak::new(256);
+        keccak.update(b"abc");
+        let mut output = [0; 64];
+        keccak.finalize_xof().squeeze(&mut output);
+        assert_eq!(
+            "ed992674a628509bb2dce176b7c03672ee73b2de6d7fcea0b1bc94729d4e75f9\
+             5dde5b2e0f92a378d2d5cf9ccc9d6ae4ad1bd7a35ea29c0ae7e869a23ab59573",
+            hex::encode(output)
+        );
+
+        let input: Vec<u8> = (0..250).collect();
+        let mut keccak = Keccak::new(800);
+        for piece in input.chunks(33) {
+            keccak.update(piece);
+        }
+        let mut output = [0; 120];
+        keccak.finalize_xof().squeeze(&mut output);
+        assert_eq!(
+            "c9e09eadeb615219f41e475964e020f201f61817f4e54759f710f2ff71763ea6\
+             326b5aea749770d196f2ceb24df883f224d0f6b34b9a85f1d0faa43d89936f59\
+             062f365822b545fb685bd027b59663997f8165a40c15bde6320b412135e790df\
+             537f3d9d7b5b7493a68245e58df95f96df977b1a807e6d41",
+            hex::encode(output)
+        );
+    }
+
+    #[test]
+    fn keccak_with_suffix() {
+        let input: Vec<u8> = (0..250).collect();
+        let mut keccak = Keccak::with_suffix(512, Keccak::SHA3_SUFFIX);
+        keccak.update(&input);
+        let mut output = [0; 32];
+        keccak.finalize_xof().squeeze(&mut output);
+        assert_eq!(sha3_256(&input), output);
+
+        let mut keccak = Keccak::with_suffix(512, Keccak::SHAKE_SUFFIX);
+        keccak.update(&input);
+        let mut output = [0; 200];
+        keccak.finalize_xof().squeeze(&mut output);
+        let mut expected = [0; 200];
+        shake256(&input, &mut expected);
+        assert_eq!(expected, output);
+    }
+
+    #[cfg(not(miri))]
+    #[test]
+    fn compare_to_libcrux() {
+        // Go beyond one block for input and output
+        for i in 0..400 {
+            let input = vec![0; i];
+            let mut my_output = vec![0; i];
+            shake128(&input, &mut my_output);
+            let mut other_output = vec![0; i];
+            libcrux_sha3::shake128_ema(&mut other_output, &input);
+            assert_eq!(my_output, other_output, "len {i} output differs");
+
+            shake256(&input, &mut my_output);
+            libcrux_sha3::shake256_ema(&mut other_output, &input);
+            assert_eq!(my_output, other_output, "len {i} output differs");
+        }
+    }
+}
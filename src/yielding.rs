@@ -0,0 +1,180 @@
+//! SHA-3 for cooperative async executors
+//!
+//! The functions in this module compute the same hashes as their counterparts
+//! in the crate root, but are `async` and yield back to the executor after
+//! every `permutations_per_yield` permutations of the absorbing phase. This
+//! allows firmware running on a single-threaded async executor (e.g.,
+//! [embassy]) to hash large inputs, like the contents of flash memory, without
+//! starving other tasks.
+//!
+//! Yielding is implemented by returning [`Poll::Pending`] once and immediately
+//! waking the task, so the functions work with any executor and do not depend
+//! on a specific async runtime.
+//!
+//! # Example Usage
+//! ```
+//! # use std::{pin::pin, task::{Context, Poll, Waker}};
+//! # use sha3::yielding;
+//! #
+//! # fn block_on<F: Future>(fut: F) -> F::Output {
+//! #     let mut fut = pin!(fut);
+//! #     let mut cx = Context::from_waker(Waker::noop());
+//! #     loop {
+//! #         if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+//! #             return out;
+//! #         }
+//! #     }
+//! # }
+//! #
+//! # block_on(async {
+//! let message = vec![0; 1024 * 1024];
+//! let hash = yielding::sha3_256(&message, 16).await;
+//! assert_eq!(sha3::sha3_256(&message), hash);
+//! # });
+//! ```
+//!
+//! [embassy]: https://embassy.dev
+
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    hasher::Hasher,
+    sponge::{AbsorbState, SHA3_DELIMETED_SUFFIX},
+};
+
+/// SHA-3 Hash with 224 bits (28 bytes) output which yields to the executor
+/// every `permutations_per_yield` permutations.
+///
+/// # Panics
+/// If `permutations_per_yield` is 0.
+pub async fn sha3_224(message: &[u8], permutations_per_yield: usize) -> [u8; 28] {
+    sha3_yielding::<28>(message, permutations_per_yield).await
+}
+
+/// SHA-3 Hash with 256 bits (32 bytes) output which yields to the executor
+/// every `permutations_per_yield` permutations.
+///
+/// # Panics
+/// If `permutations_per_yield` is 0.
+pub async fn sha3_256(message: &[u8], permutations_per_yield: usize) -> [u8; 32] {
+    sha3_yielding::<32>(message, permutations_per_yield).await
+}
+
+/// SHA-3 Hash with 384 bits (48 bytes) output which yields to the executor
+/// every `permutations_per_yield` permutations.
+///
+/// # Panics
+/// If `permutations_per_yield` is 0.
+pub async fn sha3_384(message: &[u8], permutations_per_yield: usize) -> [u8; 48] {
+    sha3_yielding::<48>(message, permutations_per_yield).await
+}
+
+/// SHA-3 Hash with 512 bits (64 bytes) output which yields to the executor
+/// every `permutations_per_yield` permutations.
+///
+/// # Panics
+/// If `permutations_per_yield` is 0.
+pub async fn sha3_512(message: &[u8], permutations_per_yield: usize) -> [u8; 64] {
+    sha3_yielding::<64>(message, permutations_per_yield).await
+}
+
+/// SHA-3 with `N` bytes output which yields to the executor every
+/// `permutations_per_yield` permutations.
+async fn sha3_yielding<const N: usize>(input: &[u8], permutations_per_yield: usize) -> [u8; N] {
+    assert!(
+        permutations_per_yield > 0,
+        "permutations_per_yield must be positive"
+    );
+    let capacity = Hasher::<N>::BITS * 2;
+    let mut sponge = AbsorbState::new(1600 - capacity, capacity, SHA3_DELIMETED_SUFFIX);
+    // Every chunk except the last one results in exactly
+    // `permutations_per_yield` permutations, as we start at a block boundary.
+    let chunk_len = Hasher::<N>::RATE.saturating_mul(permutations_per_yield);
+    let mut chunks = input.chunks(chunk_len).peekable();
+    while let Some(chunk) = chunks.next() {
+        sponge.absorb(chunk);
+        if chunks.peek().is_some() {
+            yield_now().await;
+        }
+    }
+    sponge.finalize()
+}
+
+/// Returns [`Poll::Pending`] once and wakes the task immediately.
+fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::{sha3_224, sha3_256, sha3_384, sha3_512};
+
+    /// Polls the future to completion and returns the output and the number of
+    /// times it yielded.
+    fn block_on<F: Future>(fut: F) -> (F::Output, usize) {
+        let mut fut = pin!(fut);
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut yields = 0;
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(out) => return (out, yields),
+                Poll::Pending => yields += 1,
+            }
+        }
+    }
+
+    #[test]
+    fn same_output_as_one_shot() {
+        for len in [0, 1, 135, 136, 137, 1000, 5000] {
+            let input: Vec<u8> = (0..=255).cycle().take(len).collect();
+            for k in [1, 2, 7, usize::MAX] {
+                assert_eq!(crate::sha3_224(&input), block_on(sha3_224(&input, k)).0);
+                assert_eq!(crate::sha3_256(&input), block_on(sha3_256(&input, k)).0);
+                assert_eq!(crate::sha3_384(&input), block_on(sha3_384(&input, k)).0);
+                assert_eq!(crate::sha3_512(&input), block_on(sha3_512(&input, k)).0);
+            }
+        }
+    }
+
+    #[test]
+    fn yields_every_k_permutations() {
+        // SHA3-256 has a rate of 136 bytes
+        let input = vec![0; 136 * 10];
+        assert_eq!(9, block_on(sha3_256(&input, 1)).1);
+        assert_eq!(4, block_on(sha3_256(&input, 2)).1);
+        assert_eq!(3, block_on(sha3_256(&input, 3)).1);
+        assert_eq!(0, block_on(sha3_256(&input, 10)).1);
+        assert_eq!(0, block_on(sha3_256(&[], 1)).1);
+    }
+
+    #[test]
+    #[should_panic = "permutations_per_yield must be positive"]
+    fn zero_permutations_per_yield() {
+        block_on(sha3_256(b"", 0));
+    }
+}
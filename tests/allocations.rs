@@ -0,0 +1,91 @@
+//! Checks that the hashing paths documented as allocation-free do not
+//! allocate.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::Read,
+};
+
+use sha3::{Sha3_256, Sha3_512, Shake128, Shake256};
+
+/// Allocator counting the allocations of the current thread, as the test
+/// harness allocates concurrently on other threads.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        // SAFETY: Forwarded from the caller.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: Forwarded from the caller.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of allocations performed by `f` on the current thread.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn one_shot_does_not_allocate() {
+    let input = [0x5a; 1000];
+    let mut output = [0; 500];
+    let count = allocations(|| {
+        sha3::sha3_224(&input);
+        sha3::sha3_256(&input);
+        sha3::sha3_384(&input);
+        sha3::sha3_512(&input);
+        sha3::keccak256(&input);
+        sha3::keccak512(&input);
+        sha3::shake128(&input, &mut output);
+        sha3::shake256(&input, &mut output);
+        sha3::sha3_256_into(&input, &mut output[..32]).unwrap();
+    });
+    assert_eq!(0, count);
+}
+
+#[test]
+fn incremental_does_not_allocate() {
+    let input = [0x5a; 1000];
+    let mut output = [0; 500];
+    let count = allocations(|| {
+        let mut hasher = Sha3_256::new();
+        hasher.update(&input);
+        hasher.update_u64_le(42);
+        hasher.finalize_clone();
+        hasher.finalize_reset();
+        hasher.update(&input);
+        let exported = hasher.export_state();
+        Sha3_256::import_state(&exported).unwrap().finalize();
+        hasher.finalize_into(&mut output[..32]).unwrap();
+
+        let mut hasher = Sha3_512::new();
+        hasher.update_bits(&input, 7999);
+        hasher.finalize();
+
+        let mut shake = Shake128::new();
+        shake.update(&input);
+        shake.finalize_xof().squeeze(&mut output);
+
+        let mut shake = Shake256::new();
+        shake.update(&input);
+        let mut reader = shake.finalize_xof();
+        reader.read_exact(&mut output).unwrap();
+        reader.seek(10);
+        reader.next_u64();
+    });
+    assert_eq!(0, count);
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "derive")]
+use sha3::{
+    Sha3_256,
+    digestible::{Sha3Digest, Sha3Digestible, hash_value},
+};
+
+#[derive(Sha3Digest)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Sha3Digest)]
+struct Pair(i32, i32);
+
+#[derive(Sha3Digest)]
+struct Empty;
+
+#[derive(Sha3Digest)]
+struct Labeled<'a, T> {
+    label: &'a str,
+    values: Vec<T>,
+}
+
+#[derive(Sha3Digest)]
+struct Fixed<const N: usize> {
+    values: [u8; N],
+}
+
+#[test]
+fn encodes_name_field_count_and_fields() {
+    let mut hasher = Sha3_256::new();
+    "Point".absorb_into(&mut hasher);
+    2_u64.absorb_into(&mut hasher);
+    1_i32.absorb_into(&mut hasher);
+    (-2_i32).absorb_into(&mut hasher);
+    assert_eq!(
+        hasher.finalize(),
+        hash_value::<32, _>(&Point { x: 1, y: -2 })
+    );
+
+    let mut hasher = Sha3_256::new();
+    "Empty".absorb_into(&mut hasher);
+    0_u64.absorb_into(&mut hasher);
+    assert_eq!(hasher.finalize(), hash_value::<32, _>(&Empty));
+}
+
+#[test]
+fn separates_types() {
+    assert_ne!(
+        hash_value::<32, _>(&Point { x: 1, y: 2 }),
+        hash_value::<32, _>(&Pair(1, 2))
+    );
+    assert_ne!(
+        hash_value::<32, _>(&Labeled {
+            label: "a",
+            values: vec![1_u8, 2]
+        }),
+        hash_value::<32, _>(&Labeled {
+            label: "a",
+            values: vec![1_u16, 2]
+        })
+    );
+}
+
+#[test]
+fn const_generic_named_n() {
+    let mut hasher = Sha3_256::new();
+    "Fixed".absorb_into(&mut hasher);
+    1_u64.absorb_into(&mut hasher);
+    [1_u8, 2].absorb_into(&mut hasher);
+    assert_eq!(
+        hasher.finalize(),
+        hash_value::<32, _>(&Fixed { values: [1, 2] })
+    );
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "internals")]
+use sha3::internals::{FromHexError, State};
+
+/// Keccak-f[1600] applied to the all zero state, taken from the intermediate
+/// values published by the Keccak Team.
+const PERMUTED_ZERO_STATE: &str = "
+    E7 DD E1 40 79 8F 25 F1 8A 47 C0 33 F9 CC D5 84 EE A9 5A A6 1E 26 98 D5
+    4D 49 80 6F 30 47 15 BD 57 D0 53 62 05 4E 28 8B D4 6F 8E 7F 2D A4 97 FF
+    C4 47 46 A4 A0 E5 FE 90 76 2E 19 D6 0C DA 5B 8C 9C 05 19 1B F7 A6 30 AD
+    64 FC 8F D0 B7 5A 93 30 35 D6 17 23 3F A9 5A EB 03 21 71 0D 26 E6 A6 A9
+    5F 55 CF DB 16 7C A5 81 26 C8 47 03 CD 31 B8 43 9F 56 A5 11 1A 2F F2 01
+    61 AE D9 21 5A 63 E5 05 F2 70 C9 8C F2 FE BE 64 11 66 C4 7B 95 70 36 61
+    CB 0E D0 4F 55 5A 7C B8 C8 32 CF 1C 8A E8 3E 8C 14 26 3A AE 22 79 0C 94
+    E4 09 C5 A2 24 F9 41 18 C2 65 04 E7 26 35 F5 16 3B A1 30 7F E9 44 F6 75
+    49 A2 EC 5C 7B FF F1 EA";
+
+#[test]
+fn permute_zero_state() {
+    let mut state = State::default();
+    state.permute();
+    let expected = State::from_hex(PERMUTED_ZERO_STATE).unwrap();
+    assert!(expected == state);
+    assert_eq!(
+        PERMUTED_ZERO_STATE
+            .split_whitespace()
+            .collect::<String>()
+            .to_lowercase(),
+        state.to_hex()
+    );
+    assert_eq!(0xF1258F7940E1DDE7, state.to_lanes()[0]);
+}
+
+#[test]
+fn lanes_roundtrip() {
+    let lanes: [u64; 25] = std::array::from_fn(|i| 0x0123456789abcdef_u64.rotate_left(i as u32));
+    let state = State::from_lanes(lanes);
+    assert_eq!(lanes, state.to_lanes());
+    assert!(state == State::from_hex(&state.to_hex()).unwrap());
+    // Lanes are encoded in little-endian byte order
+    assert!(state.to_hex().starts_with("efcdab8967452301"));
+}
+
+#[test]
+fn invalid_hex() {
+    assert_eq!(
+        Some(FromHexError::InvalidLength(2)),
+        State::from_hex("0102").err()
+    );
+    assert_eq!(Some(FromHexError::OddLength), State::from_hex("010").err());
+    assert_eq!(
+        Some(FromHexError::InvalidCharacter { c: 'x', index: 3 }),
+        State::from_hex("01 x2").err()
+    );
+    let too_long = "00".repeat(201);
+    assert_eq!(
+        Some(FromHexError::InvalidLength(201)),
+        State::from_hex(&too_long).err()
+    );
+}